@@ -0,0 +1,120 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use pathfinding::domains::BitGrid;
+use rand::prelude::*;
+use rand_pcg::Pcg64;
+
+const SIZE: i32 = 512;
+
+fn random_grid(rng: &mut Pcg64, p: f64) -> BitGrid {
+    let mut map = BitGrid::new(SIZE, SIZE);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            map.set(x, y, rng.gen_bool(p));
+        }
+    }
+    map
+}
+
+/// The per-cell equivalent of `BitGrid::dilate`.
+fn dilate_cells(map: &BitGrid, n: i32) -> BitGrid {
+    let mut result = BitGrid::new(SIZE, SIZE);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let blocked = (y - n..=y + n)
+                .filter(|&y| (0..SIZE).contains(&y))
+                .any(|y| {
+                    (x - n..=x + n)
+                        .filter(|&x| (0..SIZE).contains(&x))
+                        .any(|x| map.get(x, y))
+                });
+            result.set(x, y, blocked);
+        }
+    }
+    result
+}
+
+fn benchmark(c: &mut Criterion) {
+    let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+    let a = random_grid(&mut rng, 0.3);
+    let b = random_grid(&mut rng, 0.3);
+    let mut sparse = random_grid(&mut rng, 0.01);
+    sparse.set(0, 0, false);
+
+    c.benchmark_group("fill_rect")
+        .bench_function("bulk", |bench| {
+            let mut map = a.crop(0, 0, SIZE, SIZE);
+            bench.iter(|| map.fill_rect(7, 13, SIZE - 20, SIZE - 30, true));
+        })
+        .bench_function("per_cell", |bench| {
+            let mut map = a.crop(0, 0, SIZE, SIZE);
+            bench.iter(|| {
+                for y in 13..SIZE - 17 {
+                    for x in 7..SIZE - 13 {
+                        map.set(x, y, true);
+                    }
+                }
+            });
+        });
+
+    c.benchmark_group("union")
+        .bench_function("bulk", |bench| {
+            let mut map = a.crop(0, 0, SIZE, SIZE);
+            bench.iter(|| map.union_with(&b));
+        })
+        .bench_function("per_cell", |bench| {
+            let mut map = a.crop(0, 0, SIZE, SIZE);
+            bench.iter(|| {
+                for y in 0..SIZE {
+                    for x in 0..SIZE {
+                        let v = map.get(x, y) || b.get(x, y);
+                        map.set(x, y, v);
+                    }
+                }
+            });
+        });
+
+    c.benchmark_group("dilate")
+        .bench_function("bulk", |bench| {
+            bench.iter_batched_ref(
+                || sparse.crop(0, 0, SIZE, SIZE),
+                |map| map.dilate(3),
+                BatchSize::LargeInput,
+            );
+        })
+        .bench_function("per_cell", |bench| {
+            bench.iter(|| dilate_cells(&sparse, 3));
+        });
+
+    c.benchmark_group("flood_fill")
+        .bench_function("bulk", |bench| {
+            bench.iter_batched_ref(
+                || sparse.crop(0, 0, SIZE, SIZE),
+                |map| map.flood_fill(0, 0, true),
+                BatchSize::LargeInput,
+            );
+        })
+        .bench_function("per_cell", |bench| {
+            bench.iter_batched_ref(
+                || sparse.crop(0, 0, SIZE, SIZE),
+                |map| {
+                    let mut stack = vec![(0, 0)];
+                    while let Some((x, y)) = stack.pop() {
+                        if !(0..SIZE).contains(&x) || !(0..SIZE).contains(&y) || map.get(x, y) {
+                            continue;
+                        }
+                        map.set(x, y, true);
+                        stack.extend_from_slice(&[(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]);
+                    }
+                },
+                BatchSize::LargeInput,
+            );
+        });
+}
+
+criterion_group! {
+    name = bench;
+    config = Criterion::default();
+    targets = benchmark
+}
+
+criterion_main!(bench);
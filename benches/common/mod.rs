@@ -1,6 +1,6 @@
 use std::path::Path;
 
-pub mod movingai;
+pub use pathfinding::movingai;
 
 pub fn walk(base: impl AsRef<Path>, rope: &mut Vec<String>, f: &mut impl FnMut(&Path, &[String])) {
     for entry in base.as_ref().read_dir().unwrap() {
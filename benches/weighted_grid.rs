@@ -0,0 +1,86 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use pathfinding::domains::WeightedGrid;
+use pathfinding::expansion_policy::weighted_grid::avg_four::AverageOfFour;
+use pathfinding::expansion_policy::weighted_grid::jpsw::JpswExpansionPolicy;
+use pathfinding::expansion_policy::weighted_grid::min_cost;
+use pathfinding::node_pool::GridPool;
+use pathfinding::util::{grid_search, octile_heuristic};
+use pathfinding::Owner;
+use rand::prelude::*;
+use rand_pcg::Pcg64;
+
+type Problem = ((i32, i32), (i32, i32));
+
+/// Mostly uniform terrain with patches of swamp and road, and scattered obstacles.
+fn terrain(rng: &mut Pcg64) -> (WeightedGrid<u8>, Vec<Problem>) {
+    let mut map = WeightedGrid::new(512, 512);
+    for y in 0..map.height() {
+        for x in 0..map.width() {
+            *map.get_mut(x, y) = Some(4);
+        }
+    }
+    for _ in 0..150 {
+        let cost = [None, Some(2), Some(9)].choose(rng).copied().unwrap();
+        let x = rng.gen_range(0..map.width());
+        let y = rng.gen_range(0..map.height());
+        let w = rng.gen_range(1..40).min(map.width() - x);
+        let h = rng.gen_range(1..40).min(map.height() - y);
+        for y in y..y + h {
+            for x in x..x + w {
+                *map.get_mut(x, y) = cost;
+            }
+        }
+    }
+
+    let mut problems = vec![];
+    while problems.len() < 100 {
+        let from = (rng.gen_range(0..512), rng.gen_range(0..512));
+        let to = (rng.gen_range(0..512), rng.gen_range(0..512));
+        if map.get(from.0, from.1).is_some() && map.get(to.0, to.1).is_some() {
+            problems.push((from, to));
+        }
+    }
+    (map, problems)
+}
+
+fn benchmark(c: &mut Criterion) {
+    let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+    let (map, problems) = terrain(&mut rng);
+    let min = min_cost(&map);
+
+    c.benchmark_group("terrain")
+        .bench_function("avg_four", |b| {
+            let mut pool = GridPool::new(map.width(), map.height());
+            let mut owner = Owner::new();
+            b.iter(|| {
+                for &(from, to) in &problems {
+                    grid_search(
+                        &mut pool,
+                        &mut owner,
+                        &mut AverageOfFour::new(&map),
+                        octile_heuristic(to, min),
+                        from,
+                        to,
+                    );
+                }
+            });
+        })
+        .bench_function("jpsw", |b| {
+            let mut pool = GridPool::new(map.width(), map.height());
+            let mut owner = Owner::new();
+            let mut ep = JpswExpansionPolicy::new(&map);
+            b.iter(|| {
+                for &(from, to) in &problems {
+                    ep.search(&mut pool, &mut owner, octile_heuristic(to, min), from, to);
+                }
+            });
+        });
+}
+
+criterion_group! {
+    name = bench;
+    config = Criterion::default();
+    targets = benchmark
+}
+
+criterion_main!(bench);
@@ -0,0 +1,407 @@
+//! Text representations of grid maps, mostly for writing tests and reading their failures.
+//!
+//! ```text
+//! ##########
+//! #S.......#
+//! #.####...#
+//! #....#..G#
+//! ##########
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::domains::{BitGrid, WeightedGrid};
+use crate::node_pool::GridPool;
+use crate::util::GridDomain;
+use crate::Owner;
+
+/// The characters used to parse and render maps.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AsciiStyle {
+    pub free: char,
+    pub obstructed: char,
+    pub start: char,
+    pub goal: char,
+    pub path: char,
+    /// Generated but not expanded nodes.
+    pub open: char,
+    /// Expanded nodes.
+    pub closed: char,
+}
+
+impl Default for AsciiStyle {
+    fn default() -> Self {
+        AsciiStyle {
+            free: '.',
+            obstructed: '#',
+            start: 'S',
+            goal: 'G',
+            path: '*',
+            open: 'o',
+            closed: 'x',
+        }
+    }
+}
+
+/// Parses a map, e.g. one rendered by `Display`. Obstructed characters are obstructed cells, and
+/// free, start and goal characters are unobstructed cells. Whitespace around each line and blank
+/// lines are ignored, so maps can be written as indented string literals.
+pub fn parse_bitgrid(s: &str, style: &AsciiStyle) -> Result<BitGrid, AsciiParseError> {
+    let rows = rows(s)?;
+    let mut grid = BitGrid::new(rows[0].len() as i32, rows.len() as i32);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, &c) in row.iter().enumerate() {
+            let blocked = match c {
+                c if c == style.obstructed => true,
+                c if c == style.free || c == style.start || c == style.goal => false,
+                c => return Err(unexpected(x, y, c)),
+            };
+            grid.set(x as i32, y as i32, blocked);
+        }
+    }
+    Ok(grid)
+}
+
+/// Parses a weighted map. Digits are cells with that weight, free, start and goal characters are
+/// cells with a weight of 1, and obstructed characters are obstructed cells.
+pub fn parse_weighted_grid(
+    s: &str,
+    style: &AsciiStyle,
+) -> Result<WeightedGrid<u8>, AsciiParseError> {
+    let rows = rows(s)?;
+    let mut grid = WeightedGrid::new(rows[0].len() as i32, rows.len() as i32);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, &c) in row.iter().enumerate() {
+            let weight = match c {
+                c if c == style.obstructed => None,
+                c if c == style.free || c == style.start || c == style.goal => Some(1),
+                c => match c.to_digit(10) {
+                    Some(d) => Some(d as u8),
+                    None => return Err(unexpected(x, y, c)),
+                },
+            };
+            *grid.get_mut(x as i32, y as i32) = weight;
+        }
+    }
+    Ok(grid)
+}
+
+/// Returns the first cell with the character in the map, in row-major order. Useful for finding
+/// start and goal markers.
+pub fn find_char(s: &str, c: char) -> Option<(i32, i32)> {
+    non_blank_lines(s).enumerate().find_map(|(y, line)| {
+        let x = line.chars().position(|ch| ch == c)?;
+        Some((x as i32, y as i32))
+    })
+}
+
+fn non_blank_lines(s: &str) -> impl Iterator<Item = &str> {
+    s.lines().map(str::trim).filter(|l| !l.is_empty())
+}
+
+fn rows(s: &str) -> Result<Vec<Vec<char>>, AsciiParseError> {
+    let rows: Vec<Vec<char>> = non_blank_lines(s).map(|l| l.chars().collect()).collect();
+    if rows.is_empty() {
+        return Err(AsciiParseError::Empty);
+    }
+    for (y, row) in rows.iter().enumerate() {
+        if row.len() != rows[0].len() {
+            return Err(AsciiParseError::RaggedRow {
+                y: y as i32,
+                expected: rows[0].len() as i32,
+                found: row.len() as i32,
+            });
+        }
+    }
+    Ok(rows)
+}
+
+fn unexpected(x: usize, y: usize, c: char) -> AsciiParseError {
+    AsciiParseError::UnexpectedChar {
+        x: x as i32,
+        y: y as i32,
+        c,
+    }
+}
+
+/// A map which can be rendered as text.
+pub trait AsciiMap {
+    fn width(&self) -> i32;
+    fn height(&self) -> i32;
+    /// The character of the cell, which must be in-bounds.
+    fn cell_char(&self, x: i32, y: i32, style: &AsciiStyle) -> char;
+}
+
+impl AsciiMap for BitGrid {
+    fn width(&self) -> i32 {
+        self.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.height()
+    }
+
+    fn cell_char(&self, x: i32, y: i32, style: &AsciiStyle) -> char {
+        if self.get(x, y) {
+            style.obstructed
+        } else {
+            style.free
+        }
+    }
+}
+
+/// Weights are rendered as digits, with weights above 9 rendered as `+`.
+impl AsciiMap for WeightedGrid<u8> {
+    fn width(&self) -> i32 {
+        self.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.height()
+    }
+
+    fn cell_char(&self, x: i32, y: i32, style: &AsciiStyle) -> char {
+        match self.get(x, y) {
+            None => style.obstructed,
+            Some(&w) if w < 10 => (b'0' + w) as char,
+            Some(_) => '+',
+        }
+    }
+}
+
+fn render(map: &impl AsciiMap, f: &mut Formatter<'_>) -> fmt::Result {
+    AsciiRenderer::new(map).fmt(f)
+}
+
+impl Display for BitGrid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        render(self, f)
+    }
+}
+
+impl fmt::Debug for BitGrid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "BitGrid {}x{}", self.width(), self.height())?;
+        render(self, f)
+    }
+}
+
+impl Display for WeightedGrid<u8> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        render(self, f)
+    }
+}
+
+/// Renders a map with the state of a search drawn over it. From highest to lowest priority, cells
+/// show the start and goal markers, the path, expanded and generated nodes, then the map.
+///
+/// ```ignore
+/// let rendered = AsciiRenderer::new(&map)
+///     .with_search(&pool, &owner)
+///     .with_path(&path)
+///     .with_start(start)
+///     .with_goal(goal);
+/// assert!(found, "no path found:\n{}", rendered);
+/// ```
+pub struct AsciiRenderer<'a, M> {
+    map: &'a M,
+    style: AsciiStyle,
+    search: Option<(&'a GridPool, &'a Owner)>,
+    path: &'a [(i32, i32)],
+    start: Option<(i32, i32)>,
+    goal: Option<(i32, i32)>,
+}
+
+impl<'a, M: AsciiMap> AsciiRenderer<'a, M> {
+    pub fn new(map: &'a M) -> Self {
+        AsciiRenderer {
+            map,
+            style: AsciiStyle::default(),
+            search: None,
+            path: &[],
+            start: None,
+            goal: None,
+        }
+    }
+
+    pub fn with_style(mut self, style: AsciiStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Shows the nodes generated by the last search using the pool.
+    pub fn with_search(mut self, pool: &'a GridPool, owner: &'a Owner) -> Self {
+        self.search = Some((pool, owner));
+        self
+    }
+
+    pub fn with_path(mut self, path: &'a [(i32, i32)]) -> Self {
+        self.path = path;
+        self
+    }
+
+    pub fn with_start(mut self, start: (i32, i32)) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    pub fn with_goal(mut self, goal: (i32, i32)) -> Self {
+        self.goal = Some(goal);
+        self
+    }
+
+    fn cell_char(&self, x: i32, y: i32) -> char {
+        if self.start == Some((x, y)) {
+            return self.style.start;
+        }
+        if self.goal == Some((x, y)) {
+            return self.style.goal;
+        }
+        if self.path.contains(&(x, y)) {
+            return self.style.path;
+        }
+        if let Some((pool, owner)) = self.search {
+            if x < pool.width() && y < pool.height() {
+                if let Some(node) = pool.get(x, y, owner) {
+                    return if owner.ro(node).expansions > 0 {
+                        self.style.closed
+                    } else {
+                        self.style.open
+                    };
+                }
+            }
+        }
+        self.map.cell_char(x, y, &self.style)
+    }
+}
+
+impl<M: AsciiMap> Display for AsciiRenderer<'_, M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for y in 0..self.map.height() {
+            let row: String = (0..self.map.width())
+                .map(|x| self.cell_char(x, y))
+                .collect();
+            writeln!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AsciiParseError {
+    Empty,
+    RaggedRow { y: i32, expected: i32, found: i32 },
+    UnexpectedChar { x: i32, y: i32, c: char },
+}
+
+impl Display for AsciiParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "Map has no rows"),
+            Self::RaggedRow { y, expected, found } => write!(
+                f,
+                "Row {} has {} cells, but the first row has {}",
+                y, found, expected
+            ),
+            Self::UnexpectedChar { x, y, c } => {
+                write!(f, "Unexpected character {:?} at ({}, {})", c, x, y)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AsciiParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expansion_policy::bitgrid::no_corner_cutting::NoCornerCutting;
+    use crate::util::{grid_search, octile_heuristic};
+
+    const MAP: &str = "
+        ##########
+        #S.......#
+        #.####...#
+        #....#..G#
+        ##########
+    ";
+
+    #[test]
+    fn check_roundtrip() {
+        let style = AsciiStyle::default();
+        let map = parse_bitgrid(MAP, &style).unwrap();
+        assert_eq!((map.width(), map.height()), (10, 5));
+        assert!(map.get(0, 0) && !map.get(1, 1) && map.get(2, 2));
+        assert_eq!(find_char(MAP, 'S'), Some((1, 1)));
+        assert_eq!(find_char(MAP, 'G'), Some((8, 3)));
+        let rendered = map.to_string();
+        let expected = MAP.replace(&['S', 'G'][..], ".");
+        let lines = |s| non_blank_lines(s).collect::<Vec<_>>();
+        assert_eq!(lines(&rendered), lines(&expected));
+
+        let weighted = parse_weighted_grid("#19\n.5#", &style).unwrap();
+        assert_eq!(weighted.get(0, 0), None);
+        assert_eq!(weighted.get(2, 0), Some(&9));
+        assert_eq!(weighted.get(0, 1), Some(&1));
+        assert_eq!(weighted.to_string(), "#19\n15#\n");
+
+        assert_eq!(
+            parse_bitgrid(" \n ", &style).err(),
+            Some(AsciiParseError::Empty)
+        );
+        assert_eq!(
+            parse_bitgrid("..\n.", &style).err(),
+            Some(AsciiParseError::RaggedRow {
+                y: 1,
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            parse_bitgrid("..\n.?", &style).err(),
+            Some(AsciiParseError::UnexpectedChar { x: 1, y: 1, c: '?' })
+        );
+    }
+
+    #[test]
+    fn check_render_search() {
+        let map = parse_bitgrid(MAP, &AsciiStyle::default()).unwrap();
+        let (start, goal) = (find_char(MAP, 'S').unwrap(), find_char(MAP, 'G').unwrap());
+        let mut pool = GridPool::new(map.width(), map.height());
+        let mut owner = Owner::new();
+        grid_search(
+            &mut pool,
+            &mut owner,
+            &mut NoCornerCutting::new(&map),
+            octile_heuristic(goal, 1.0),
+            start,
+            goal,
+        );
+        let mut path = vec![goal];
+        while let Some(parent) = owner
+            .ro(pool.get(path[0].0, path[0].1, &owner).unwrap())
+            .parent
+        {
+            path.insert(0, parent);
+        }
+
+        let rendered = AsciiRenderer::new(&map)
+            .with_search(&pool, &owner)
+            .with_path(&path)
+            .with_start(start)
+            .with_goal(goal)
+            .to_string();
+        assert_eq!(find_char(&rendered, 'S'), Some(start));
+        assert_eq!(find_char(&rendered, 'G'), Some(goal));
+        let count = |c| rendered.chars().filter(|&ch| ch == c).count();
+        assert_eq!(count('*'), path.len() - 2);
+        assert!(count('x') + count('o') > 0);
+        // the rendered search parses back to the same map
+        let parsed = parse_bitgrid(
+            &rendered.replace(&['*', 'x', 'o'][..], "."),
+            &AsciiStyle::default(),
+        )
+        .unwrap();
+        assert_eq!(parsed.to_string(), map.to_string());
+    }
+}
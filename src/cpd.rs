@@ -0,0 +1,473 @@
+use crate::domains::BitGrid;
+use crate::expansion_policy::bitgrid::no_corner_cutting::NoCornerCutting;
+use crate::node_pool::GridPool;
+use crate::util::{grid_search, zero_heuristic, Direction, GridDomain};
+use crate::Owner;
+
+const DIRECTIONS: [Direction; 8] = [
+    Direction::NorthWest,
+    Direction::North,
+    Direction::NorthEast,
+    Direction::West,
+    Direction::East,
+    Direction::SouthWest,
+    Direction::South,
+    Direction::SouthEast,
+];
+
+/// Marks a target which is unreachable from the source of a first-move table.
+const NO_MOVE: u8 = 8;
+
+/// Marks a cell which is not part of the vertex ordering (i.e. it is obstructed).
+const UNORDERED: u32 = u32::MAX;
+
+/// A compressed path database over the `NoCornerCutting` movement rules of a `BitGrid`.
+///
+/// For every traversable source cell, the optimal first move towards every other cell is
+/// precomputed with Dijkstra's algorithm. The resulting first-move tables are laid out according to
+/// a depth-first ordering of the traversable cells, which places nearby cells next to each other,
+/// and are then run-length encoded.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize))]
+pub struct BitGridCpd {
+    width: i32,
+    height: i32,
+    /// The position of each cell in the depth-first ordering, in row-major order.
+    order: Box<[u32]>,
+    /// The start of the runs of each source, indexed by the source's position in the ordering.
+    offsets: Box<[usize]>,
+    runs: Box<[Run]>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+struct Run {
+    start: u32,
+    first_move: u8,
+}
+
+impl BitGridCpd {
+    /// note: this runs a full Dijkstra search from every traversable cell, so it is intended to be
+    ///       done offline for anything but small maps.
+    pub fn build(map: &BitGrid) -> Self {
+        let order = dfs_order(map);
+        let mut cells = vec![(0, 0); order.iter().filter(|&&o| o != UNORDERED).count()];
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                let o = order[(x + y * map.width()) as usize];
+                if o != UNORDERED {
+                    cells[o as usize] = (x, y);
+                }
+            }
+        }
+
+        let mut pool = GridPool::new(map.width(), map.height());
+        let mut owner = Owner::new();
+        let mut first_moves = vec![NO_MOVE; cells.len()];
+        let mut offsets = Vec::with_capacity(cells.len() + 1);
+        let mut runs = vec![];
+
+        for &source in &cells {
+            grid_search(
+                &mut pool,
+                &mut owner,
+                &mut NoCornerCutting::new(map),
+                zero_heuristic(),
+                source,
+                (-1, -1),
+            );
+            compute_first_moves(&pool, &owner, &order, map.width(), source, &mut first_moves);
+
+            offsets.push(runs.len());
+            let source_index = order[(source.0 + source.1 * map.width()) as usize] as usize;
+            for (i, &mv) in first_moves.iter().enumerate() {
+                // the first move from a cell to itself is never queried, so it is free to join
+                // whichever run it lands in.
+                if i == source_index && i != 0 {
+                    continue;
+                }
+                match runs[*offsets.last().unwrap()..].last() {
+                    Some(&Run { first_move, .. }) if first_move == mv => {}
+                    _ => runs.push(Run {
+                        start: i as u32,
+                        first_move: mv,
+                    }),
+                }
+            }
+        }
+        offsets.push(runs.len());
+
+        BitGridCpd {
+            width: map.width(),
+            height: map.height(),
+            order: order.into_boxed_slice(),
+            offsets: offsets.into_boxed_slice(),
+            runs: runs.into_boxed_slice(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// The total number of runs stored in the database.
+    pub fn total_runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Returns the first move of an optimal path from `from` to `to`.
+    ///
+    /// Returns `None` if `from == to`, if either cell is obstructed, or if `to` is unreachable.
+    /// Runtime is logarithmic in the number of runs of `from`'s first-move table.
+    #[track_caller]
+    pub fn first_move(&self, from: (i32, i32), to: (i32, i32)) -> Option<Direction> {
+        let source = self.ordering(from)?;
+        let target = self.ordering(to)?;
+        if source == target {
+            return None;
+        }
+
+        let runs = &self.runs[self.offsets[source as usize]..self.offsets[source as usize + 1]];
+        let i = match runs.binary_search_by_key(&target, |r| r.start) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        DIRECTIONS.get(runs[i].first_move as usize).copied()
+    }
+
+    /// Follows first moves from `from` until `to` is reached, returning every visited cell
+    /// (including both endpoints).
+    ///
+    /// Returns `None` if `to` is unreachable.
+    #[track_caller]
+    pub fn path(&self, from: (i32, i32), to: (i32, i32)) -> Option<Vec<(i32, i32)>> {
+        self.ordering(from)?;
+        self.ordering(to)?;
+        let mut path = vec![from];
+        let mut current = from;
+        while current != to {
+            let (dx, dy) = offset(self.first_move(current, to)?);
+            current = (current.0 + dx, current.1 + dy);
+            path.push(current);
+            // optimal paths stay on the map and never revisit a cell, so this only happens if the
+            // first-move tables don't describe optimal paths
+            let off_map =
+                !(0..self.width).contains(&current.0) || !(0..self.height).contains(&current.1);
+            if off_map || path.len() >= self.offsets.len() {
+                return None;
+            }
+        }
+        Some(path)
+    }
+
+    #[track_caller]
+    fn ordering(&self, (x, y): (i32, i32)) -> Option<u32> {
+        if !(0..self.width).contains(&x) || !(0..self.height).contains(&y) {
+            panic!("Grid cell ({}, {}) is out of bounds.", x, y);
+        }
+        match self.order[(x + y * self.width) as usize] {
+            UNORDERED => None,
+            o => Some(o),
+        }
+    }
+}
+
+fn offset(dir: Direction) -> (i32, i32) {
+    match dir {
+        Direction::NorthWest => (-1, -1),
+        Direction::North => (0, -1),
+        Direction::NorthEast => (1, -1),
+        Direction::West => (-1, 0),
+        Direction::East => (1, 0),
+        Direction::SouthWest => (-1, 1),
+        Direction::South => (0, 1),
+        Direction::SouthEast => (1, 1),
+    }
+}
+
+fn direction_index((dx, dy): (i32, i32)) -> u8 {
+    match (dx, dy) {
+        (-1, -1) => 0,
+        (0, -1) => 1,
+        (1, -1) => 2,
+        (-1, 0) => 3,
+        (1, 0) => 4,
+        (-1, 1) => 5,
+        (0, 1) => 6,
+        (1, 1) => 7,
+        _ => unreachable!(),
+    }
+}
+
+/// Orders the traversable cells of the map by a depth-first traversal, so that cells which are
+/// close together tend to be close together in the ordering.
+fn dfs_order(map: &BitGrid) -> Vec<u32> {
+    let mut order = vec![UNORDERED; map.width() as usize * map.height() as usize];
+    let mut next = 0;
+    let mut stack = vec![];
+    for y in 0..map.height() {
+        for x in 0..map.width() {
+            if map.get(x, y) || order[(x + y * map.width()) as usize] != UNORDERED {
+                continue;
+            }
+            stack.push((x, y));
+            while let Some((x, y)) = stack.pop() {
+                let idx = (x + y * map.width()) as usize;
+                if order[idx] != UNORDERED {
+                    continue;
+                }
+                order[idx] = next;
+                next += 1;
+
+                let nbs = map.get_neighbors(x, y);
+                // pushed in reverse so that the traversal visits the orthogonal directions first
+                for &dir in DIRECTIONS.iter().rev() {
+                    let (dx, dy) = offset(dir);
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nbs.contains(dir) || order[(nx + ny * map.width()) as usize] != UNORDERED {
+                        continue;
+                    }
+                    // diagonal moves must not cut corners
+                    if dx != 0 && dy != 0 && (map.get(nx, y) || map.get(x, ny)) {
+                        continue;
+                    }
+                    stack.push((nx, ny));
+                }
+            }
+        }
+    }
+    order
+}
+
+fn compute_first_moves(
+    pool: &GridPool,
+    owner: &Owner,
+    order: &[u32],
+    width: i32,
+    source: (i32, i32),
+    first_moves: &mut [u8],
+) {
+    let mut known = vec![false; first_moves.len()];
+    let mut chain = vec![];
+    for y in 0..pool.height() {
+        for x in 0..width {
+            let o = order[(x + y * width) as usize];
+            if o == UNORDERED || known[o as usize] {
+                continue;
+            }
+
+            // walk up the search tree until we find a cell whose first move is known or a cell
+            // adjacent to the source, then propagate the first move back down the chain.
+            let mut current = (x, y);
+            let mv = loop {
+                let o = order[(current.0 + current.1 * width) as usize] as usize;
+                if known[o] {
+                    break first_moves[o];
+                }
+                chain.push(o);
+                let parent = pool
+                    .get(current.0, current.1, owner)
+                    .and_then(|n| owner.ro(n).parent);
+                match parent {
+                    None => break NO_MOVE,
+                    Some(p) if p == source => {
+                        break direction_index((current.0 - p.0, current.1 - p.1));
+                    }
+                    Some(p) => current = p,
+                }
+            };
+            for o in chain.drain(..) {
+                first_moves[o] = mv;
+                known[o] = true;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde {
+    use serde::{Deserialize, Deserializer};
+
+    use super::{BitGridCpd, Run, NO_MOVE, UNORDERED};
+
+    #[derive(Deserialize)]
+    struct BitGridCpdTransport {
+        width: i32,
+        height: i32,
+        order: Vec<u32>,
+        offsets: Vec<usize>,
+        runs: Vec<Run>,
+    }
+
+    impl BitGridCpdTransport {
+        fn validate(&self) -> Result<(), &'static str> {
+            if self.width <= 0 || self.height <= 0 {
+                return Err("width and height must be positive");
+            }
+            if self.order.len() as u64 != self.width as u64 * self.height as u64 {
+                return Err("ordering doesn't match the map size");
+            }
+            let sources = self
+                .offsets
+                .len()
+                .checked_sub(1)
+                .ok_or("missing run offsets")?;
+            let mut seen = vec![false; sources];
+            for &o in self.order.iter().filter(|&&o| o != UNORDERED) {
+                match seen.get_mut(o as usize) {
+                    Some(seen) if !*seen => *seen = true,
+                    _ => return Err("ordering is not a permutation of the sources"),
+                }
+            }
+            if seen.contains(&false) {
+                return Err("ordering is not a permutation of the sources");
+            }
+            if self.offsets[0] != 0 || self.offsets[sources] != self.runs.len() {
+                return Err("invalid run offsets");
+            }
+            if self.offsets.windows(2).any(|w| w[0] > w[1]) {
+                return Err("invalid run offsets");
+            }
+            for w in self.offsets.windows(2) {
+                let runs = &self.runs[w[0]..w[1]];
+                if runs.first().map(|r| r.start) != Some(0) {
+                    return Err("first-move table doesn't start at the first target");
+                }
+                if runs.windows(2).any(|r| r[0].start >= r[1].start)
+                    || runs.iter().any(|r| r.start as usize >= sources)
+                {
+                    return Err("invalid run starts");
+                }
+                if runs.iter().any(|r| r.first_move > NO_MOVE) {
+                    return Err("invalid first move");
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for BitGridCpd {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let v = BitGridCpdTransport::deserialize(deserializer)?;
+            v.validate().map_err(serde::de::Error::custom)?;
+            Ok(BitGridCpd {
+                width: v.width,
+                height: v.height,
+                order: v.order.into_boxed_slice(),
+                offsets: v.offsets.into_boxed_slice(),
+                runs: v.runs.into_boxed_slice(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::random_grid;
+    use crate::util::octile_heuristic;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    fn path_cost(path: &[(i32, i32)]) -> f64 {
+        path.windows(2)
+            .map(|w| {
+                if w[0].0 != w[1].0 && w[0].1 != w[1].1 {
+                    std::f64::consts::SQRT_2
+                } else {
+                    1.0
+                }
+            })
+            .sum()
+    }
+
+    #[test]
+    fn check_optimal() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let map = random_grid(&mut rng, 23, 17, 0.3);
+        let cpd = BitGridCpd::build(&map);
+        assert!(cpd.total_runs() > 0);
+
+        let mut pool = GridPool::new(map.width(), map.height());
+        let mut owner = Owner::new();
+        for _ in 0..200 {
+            let from = (
+                rng.gen_range(0..map.width()),
+                rng.gen_range(0..map.height()),
+            );
+            let to = (
+                rng.gen_range(0..map.width()),
+                rng.gen_range(0..map.height()),
+            );
+            if map.get(from.0, from.1) || map.get(to.0, to.1) {
+                assert_eq!(cpd.first_move(from, to), None);
+                continue;
+            }
+            grid_search(
+                &mut pool,
+                &mut owner,
+                &mut NoCornerCutting::new(&map),
+                octile_heuristic(to, 1.0),
+                from,
+                to,
+            );
+            match pool.get(to.0, to.1, &owner).map(|n| owner.ro(n).g) {
+                Some(g) if g.is_finite() => {
+                    let path = cpd.path(from, to).unwrap();
+                    assert!((path_cost(&path) - g).abs() < 1e-9);
+                }
+                _ => assert_eq!(cpd.path(from, to), None),
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn check_serde_round_trip() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let map = random_grid(&mut rng, 13, 11, 0.3);
+        let cpd = BitGridCpd::build(&map);
+        let json = serde_json::to_string(&cpd).unwrap();
+        let copy: BitGridCpd = serde_json::from_str(&json).unwrap();
+        assert_eq!(copy.total_runs(), cpd.total_runs());
+        for from in (0..map.height()).flat_map(|y| (0..map.width()).map(move |x| (x, y))) {
+            for to in (0..map.height()).flat_map(|y| (0..map.width()).map(move |x| (x, y))) {
+                assert_eq!(copy.first_move(from, to), cpd.first_move(from, to));
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn check_serde_validation() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let map = random_grid(&mut rng, 13, 11, 0.3);
+        let json = serde_json::to_value(BitGridCpd::build(&map)).unwrap();
+        let corruptions: [fn(&mut serde_json::Value); 6] = [
+            |v| v["width"] = 12.into(),
+            |v| v["offsets"][1] = 0.into(),
+            |v| drop(v["offsets"].as_array_mut().unwrap().pop()),
+            |v| v["runs"][0]["start"] = 1.into(),
+            |v| v["runs"][0]["first_move"] = 9.into(),
+            |v| {
+                let order = v["order"].as_array_mut().unwrap();
+                let i = order.iter().position(|o| o != u32::MAX).unwrap();
+                order[i] = 10_000.into();
+            },
+        ];
+        for corrupt in &corruptions {
+            let mut json = json.clone();
+            corrupt(&mut json);
+            assert!(serde_json::from_value::<BitGridCpd>(json).is_err());
+        }
+    }
+}
@@ -0,0 +1,414 @@
+//! Reading and writing of the file formats of the
+//! [9th DIMACS implementation challenge](http://www.diag.uniroma1.it/challenge9/format.shtml).
+//!
+//! Vertex IDs are 1-based in the files and 0-based in the graphs and queries. Coordinates are kept
+//! in file units, which for the published road networks are millionths of a degree of
+//! `(longitude, latitude)`; see `util::great_circle_graph_heuristic`.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use crate::domains::DirectedGraph;
+
+/// Loads a `.gr` file.
+pub fn load_graph(gr: &Path) -> Result<DirectedGraph<()>, DimacsParseError> {
+    parse_gr(BufReader::new(File::open(gr)?))
+}
+
+/// Loads a `.gr` file along with its coordinates, which are expected to be in a file of the same
+/// name with the `.co` extension (e.g. `USA-road-d.NY.gr` and `USA-road-d.NY.co`).
+pub fn load_graph_with_coordinates(
+    gr: &Path,
+) -> Result<DirectedGraph<(f64, f64)>, DimacsParseError> {
+    let co = BufReader::new(File::open(gr.with_extension("co"))?);
+    parse_graph(BufReader::new(File::open(gr)?), co)
+}
+
+pub fn load_ss(ss: &Path, vertices: usize) -> Result<Vec<usize>, DimacsParseError> {
+    parse_ss(BufReader::new(File::open(ss)?), vertices)
+}
+
+pub fn load_p2p(p2p: &Path, vertices: usize) -> Result<Vec<(usize, usize)>, DimacsParseError> {
+    parse_p2p(BufReader::new(File::open(p2p)?), vertices)
+}
+
+pub fn parse_gr(gr: impl BufRead) -> Result<DirectedGraph<()>, DimacsParseError> {
+    let (n, arcs) = parse_arcs(gr)?;
+    build(vec![(); n], &arcs)
+}
+
+/// Parses a `.gr` file, using the coordinates of the `.co` file as vertex data.
+pub fn parse_graph(
+    gr: impl BufRead,
+    co: impl BufRead,
+) -> Result<DirectedGraph<(f64, f64)>, DimacsParseError> {
+    let coordinates = parse_co(co)?;
+    let (n, arcs) = parse_arcs(gr)?;
+    if coordinates.len() != n {
+        return Err(DimacsParseError::InvalidData);
+    }
+    build(coordinates, &arcs)
+}
+
+/// Parses a `.co` file, returning the coordinates of each vertex.
+pub fn parse_co(co: impl BufRead) -> Result<Vec<(f64, f64)>, DimacsParseError> {
+    // the records are collected first so a bogus vertex count can't cause a huge allocation
+    let mut records = vec![];
+    let [n] = parse_lines(co, &["aux", "sp", "co"], "v", |[n], [id, x, y]| {
+        records.push((vertex(id, n)?, (x.parse()?, y.parse()?)));
+        Ok(())
+    })?;
+    if records.len() != n {
+        return Err(DimacsParseError::InvalidData);
+    }
+    let mut coordinates = vec![None; n];
+    for (v, c) in records {
+        if coordinates[v].replace(c).is_some() {
+            return Err(DimacsParseError::InvalidData);
+        }
+    }
+    coordinates
+        .into_iter()
+        .collect::<Option<_>>()
+        .ok_or(DimacsParseError::InvalidData)
+}
+
+/// Parses a `.ss` file, returning the source vertices of the single-source queries.
+pub fn parse_ss(ss: impl BufRead, vertices: usize) -> Result<Vec<usize>, DimacsParseError> {
+    let mut sources = vec![];
+    let [count] = parse_lines(ss, &["aux", "sp", "ss"], "s", |_, [s]| {
+        sources.push(vertex(s, vertices)?);
+        Ok(())
+    })?;
+    if sources.len() != count {
+        return Err(DimacsParseError::InvalidData);
+    }
+    Ok(sources)
+}
+
+/// Parses a `.p2p` file, returning the `(source, target)` vertices of the point-to-point queries.
+pub fn parse_p2p(
+    p2p: impl BufRead,
+    vertices: usize,
+) -> Result<Vec<(usize, usize)>, DimacsParseError> {
+    let mut queries = vec![];
+    let [count] = parse_lines(p2p, &["aux", "sp", "p2p"], "q", |_, [s, t]| {
+        queries.push((vertex(s, vertices)?, vertex(t, vertices)?));
+        Ok(())
+    })?;
+    if queries.len() != count {
+        return Err(DimacsParseError::InvalidData);
+    }
+    Ok(queries)
+}
+
+/// Writes the edges of the graph in `.gr` format.
+///
+/// note: the format only allows integer arc lengths. other costs are written as decimals, which
+///       this module can read back but other tools might not.
+pub fn write_gr<V>(mut gr: impl Write, graph: &DirectedGraph<V>) -> std::io::Result<()> {
+    writeln!(gr, "p sp {} {}", graph.len(), graph.total_edges())?;
+    for from in 0..graph.len() {
+        for e in graph.outgoing_edges(from) {
+            writeln!(gr, "a {} {} {}", from + 1, e.destination + 1, e.cost)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the vertex data of the graph in `.co` format.
+pub fn write_co(mut co: impl Write, graph: &DirectedGraph<(f64, f64)>) -> std::io::Result<()> {
+    writeln!(co, "p aux sp co {}", graph.len())?;
+    for v in 0..graph.len() {
+        let (x, y) = graph.vertex_data(v);
+        writeln!(co, "v {} {} {}", v + 1, x, y)?;
+    }
+    Ok(())
+}
+
+pub fn write_ss(mut ss: impl Write, sources: &[usize]) -> std::io::Result<()> {
+    writeln!(ss, "p aux sp ss {}", sources.len())?;
+    for s in sources {
+        writeln!(ss, "s {}", s + 1)?;
+    }
+    Ok(())
+}
+
+pub fn write_p2p(mut p2p: impl Write, queries: &[(usize, usize)]) -> std::io::Result<()> {
+    writeln!(p2p, "p aux sp p2p {}", queries.len())?;
+    for (s, t) in queries {
+        writeln!(p2p, "q {} {}", s + 1, t + 1)?;
+    }
+    Ok(())
+}
+
+/// An arc as `(from, to, cost)`.
+type Arc = (usize, usize, f64);
+
+fn parse_arcs(gr: impl BufRead) -> Result<(usize, Vec<Arc>), DimacsParseError> {
+    let mut arcs = vec![];
+    let [n, m] = parse_lines(gr, &["sp"], "a", |[n, _], [from, to, cost]| {
+        arcs.push((vertex(from, n)?, vertex(to, n)?, cost.parse()?));
+        Ok(())
+    })?;
+    // isolated vertices have no records, so the vertex count can't be checked against the data.
+    // instead it's bounded by what the published networks use, which keeps a bogus header from
+    // allocating without limit.
+    if n > u32::MAX as usize {
+        return Err(DimacsParseError::InvalidHeader);
+    }
+    if arcs.len() != m {
+        return Err(DimacsParseError::InvalidData);
+    }
+    Ok((n, arcs))
+}
+
+fn build<V>(vertices: Vec<V>, arcs: &[Arc]) -> Result<DirectedGraph<V>, DimacsParseError> {
+    let mut graph = DirectedGraph::new();
+    for data in vertices {
+        graph.add_vertex(data);
+    }
+    graph
+        .try_add_edges(arcs)
+        .map_err(|_| DimacsParseError::InvalidData)?;
+    Ok(graph)
+}
+
+/// Converts a 1-based vertex ID to a 0-based one, checking that it's one of `n` vertices.
+fn vertex(id: &str, n: usize) -> Result<usize, DimacsParseError> {
+    match id.parse()? {
+        0 => Err(DimacsParseError::InvalidData),
+        id if id > n => Err(DimacsParseError::InvalidData),
+        id => Ok(id - 1),
+    }
+}
+
+/// Reads a file consisting of comment lines, a problem line `p <kind> <H numbers>` and then record
+/// lines `<descriptor> <N fields>`, which are passed to `record` along with the problem numbers.
+fn parse_lines<const H: usize, const N: usize>(
+    mut input: impl BufRead,
+    kind: &[&str],
+    descriptor: &str,
+    mut record: impl FnMut([usize; H], [&str; N]) -> Result<(), DimacsParseError>,
+) -> Result<[usize; H], DimacsParseError> {
+    let mut header = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            break;
+        }
+        let mut fields = line.split_whitespace();
+        match fields.next() {
+            None | Some("c") => {}
+            Some("p") => {
+                if header.is_some() || !kind.iter().all(|&k| fields.next() == Some(k)) {
+                    return Err(DimacsParseError::InvalidHeader);
+                }
+                let mut numbers = [0; H];
+                for n in &mut numbers {
+                    *n = fields
+                        .next()
+                        .ok_or(DimacsParseError::InvalidHeader)?
+                        .parse()?;
+                }
+                if fields.next().is_some() {
+                    return Err(DimacsParseError::InvalidHeader);
+                }
+                header = Some(numbers);
+            }
+            Some(d) if d == descriptor => {
+                let header = header.ok_or(DimacsParseError::InvalidHeader)?;
+                let mut values = [""; N];
+                for v in &mut values {
+                    *v = fields.next().ok_or(DimacsParseError::InvalidData)?;
+                }
+                if fields.next().is_some() {
+                    return Err(DimacsParseError::InvalidData);
+                }
+                record(header, values)?;
+            }
+            Some(_) => return Err(DimacsParseError::InvalidData),
+        }
+    }
+    header.ok_or(DimacsParseError::InvalidHeader)
+}
+
+#[derive(Debug)]
+pub enum DimacsParseError {
+    Stdio(std::io::Error),
+    ParseIntError(std::num::ParseIntError),
+    ParseFloatError(std::num::ParseFloatError),
+    InvalidHeader,
+    InvalidData,
+}
+
+impl From<std::io::Error> for DimacsParseError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Stdio(e)
+    }
+}
+
+impl From<std::num::ParseIntError> for DimacsParseError {
+    fn from(e: std::num::ParseIntError) -> Self {
+        Self::ParseIntError(e)
+    }
+}
+
+impl From<std::num::ParseFloatError> for DimacsParseError {
+    fn from(e: std::num::ParseFloatError) -> Self {
+        Self::ParseFloatError(e)
+    }
+}
+
+impl std::fmt::Display for DimacsParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stdio(e) => write!(f, "{}", e),
+            Self::ParseIntError(e) => write!(f, "{}", e),
+            Self::ParseFloatError(e) => write!(f, "{}", e),
+            Self::InvalidHeader => write!(f, "Invalid or missing problem line"),
+            Self::InvalidData => write!(f, "Invalid data provided"),
+        }
+    }
+}
+
+impl std::error::Error for DimacsParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Stdio(e) => Some(e),
+            Self::ParseIntError(e) => Some(e),
+            Self::ParseFloatError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expansion_policy::graph::OutgoingEdges;
+    use crate::node_pool::IndexPool;
+    use crate::util::{great_circle_graph_heuristic, index_search, zero_heuristic};
+    use crate::Owner;
+
+    // four intersections around a block of manhattan, with one-way streets
+    const GR: &str = "c 9th DIMACS Implementation Challenge
+c
+p sp 4 5
+c
+a 1 2 803
+a 2 3 270
+a 3 4 810
+a 4 1 270
+a 1 3 900
+";
+
+    const CO: &str = "p aux sp co 4
+c
+v 1 -73988000 40748000
+v 2 -73980000 40752000
+v 3 -73982000 40754000
+v 4 -73990000 40750000
+";
+
+    #[test]
+    fn check_round_trip() {
+        let graph = parse_graph(GR.as_bytes(), CO.as_bytes()).unwrap();
+        assert_eq!(graph.len(), 4);
+        assert_eq!(graph.total_edges(), 5);
+        assert_eq!(graph.find_edge(0, 2).unwrap().cost, 900.0);
+        assert!(graph.find_edge(2, 0).is_none());
+        assert_eq!(*graph.vertex_data(3), (-73990000.0, 40750000.0));
+
+        let (mut gr, mut co) = (vec![], vec![]);
+        write_gr(&mut gr, &graph).unwrap();
+        write_co(&mut co, &graph).unwrap();
+        let copy = parse_graph(&gr[..], &co[..]).unwrap();
+        for v in 0..graph.len() {
+            assert_eq!(graph.outgoing_edges(v), copy.outgoing_edges(v));
+            assert_eq!(graph.vertex_data(v), copy.vertex_data(v));
+        }
+
+        let queries = [(0, 3), (2, 1)];
+        let mut p2p = vec![];
+        write_p2p(&mut p2p, &queries).unwrap();
+        assert_eq!(parse_p2p(&p2p[..], 4).unwrap(), queries);
+        let mut ss = vec![];
+        write_ss(&mut ss, &[1, 2]).unwrap();
+        assert_eq!(parse_ss(&ss[..], 4).unwrap(), [1, 2]);
+    }
+
+    #[test]
+    fn check_errors() {
+        let parse = |s: &str| parse_gr(s.as_bytes()).map(|_| ());
+        assert!(matches!(
+            parse("a 1 2 3\n"),
+            Err(DimacsParseError::InvalidHeader)
+        ));
+        assert!(matches!(
+            parse("p sp 2 1\na 1 3 1\n"),
+            Err(DimacsParseError::InvalidData)
+        ));
+        assert!(matches!(
+            parse("p sp 2 2\na 1 2 1\n"),
+            Err(DimacsParseError::InvalidData)
+        ));
+        assert!(matches!(
+            parse("p sp 2 1\na 1 x 1\n"),
+            Err(DimacsParseError::ParseIntError(_))
+        ));
+        assert!(matches!(
+            parse("p aux sp co 2\n"),
+            Err(DimacsParseError::InvalidHeader)
+        ));
+        assert!(matches!(
+            parse("p sp 18446744073709551615 0\n"),
+            Err(DimacsParseError::InvalidHeader)
+        ));
+        // vertex 2 has no coordinates
+        assert!(matches!(
+            parse_co("p aux sp co 2\nv 1 0 0\n".as_bytes()),
+            Err(DimacsParseError::InvalidData)
+        ));
+        assert!(matches!(
+            parse_co("p aux sp co 2\nv 1 0 0\nv 1 0 0\n".as_bytes()),
+            Err(DimacsParseError::InvalidData)
+        ));
+        // the header alone doesn't decide how much is allocated
+        assert!(matches!(
+            parse_co("p aux sp co 18446744073709551615\nv 1 0 0\n".as_bytes()),
+            Err(DimacsParseError::InvalidData)
+        ));
+        assert!(matches!(
+            parse_p2p("p aux sp p2p 1\nq 1 5\n".as_bytes(), 4),
+            Err(DimacsParseError::InvalidData)
+        ));
+    }
+
+    #[test]
+    fn check_heuristics() {
+        let graph = parse_graph(GR.as_bytes(), CO.as_bytes()).unwrap();
+
+        // one degree of longitude along the equator
+        let mut equator = DirectedGraph::new();
+        equator.add_vertex((0.0, 0.0));
+        equator.add_vertex((1e6, 0.0));
+        let h = great_circle_graph_heuristic(&equator, 1, 1e-6, 1.0);
+        assert!((h(0) - 110_574.3).abs() < 0.1);
+        assert_eq!(h(1), 0.0);
+
+        let mut pool = IndexPool::new(graph.len());
+        let mut owner = Owner::new();
+        let mut ep = OutgoingEdges::new(&graph);
+        // arc lengths are in metres
+        let h = great_circle_graph_heuristic(&graph, 3, 1e-6, 1.0);
+        assert!(h(2) > 800.0 && h(2) <= 810.0);
+        index_search(&mut pool, &mut owner, &mut ep, h, 0, 3);
+        let with_h = owner.ro(pool.get(3, &owner).unwrap()).g;
+        index_search(&mut pool, &mut owner, &mut ep, zero_heuristic(), 0, 3);
+        assert_eq!(owner.ro(pool.get(3, &owner).unwrap()).g, with_h);
+        assert_eq!(with_h, 1710.0);
+    }
+}
@@ -2,6 +2,8 @@ use enumset::EnumSet;
 
 use crate::util::Direction;
 
+mod edit;
+
 pub struct BitGrid {
     width: i32,
     height: i32,
@@ -26,22 +28,25 @@ impl BitGrid {
             cells: vec![0; 8 + bytes + 8].into_boxed_slice(),
         };
 
-        // initialize padding to 1s
-        this.cells[..8].fill(!0);
-        let l = this.cells.len();
-        this.cells[l - 8..].fill(!0);
+        this.pad();
+        this
+    }
+
+    /// Sets the padding to 1s.
+    fn pad(&mut self) {
+        self.cells[..8].fill(!0);
+        let l = self.cells.len();
+        self.cells[l - 8..].fill(!0);
         unsafe {
-            for x in -1..width {
-                this.set_unchecked(x, -1, true);
-                this.set_unchecked(x, height, true);
+            for x in -1..self.width {
+                self.set_unchecked(x, -1, true);
+                self.set_unchecked(x, self.height, true);
             }
-            for y in 0..height {
-                this.set_unchecked(-1, y, true);
+            for y in 0..self.height {
+                self.set_unchecked(-1, y, true);
             }
-            this.set_unchecked(width, height, true);
+            self.set_unchecked(self.width, self.height, true);
         }
-
-        this
     }
 
     #[inline(always)]
@@ -247,6 +252,18 @@ mod serde {
     }
 }
 
+/// A grid in which each cell is obstructed with probability `p`.
+#[cfg(test)]
+pub(crate) fn random_grid(rng: &mut impl rand::Rng, width: i32, height: i32, p: f64) -> BitGrid {
+    let mut grid = BitGrid::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            grid.set(x, y, rng.gen_bool(p));
+        }
+    }
+    grid
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,667 @@
+//! Bulk editing operations on `BitGrid`. These work on many cells at a time using the packed
+//! layout, rather than calling `set` for each cell.
+
+use super::BitGrid;
+
+/// The number of cells read or written by one unaligned word access.
+const CHUNK: i32 = 57;
+
+impl BitGrid {
+    /// Sets every cell in the rectangle to `v`.
+    #[track_caller]
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, v: bool) {
+        self.rect_bounds_check(x, y, width, height);
+        for y in y..y + height {
+            unsafe {
+                // SAFETY: The rectangle was bounds checked above.
+                self.fill_span_unchecked(x, x + width, y, v)
+            }
+        }
+    }
+
+    /// Returns the number of obstructed cells.
+    pub fn count_obstacles(&self) -> usize {
+        let mut count = 0;
+        for y in 0..self.height {
+            for x in (0..self.width).step_by(CHUNK as usize) {
+                let n = (self.width - x).min(CHUNK);
+                unsafe {
+                    // SAFETY: (x, y) is in-bounds.
+                    count += (self.get_row_unchecked(x, y) & mask(n)).count_ones() as usize;
+                }
+            }
+        }
+        count
+    }
+
+    /// Sets the 4-connected region of cells with the same value as `(x, y)` to `v`. Returns the
+    /// number of cells changed.
+    #[track_caller]
+    pub fn flood_fill(&mut self, x: i32, y: i32, v: bool) -> usize {
+        self.unpadded_bounds_check(x, y);
+        let old = self.get(x, y);
+        if old == v {
+            return 0;
+        }
+        // a run of cells with value `old` has no set bits in `row ^ flip`
+        let flip = if old { !0 } else { 0 };
+        let mut count = 0;
+        let mut stack = vec![(x, y)];
+        while let Some((x, y)) = stack.pop() {
+            unsafe {
+                // SAFETY: Only in-bounds cells are pushed to the stack. The spans are clamped to
+                //         the row, so the rows read are at most one cell out of bounds.
+                if self.get_unchecked(x, y) != old {
+                    continue;
+                }
+
+                let mut end = x;
+                loop {
+                    let run = ((self.get_row_unchecked(end, y) ^ flip) & mask(CHUNK))
+                        .trailing_zeros()
+                        .min(CHUNK as u32) as i32;
+                    end += run;
+                    if run < CHUNK || end >= self.width {
+                        break;
+                    }
+                }
+                let end = end.min(self.width);
+
+                let mut start = x + 1;
+                loop {
+                    let run = ((self.get_row_upper_unchecked(start - 1, y) ^ flip) & !0 << 7)
+                        .leading_zeros()
+                        .min(CHUNK as u32) as i32;
+                    start -= run;
+                    if run < CHUNK || start <= 0 {
+                        break;
+                    }
+                }
+                let start = start.max(0);
+
+                self.fill_span_unchecked(start, end, y, v);
+                count += (end - start) as usize;
+
+                for ny in [y - 1, y + 1] {
+                    if !(0..self.height).contains(&ny) {
+                        continue;
+                    }
+                    let mut prev = false;
+                    for nx in start..end {
+                        let cur = self.get_unchecked(nx, ny) == old;
+                        if cur && !prev {
+                            stack.push((nx, ny));
+                        }
+                        prev = cur;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    /// Obstructs every cell that is obstructed in `self` or `other`.
+    #[track_caller]
+    pub fn union_with(&mut self, other: &BitGrid) {
+        self.zip_with(other, |a, b| a | b);
+    }
+
+    /// Obstructs every cell that is obstructed in both `self` and `other`, and clears the rest.
+    #[track_caller]
+    pub fn intersect_with(&mut self, other: &BitGrid) {
+        self.zip_with(other, |a, b| a & b);
+    }
+
+    /// Clears every cell that is obstructed in `other`.
+    #[track_caller]
+    pub fn difference_with(&mut self, other: &BitGrid) {
+        self.zip_with(other, |a, b| a & !b);
+        // the padding was cleared too
+        self.pad();
+    }
+
+    /// Returns a copy of the rectangle as a new grid.
+    #[track_caller]
+    pub fn crop(&self, x: i32, y: i32, width: i32, height: i32) -> BitGrid {
+        self.rect_bounds_check(x, y, width, height);
+        let mut result = BitGrid::new(width, height);
+        let mut row = vec![];
+        for dy in 0..height {
+            unsafe {
+                // SAFETY: The rectangle was bounds checked above, and is the size of the result.
+                self.read_span_unchecked(x, y + dy, width, &mut row);
+                result.write_span_unchecked(0, dy, width, &row);
+            }
+        }
+        result
+    }
+
+    /// Copies `src` into this grid with its top-left corner at `(x, y)`. Cells of `src` which fall
+    /// outside this grid are ignored.
+    pub fn paste(&mut self, src: &BitGrid, x: i32, y: i32) {
+        let (sx, sy) = ((-x).max(0), (-y).max(0));
+        let width = (src.width - sx).min(self.width - x - sx);
+        let height = (src.height - sy).min(self.height - y - sy);
+        if width <= 0 || height <= 0 {
+            return;
+        }
+        let mut row = vec![];
+        for dy in 0..height {
+            unsafe {
+                // SAFETY: The rectangle was clipped to both grids above.
+                src.read_span_unchecked(sx, sy + dy, width, &mut row);
+                self.write_span_unchecked(x + sx, y + sy + dy, width, &row);
+            }
+        }
+    }
+
+    /// Returns a copy of this grid with a new size, keeping the top-left corner in place. Cells
+    /// which were not in this grid are set to `fill`.
+    pub fn resized(&self, width: i32, height: i32, fill: bool) -> BitGrid {
+        let mut result = BitGrid::new(width, height);
+        if fill {
+            result.fill_rect(0, 0, width, height, true);
+        }
+        result.paste(self, 0, 0);
+        result
+    }
+
+    /// Returns a copy of this grid with the x and y axes swapped.
+    pub fn transpose(&self) -> BitGrid {
+        let mut result = BitGrid::new(self.height, self.width);
+        for y in (0..self.height).step_by(8) {
+            let rows = (self.height - y).min(8);
+            for x in (0..self.width).step_by(8) {
+                let cols = (self.width - x).min(8);
+                unsafe {
+                    // SAFETY: The 8 by 8 block is clipped to this grid, and its transpose is in
+                    //         the result.
+                    let mut block = 0;
+                    for i in 0..rows {
+                        block |= (self.get_row_unchecked(x, y + i) & mask(cols)) << (8 * i);
+                    }
+                    let block = transpose8(block);
+                    for j in 0..cols {
+                        result.write_bits_unchecked(y, x + j, block >> (8 * j), rows);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns a copy of this grid rotated a quarter turn clockwise.
+    pub fn rotate_cw(&self) -> BitGrid {
+        let mut result = self.transpose();
+        result.flip_horizontal();
+        result
+    }
+
+    /// Returns a copy of this grid rotated a quarter turn counter-clockwise.
+    pub fn rotate_ccw(&self) -> BitGrid {
+        let mut result = self.transpose();
+        result.flip_vertical();
+        result
+    }
+
+    /// Mirrors the grid left to right.
+    pub fn flip_horizontal(&mut self) {
+        let words = (self.width as usize - 1) / 64 + 1;
+        let shift = words * 64 - self.width as usize;
+        let mut row = vec![];
+        for y in 0..self.height {
+            unsafe {
+                // SAFETY: The whole row is in-bounds.
+                self.read_span_unchecked(0, y, self.width, &mut row);
+                row.reverse();
+                for w in &mut row {
+                    *w = w.reverse_bits();
+                }
+                shift_right(&mut row, shift);
+                self.write_span_unchecked(0, y, self.width, &row);
+            }
+        }
+    }
+
+    /// Mirrors the grid top to bottom.
+    pub fn flip_vertical(&mut self) {
+        let (mut top, mut bottom) = (vec![], vec![]);
+        for y in 0..self.height / 2 {
+            let other = self.height - 1 - y;
+            unsafe {
+                // SAFETY: Both rows are in-bounds.
+                self.read_span_unchecked(0, y, self.width, &mut top);
+                self.read_span_unchecked(0, other, self.width, &mut bottom);
+                self.write_span_unchecked(0, y, self.width, &bottom);
+                self.write_span_unchecked(0, other, self.width, &top);
+            }
+        }
+    }
+
+    /// Obstructs every cell within `n` cells (in Chebyshev distance) of an obstructed cell. Cells
+    /// outside the grid are treated as unobstructed.
+    ///
+    /// note: runtime is linear in the number of cells times the logarithm of `n`.
+    #[track_caller]
+    pub fn dilate(&mut self, n: i32) {
+        assert!(n >= 0, "n must not be negative");
+        let mut rows = self.read_rows();
+        dilate_rows(&mut rows, self.width as usize, n as usize);
+        self.write_rows(&rows);
+    }
+
+    /// Clears every cell within `n` cells (in Chebyshev distance) of an unobstructed cell. Cells
+    /// outside the grid are treated as obstructed.
+    ///
+    /// note: runtime is linear in the number of cells times the logarithm of `n`.
+    #[track_caller]
+    pub fn erode(&mut self, n: i32) {
+        assert!(n >= 0, "n must not be negative");
+        let mut rows = self.read_rows();
+        let width = self.width as usize;
+        for row in &mut rows {
+            invert(row, width);
+        }
+        dilate_rows(&mut rows, width, n as usize);
+        for row in &mut rows {
+            invert(row, width);
+        }
+        self.write_rows(&rows);
+    }
+
+    /// Sets the cells of row `y` from a bitset, 64 cells per word with the lowest bit first. Bits
+    /// beyond the width of the grid are ignored.
+    #[track_caller]
+    pub(crate) fn set_row_bits(&mut self, y: i32, bits: &[u64]) {
+        assert!(
+            bits.len() * 64 >= self.width as usize,
+            "bitset must cover the width of the grid"
+        );
+        self.rect_bounds_check(0, y, self.width, 1);
+        unsafe {
+            // SAFETY: The row is in-bounds, and there are enough words to cover it.
+            self.write_span_unchecked(0, y, self.width, bits);
+        }
+    }
+
+    fn read_rows(&self) -> Vec<Vec<u64>> {
+        (0..self.height)
+            .map(|y| {
+                let mut row = vec![];
+                unsafe {
+                    // SAFETY: The whole row is in-bounds.
+                    self.read_span_unchecked(0, y, self.width, &mut row);
+                }
+                row
+            })
+            .collect()
+    }
+
+    fn write_rows(&mut self, rows: &[Vec<u64>]) {
+        for (y, row) in rows.iter().enumerate() {
+            unsafe {
+                // SAFETY: There is one row per row of the grid, each as wide as the grid.
+                self.write_span_unchecked(0, y as i32, self.width, row);
+            }
+        }
+    }
+
+    #[track_caller]
+    fn zip_with(&mut self, other: &BitGrid, f: impl Fn(u8, u8) -> u8) {
+        assert!(
+            self.width == other.width && self.height == other.height,
+            "grids must have the same size"
+        );
+        for (a, &b) in self.cells.iter_mut().zip(other.cells.iter()) {
+            *a = f(*a, b);
+        }
+    }
+
+    /// Reads the cells `x..x+len` of row `y` into a bitset, 64 cells per word.
+    ///
+    /// SAFETY: `x` and `x+len` must be in `0..=width`, `y` must be in `0..height`.
+    unsafe fn read_span_unchecked(&self, x: i32, y: i32, len: i32, out: &mut Vec<u64>) {
+        out.clear();
+        for i in (0..len).step_by(64) {
+            let n = (len - i).min(64);
+            let mut word = self.get_row_unchecked(x + i, y);
+            if n > CHUNK {
+                word |= self.get_row_unchecked(x + i + CHUNK, y) << CHUNK;
+            }
+            out.push(word & mask(n));
+        }
+    }
+
+    /// Writes a bitset produced by `read_span_unchecked` to the cells `x..x+len` of row `y`.
+    ///
+    /// SAFETY: `x` and `x+len` must be in `0..=width`, `y` must be in `0..height`.
+    unsafe fn write_span_unchecked(&mut self, x: i32, y: i32, len: i32, bits: &[u64]) {
+        for (j, &word) in bits.iter().enumerate() {
+            let i = j as i32 * 64;
+            let n = (len - i).min(64);
+            self.write_bits_unchecked(x + i, y, word, n.min(CHUNK));
+            if n > CHUNK {
+                self.write_bits_unchecked(x + i + CHUNK, y, word >> CHUNK, n - CHUNK);
+            }
+        }
+    }
+
+    /// SAFETY: `x0` and `x1` must be in `0..=width`, `y` must be in `0..height`.
+    unsafe fn fill_span_unchecked(&mut self, x0: i32, x1: i32, y: i32, v: bool) {
+        let bits = if v { !0 } else { 0 };
+        for x in (x0..x1).step_by(CHUNK as usize) {
+            self.write_bits_unchecked(x, y, bits, (x1 - x).min(CHUNK));
+        }
+    }
+
+    /// Writes the lowest `n` bits of `bits` to the cells `x..x+n` of row `y`.
+    ///
+    /// SAFETY: `n` must be in `0..=57`, `x` and `x+n` must be in `0..=width`, `y` must be in
+    /// `0..height`.
+    #[inline(always)]
+    unsafe fn write_bits_unchecked(&mut self, x: i32, y: i32, bits: u64, n: i32) {
+        let (idx, bit) = self.locate(x, y);
+        let ptr: *mut u8 = self.cells.get_unchecked_mut(idx);
+        let mask = mask(n) << bit;
+        let w = (ptr as *const u64).read_unaligned().to_le();
+        let w = w & !mask | bits << bit & mask;
+        (ptr as *mut u64).write_unaligned(w.to_le());
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    fn rect_bounds_check(&self, x: i32, y: i32, width: i32, height: i32) {
+        if x < 0
+            || y < 0
+            || width < 0
+            || height < 0
+            || x + width > self.width
+            || y + height > self.height
+        {
+            panic!(
+                "Grid rectangle ({}, {}) to ({}, {}) is out of bounds.",
+                x,
+                y,
+                x + width,
+                y + height
+            );
+        }
+    }
+}
+
+#[inline(always)]
+fn mask(n: i32) -> u64 {
+    if n >= 64 {
+        !0
+    } else {
+        (1 << n) - 1
+    }
+}
+
+/// Transposes an 8 by 8 bit matrix stored one row per byte.
+fn transpose8(mut x: u64) -> u64 {
+    let t = (x ^ x >> 7) & 0x00AA_00AA_00AA_00AA;
+    x ^= t ^ t << 7;
+    let t = (x ^ x >> 14) & 0x0000_CCCC_0000_CCCC;
+    x ^= t ^ t << 14;
+    let t = (x ^ x >> 28) & 0x0000_0000_F0F0_F0F0;
+    x ^= t ^ t << 28;
+    x
+}
+
+/// Moves every bit of the bitset `s` places towards bit 0.
+fn shift_right(bits: &mut [u64], s: usize) {
+    let (words, s) = (s / 64, s % 64);
+    for i in 0..bits.len() {
+        let lo = bits.get(i + words).copied().unwrap_or(0);
+        let hi = bits.get(i + words + 1).copied().unwrap_or(0);
+        bits[i] = if s == 0 { lo } else { lo >> s | hi << (64 - s) };
+    }
+}
+
+/// Moves every bit of the bitset `s` places away from bit 0, dropping bits past `len`.
+fn shift_left(bits: &mut [u64], s: usize, len: usize) {
+    let (words, s) = (s / 64, s % 64);
+    for i in (0..bits.len()).rev() {
+        let hi = match i.checked_sub(words) {
+            Some(j) => bits[j],
+            None => 0,
+        };
+        let lo = match i.checked_sub(words + 1) {
+            Some(j) => bits[j],
+            None => 0,
+        };
+        bits[i] = if s == 0 { hi } else { hi << s | lo >> (64 - s) };
+    }
+    if let Some(last) = bits.last_mut() {
+        *last &= mask((len - (len - 1) / 64 * 64) as i32);
+    }
+}
+
+fn invert(bits: &mut [u64], len: usize) {
+    for w in bits.iter_mut() {
+        *w = !*w;
+    }
+    if let Some(last) = bits.last_mut() {
+        *last &= mask((len - (len - 1) / 64 * 64) as i32);
+    }
+}
+
+/// Dilates the rows by `n` cells, first along each row and then across rows. Each step doubles the
+/// covered radius, so there are only logarithmically many steps.
+fn dilate_rows(rows: &mut [Vec<u64>], len: usize, n: usize) {
+    let mut tmp = vec![];
+    for row in rows.iter_mut() {
+        let mut covered = 0;
+        while covered < n {
+            let s = (covered + 1).min(n - covered);
+            tmp.clone_from(row);
+            shift_left(&mut tmp, s, len);
+            let mut right = row.clone();
+            shift_right(&mut right, s);
+            for ((w, l), r) in row.iter_mut().zip(&tmp).zip(&right) {
+                *w |= l | r;
+            }
+            covered += s;
+        }
+    }
+
+    let mut covered = 0;
+    while covered < n {
+        let s = (covered + 1).min(n - covered);
+        let prev = rows.to_vec();
+        for (y, row) in rows.iter_mut().enumerate() {
+            for other in [y.checked_sub(s), Some(y + s)].iter().flatten() {
+                if let Some(other) = prev.get(*other) {
+                    for (w, o) in row.iter_mut().zip(other) {
+                        *w |= o;
+                    }
+                }
+            }
+        }
+        covered += s;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::random_grid;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    fn cells(grid: &BitGrid) -> Vec<Vec<bool>> {
+        (0..grid.height())
+            .map(|y| (0..grid.width()).map(|x| grid.get(x, y)).collect())
+            .collect()
+    }
+
+    fn check_padding(grid: &BitGrid) {
+        for x in -1..grid.width() + 1 {
+            assert!(grid.get(x, -1) && grid.get(x, grid.height()));
+        }
+        for y in -1..grid.height() + 1 {
+            assert!(grid.get(-1, y) && grid.get(grid.width(), y));
+        }
+    }
+
+    #[test]
+    fn check_rects_and_boolean_ops() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let mut grid = random_grid(&mut rng, 173, 19, 0.5);
+        let mut expected = cells(&grid);
+        for _ in 0..50 {
+            let (x, y) = (rng.gen_range(0..173), rng.gen_range(0..19));
+            let (w, h) = (rng.gen_range(0..=173 - x), rng.gen_range(0..=19 - y));
+            let v = rng.gen();
+            grid.fill_rect(x, y, w, h, v);
+            for row in &mut expected[y as usize..(y + h) as usize] {
+                row[x as usize..(x + w) as usize].fill(v);
+            }
+            assert_eq!(cells(&grid), expected);
+            let count = expected.iter().flatten().filter(|&&b| b).count();
+            assert_eq!(grid.count_obstacles(), count);
+        }
+        check_padding(&grid);
+
+        let other = random_grid(&mut rng, 173, 19, 0.5);
+        let ops: [(fn(&mut BitGrid, &BitGrid), fn(bool, bool) -> bool); 3] = [
+            (BitGrid::union_with, |a, b| a | b),
+            (BitGrid::intersect_with, |a, b| a & b),
+            (BitGrid::difference_with, |a, b| a & !b),
+        ];
+        for (op, f) in ops.iter() {
+            let mut result = grid.resized(173, 19, false);
+            op(&mut result, &other);
+            check_padding(&result);
+            for y in 0..19 {
+                for x in 0..173 {
+                    assert_eq!(result.get(x, y), f(grid.get(x, y), other.get(x, y)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn check_flood_fill() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        for &p in &[0.1, 0.4, 0.6, 0.9] {
+            let mut grid = random_grid(&mut rng, 131, 29, p);
+            for _ in 0..20 {
+                let (x, y) = (rng.gen_range(0..131), rng.gen_range(0..29));
+                let v = !grid.get(x, y);
+
+                let mut expected = cells(&grid);
+                let mut stack = vec![(x, y)];
+                let mut count = 0;
+                while let Some((x, y)) = stack.pop() {
+                    if !(0..131).contains(&x) || !(0..29).contains(&y) {
+                        continue;
+                    }
+                    if expected[y as usize][x as usize] == v {
+                        continue;
+                    }
+                    expected[y as usize][x as usize] = v;
+                    count += 1;
+                    stack.extend([(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]);
+                }
+
+                assert_eq!(grid.flood_fill(x, y, v), count);
+                assert_eq!(cells(&grid), expected);
+                check_padding(&grid);
+            }
+        }
+    }
+
+    #[test]
+    fn check_crop_paste_and_transforms() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let grid = random_grid(&mut rng, 150, 71, 0.5);
+
+        let crop = grid.crop(13, 7, 130, 41);
+        for y in 0..41 {
+            for x in 0..130 {
+                assert_eq!(crop.get(x, y), grid.get(x + 13, y + 7));
+            }
+        }
+        check_padding(&crop);
+
+        for &(px, py) in &[(-20, -3), (40, 50), (100, 2), (-129, -40), (150, 0)] {
+            let mut pasted = grid.resized(150, 71, false);
+            pasted.paste(&crop, px, py);
+            for y in 0..71 {
+                for x in 0..150 {
+                    let (cx, cy) = (x - px, y - py);
+                    let expected = if (0..130).contains(&cx) && (0..41).contains(&cy) {
+                        crop.get(cx, cy)
+                    } else {
+                        grid.get(x, y)
+                    };
+                    assert_eq!(pasted.get(x, y), expected);
+                }
+            }
+            check_padding(&pasted);
+        }
+
+        let resized = crop.resized(200, 30, true);
+        for y in 0..30 {
+            for x in 0..200 {
+                let expected = if x < 130 { crop.get(x, y) } else { true };
+                assert_eq!(resized.get(x, y), expected);
+            }
+        }
+
+        let (w, h) = (grid.width(), grid.height());
+        let transposed = grid.transpose();
+        let cw = grid.rotate_cw();
+        let ccw = grid.rotate_ccw();
+        let mut flipped_h = grid.resized(w, h, false);
+        flipped_h.flip_horizontal();
+        let mut flipped_v = grid.resized(w, h, false);
+        flipped_v.flip_vertical();
+        for g in &[&transposed, &cw, &ccw] {
+            assert_eq!((g.width(), g.height()), (h, w));
+            check_padding(g);
+        }
+        check_padding(&flipped_h);
+        check_padding(&flipped_v);
+        for y in 0..h {
+            for x in 0..w {
+                let v = grid.get(x, y);
+                assert_eq!(transposed.get(y, x), v);
+                assert_eq!(cw.get(h - 1 - y, x), v);
+                assert_eq!(ccw.get(y, w - 1 - x), v);
+                assert_eq!(flipped_h.get(w - 1 - x, y), v);
+                assert_eq!(flipped_v.get(x, h - 1 - y), v);
+            }
+        }
+    }
+
+    #[test]
+    fn check_morphology() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let grid = random_grid(&mut rng, 141, 23, 0.03);
+        for &n in &[0, 1, 2, 5, 13, 70] {
+            let mut dilated = grid.resized(141, 23, false);
+            dilated.dilate(n);
+            // the complement of the grid
+            let mut eroded = BitGrid::new(141, 23);
+            eroded.fill_rect(0, 0, 141, 23, true);
+            eroded.difference_with(&grid);
+            eroded.erode(n);
+            for y in 0..23 {
+                for x in 0..141 {
+                    let near = (y - n..=y + n).any(|ny| {
+                        (x - n..=x + n).any(|nx| {
+                            (0..141).contains(&nx) && (0..23).contains(&ny) && grid.get(nx, ny)
+                        })
+                    });
+                    assert_eq!(dilated.get(x, y), near);
+                    assert_eq!(eroded.get(x, y), !near);
+                }
+            }
+            check_padding(&dilated);
+            check_padding(&eroded);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use crate::domains::BitGrid;
+
+/// The true clearance of each cell of a `BitGrid`: the size of the largest square of unobstructed
+/// cells with the cell as its top-left corner. Obstructed cells have a clearance of 0.
+///
+/// An agent occupying a `size` by `size` square of cells, located by its top-left cell, fits at
+/// every cell with a clearance of at least `size`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClearanceMap {
+    width: i32,
+    height: i32,
+    cells: Box<[u16]>,
+}
+
+impl ClearanceMap {
+    pub fn new(map: &BitGrid) -> Self {
+        let (width, height) = (map.width(), map.height());
+        // same padding as WeightedGrid; padding cells have no clearance
+        let padded_width = width as usize + 1;
+        let padded_height = height as usize + 2;
+        let mut this = ClearanceMap {
+            width,
+            height,
+            cells: vec![0; padded_width * padded_height + 1].into_boxed_slice(),
+        };
+
+        for y in (0..height).rev() {
+            for x in (0..width).rev() {
+                if map.get(x, y) {
+                    continue;
+                }
+                unsafe {
+                    // SAFETY: (x, y) is in-bounds, so its south-east neighbors are in the padding.
+                    let c = this
+                        .get_unchecked(x + 1, y)
+                        .min(this.get_unchecked(x, y + 1))
+                        .min(this.get_unchecked(x + 1, y + 1));
+                    let idx = this.locate(x, y);
+                    *this.cells.get_unchecked_mut(idx) = c.saturating_add(1);
+                }
+            }
+        }
+
+        this
+    }
+
+    #[inline(always)]
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    pub fn get(&self, x: i32, y: i32) -> u16 {
+        if !(-1..self.width + 1).contains(&x) || !(-1..self.height + 1).contains(&y) {
+            panic!("Grid cell ({}, {}) is out of bounds.", x, y);
+        }
+        unsafe { self.get_unchecked(x, y) }
+    }
+
+    /// SAFETY: `x` must be in `-1..width+1`, `y` must be in `-1..height+1`.
+    /// Padding cells can be relied upon to have no clearance.
+    #[inline(always)]
+    pub unsafe fn get_unchecked(&self, x: i32, y: i32) -> u16 {
+        *self.cells.get_unchecked(self.locate(x, y))
+    }
+
+    /// Builds a map in which cells are obstructed unless an agent of the given size, located by its
+    /// top-left cell, fits there. Searching the result with any `BitGrid` policy that doesn't cut
+    /// corners, such as `JpsExpansionPolicy`, produces paths the agent can follow.
+    pub fn inflate(&self, size: u16) -> BitGrid {
+        let mut map = BitGrid::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                map.set(x, y, self.get(x, y) < size);
+            }
+        }
+        map
+    }
+
+    /// Builds a map in which cells are obstructed unless an agent covering all cells within
+    /// `radius` cells (by Chebyshev distance) of its center cell fits there. Unlike
+    /// [`ClearanceMap::inflate`], agents are located by their center cell.
+    pub fn inflate_radius(&self, radius: u16) -> BitGrid {
+        let size = radius.saturating_mul(2).saturating_add(1);
+        let r = radius as i32;
+        let mut map = BitGrid::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let fits = x >= r && y >= r && self.get(x - r, y - r) >= size;
+                map.set(x, y, !fits);
+            }
+        }
+        map
+    }
+
+    #[inline(always)]
+    fn locate(&self, x: i32, y: i32) -> usize {
+        #[cfg(debug_assertions)]
+        if !(-1..self.width + 1).contains(&x) || !(-1..self.height + 1).contains(&y) {
+            panic!("Grid cell ({}, {}) is out of bounds.", x, y);
+        }
+
+        let padded_width = self.width as usize + 1;
+        (y + 1) as usize * padded_width + (x + 1) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::random_grid;
+    use rand_pcg::Pcg64;
+
+    #[test]
+    fn check_clearance() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let map = random_grid(&mut rng, 23, 17, 0.1);
+        let clearance = ClearanceMap::new(&map);
+        let fits = |x: i32, y: i32, size: i32| {
+            x + size <= map.width()
+                && y + size <= map.height()
+                && (y..y + size).all(|y| (x..x + size).all(|x| !map.get(x, y)))
+        };
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                let c = clearance.get(x, y) as i32;
+                assert!(c == 0 || fits(x, y, c));
+                assert!(!fits(x, y, c + 1));
+            }
+        }
+
+        let inflated = clearance.inflate(3);
+        let by_radius = clearance.inflate_radius(1);
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                assert_eq!(inflated.get(x, y), !fits(x, y, 3));
+                let centered = x >= 1 && y >= 1 && fits(x - 1, y - 1, 3);
+                assert_eq!(by_radius.get(x, y), !centered);
+            }
+        }
+    }
+}
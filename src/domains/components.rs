@@ -0,0 +1,331 @@
+use crate::domains::BitGrid;
+
+/// The connected components of the unobstructed cells of a `BitGrid`, for answering reachability
+/// queries in constant time.
+///
+/// Components are 4-connected. When corner cutting is not allowed, the 8-connected components are
+/// the same, since every diagonal move could be replaced by two orthogonal ones. So the same
+/// labelling serves `NoCornerCutting`, `JpsExpansionPolicy` and 4-connected policies alike.
+#[derive(Clone, Debug)]
+pub struct ComponentMap {
+    width: i32,
+    height: i32,
+    /// The component of each cell, or 0 for obstructed cells.
+    labels: Box<[u32]>,
+    /// The number of cells in each component, indexed by label.
+    sizes: Vec<usize>,
+    /// Labels which are no longer in use.
+    free: Vec<u32>,
+}
+
+const NONE: u32 = 0;
+
+impl ComponentMap {
+    /// Labels the components of the map.
+    ///
+    /// note: runtime is linear in the number of cells.
+    pub fn new(map: &BitGrid) -> Self {
+        let (width, height) = (map.width(), map.height());
+        let mut this = ComponentMap {
+            width,
+            height,
+            labels: vec![NONE; width as usize * height as usize].into_boxed_slice(),
+            sizes: vec![0],
+            free: vec![],
+        };
+        let mut stack = vec![];
+        for y in 0..height {
+            for x in 0..width {
+                if !map.get(x, y) && this.labels[this.locate(x, y)] == NONE {
+                    let label = this.new_label();
+                    this.fill(map, &mut stack, (x, y), label);
+                }
+            }
+        }
+        this
+    }
+
+    #[inline(always)]
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Returns the label of the component of the cell, or `None` if the cell is obstructed.
+    #[track_caller]
+    #[inline(always)]
+    pub fn component(&self, x: i32, y: i32) -> Option<u32> {
+        self.bounds_check(x, y);
+        match self.labels[self.locate(x, y)] {
+            NONE => None,
+            label => Some(label),
+        }
+    }
+
+    /// Returns the number of cells in the component.
+    pub fn component_size(&self, label: u32) -> usize {
+        self.sizes.get(label as usize).copied().unwrap_or(0)
+    }
+
+    /// Returns whether the cells are unobstructed and in the same component, i.e. whether there is
+    /// a path between them.
+    #[track_caller]
+    #[inline(always)]
+    pub fn same_component(&self, a: (i32, i32), b: (i32, i32)) -> bool {
+        match (self.component(a.0, a.1), self.component(b.0, b.1)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Sets the cell of the map and updates the components to match.
+    ///
+    /// Unobstructing a cell merges the components around it, relabelling the smaller ones.
+    /// Obstructing a cell may split its component, in which case the pieces are relabelled.
+    ///
+    /// note: runtime is linear in the size of the relabelled components.
+    #[track_caller]
+    pub fn set(&mut self, map: &mut BitGrid, x: i32, y: i32, blocked: bool) {
+        assert!(
+            map.width() == self.width && map.height() == self.height,
+            "the map must be the one the components were built from"
+        );
+        self.bounds_check(x, y);
+        if map.get(x, y) == blocked {
+            return;
+        }
+        map.set(x, y, blocked);
+
+        let idx = self.locate(x, y);
+        let mut stack = vec![];
+        if blocked {
+            let label = self.labels[idx];
+            self.labels[idx] = NONE;
+            self.sizes[label as usize] -= 1;
+            if self.sizes[label as usize] == 0 {
+                self.free.push(label);
+            } else if !self.locally_connected(map, x, y) {
+                // the component may have been split; give each piece a fresh label
+                self.sizes[label as usize] = 0;
+                for (nx, ny) in orthogonal(x, y) {
+                    if !map.get(nx, ny) && self.labels[self.locate(nx, ny)] == label {
+                        let piece = self.new_label();
+                        self.fill(map, &mut stack, (nx, ny), piece);
+                    }
+                }
+                self.free.push(label);
+            }
+        } else {
+            // merge the neighboring components into the largest one
+            let mut largest = NONE;
+            for (nx, ny) in orthogonal(x, y) {
+                if !map.get(nx, ny) {
+                    let label = self.labels[self.locate(nx, ny)];
+                    if largest == NONE || self.sizes[label as usize] > self.sizes[largest as usize]
+                    {
+                        largest = label;
+                    }
+                }
+            }
+            if largest == NONE {
+                largest = self.new_label();
+            }
+            self.labels[idx] = largest;
+            self.sizes[largest as usize] += 1;
+            for (nx, ny) in orthogonal(x, y) {
+                if !map.get(nx, ny) {
+                    let label = self.labels[self.locate(nx, ny)];
+                    if label != largest {
+                        self.sizes[label as usize] = 0;
+                        self.free.push(label);
+                        self.fill(map, &mut stack, (nx, ny), largest);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns whether the unobstructed orthogonal neighbors of the cell are connected to each
+    /// other through its other neighbors, in which case obstructing the cell can't split anything.
+    fn locally_connected(&self, map: &BitGrid, x: i32, y: i32) -> bool {
+        // the neighbors in clockwise order, starting and ending at orthogonal neighbors
+        let ring = [
+            (0, -1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (-1, 1),
+            (-1, 0),
+            (-1, -1),
+        ];
+        let open = |i: usize| {
+            let (dx, dy) = ring[i % 8];
+            !map.get(x + dx, y + dy)
+        };
+        // find an obstructed neighbor to start from; if there is none, everything is connected
+        let start = match (0..8).find(|&i| !open(i)) {
+            Some(i) => i,
+            None => return true,
+        };
+        // count the runs of unobstructed neighbors which contain an orthogonal neighbor
+        let mut runs = 0;
+        let mut i = start + 1;
+        while i <= start + 8 {
+            let mut orthogonal = false;
+            while i <= start + 8 && open(i) {
+                orthogonal |= i % 2 == 0;
+                i += 1;
+            }
+            if orthogonal {
+                runs += 1;
+            }
+            i += 1;
+        }
+        runs <= 1
+    }
+
+    /// Labels the unobstructed cells reachable from `start`.
+    fn fill(&mut self, map: &BitGrid, stack: &mut Vec<(i32, i32)>, start: (i32, i32), label: u32) {
+        let idx = self.locate(start.0, start.1);
+        self.labels[idx] = label;
+        self.sizes[label as usize] += 1;
+        stack.push(start);
+        while let Some((x, y)) = stack.pop() {
+            for (nx, ny) in orthogonal(x, y) {
+                // padding cells are obstructed, so neighbors passing this check are in-bounds
+                if !map.get(nx, ny) {
+                    let idx = self.locate(nx, ny);
+                    if self.labels[idx] != label {
+                        self.labels[idx] = label;
+                        self.sizes[label as usize] += 1;
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+        }
+    }
+
+    fn new_label(&mut self) -> u32 {
+        match self.free.pop() {
+            Some(label) => label,
+            None => {
+                self.sizes.push(0);
+                (self.sizes.len() - 1) as u32
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn locate(&self, x: i32, y: i32) -> usize {
+        #[cfg(debug_assertions)]
+        self.bounds_check(x, y);
+
+        x as usize + y as usize * self.width as usize
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    fn bounds_check(&self, x: i32, y: i32) {
+        if !(0..self.width).contains(&x) || !(0..self.height).contains(&y) {
+            panic!("Grid cell ({}, {}) is out of bounds.", x, y);
+        }
+    }
+}
+
+fn orthogonal(x: i32, y: i32) -> [(i32, i32); 4] {
+    [(x, y - 1), (x + 1, y), (x, y + 1), (x - 1, y)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::random_grid;
+    use crate::expansion_policy::bitgrid::no_corner_cutting::NoCornerCutting;
+    use crate::node_pool::GridPool;
+    use crate::util::{grid_search, octile_heuristic, reachable_grid_search};
+    use crate::Owner;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    /// Checks that the labels partition the cells the same way as labelling from scratch.
+    fn check_equivalent(components: &ComponentMap, map: &BitGrid) {
+        let fresh = ComponentMap::new(map);
+        let mut mapping = std::collections::HashMap::new();
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                let (a, b) = (fresh.component(x, y), components.component(x, y));
+                assert_eq!(*mapping.entry(a).or_insert(b), b);
+                if let Some(b) = b {
+                    assert_eq!(
+                        fresh.component_size(a.unwrap()),
+                        components.component_size(b)
+                    );
+                }
+            }
+        }
+        let distinct: std::collections::HashSet<_> = mapping.values().collect();
+        assert_eq!(distinct.len(), mapping.len());
+    }
+
+    #[test]
+    fn check_incremental() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let mut map = random_grid(&mut rng, 31, 23, 0.4);
+        let mut components = ComponentMap::new(&map);
+        for _ in 0..2000 {
+            let x = rng.gen_range(0..map.width());
+            let y = rng.gen_range(0..map.height());
+            components.set(&mut map, x, y, rng.gen_bool(0.45));
+            check_equivalent(&components, &map);
+        }
+    }
+
+    #[test]
+    fn check_short_circuit() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let map = random_grid(&mut rng, 40, 40, 0.35);
+        let components = ComponentMap::new(&map);
+        let mut pool = GridPool::new(map.width(), map.height());
+        let mut owner = Owner::new();
+        for _ in 0..100 {
+            let mut cell = || {
+                (
+                    rng.gen_range(0..map.width()),
+                    rng.gen_range(0..map.height()),
+                )
+            };
+            let (from, to) = (cell(), cell());
+            if map.get(from.0, from.1) {
+                continue;
+            }
+            grid_search(
+                &mut pool,
+                &mut owner,
+                &mut NoCornerCutting::new(&map),
+                octile_heuristic(to, 1.0),
+                from,
+                to,
+            );
+            let expected = pool.get(to.0, to.1, &owner).map(|n| owner.ro(n).g);
+            assert_eq!(expected.is_some(), components.same_component(from, to));
+
+            let reachable = reachable_grid_search(
+                &mut pool,
+                &mut owner,
+                &mut NoCornerCutting::new(&map),
+                &components,
+                octile_heuristic(to, 1.0),
+                from,
+                to,
+            );
+            let actual = pool.get(to.0, to.1, &owner).map(|n| owner.ro(n).g);
+            assert_eq!(reachable, expected.is_some());
+            assert_eq!(expected, actual);
+        }
+    }
+}
@@ -0,0 +1,653 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::ops::Deref;
+use std::path::Path;
+
+use crate::domains::DirectedGraph;
+use crate::Edge;
+
+/// An immutable directed graph stored in compressed sparse row form, along with its reverse.
+///
+/// Edges of each vertex are sorted by destination. Vertex and edge indices are stored as `I`, so
+/// `CsrGraph<u32>` takes 12 bytes per edge per direction for graphs of up to `u32::MAX` edges.
+///
+/// The graph can be written to a file and read back, or memory-mapped with the `mmap` feature.
+pub struct CsrGraph<I = usize> {
+    forward: Csr<I>,
+    reverse: Csr<I>,
+    #[cfg(feature = "mmap")]
+    _map: Option<memmap2::Mmap>,
+}
+
+struct Csr<I> {
+    offsets: Buf<I>,
+    targets: Buf<I>,
+    costs: Buf<f64>,
+}
+
+/// Storage of one array, which is either owned or points into the memory map of the graph.
+enum Buf<T> {
+    Owned(Box<[T]>),
+    #[cfg(feature = "mmap")]
+    Mapped(*const T, usize),
+}
+
+// SAFETY: mapped buffers point into the read-only memory map owned by the graph, which is Send and
+//         Sync.
+#[cfg(feature = "mmap")]
+unsafe impl<T: Send> Send for Buf<T> {}
+#[cfg(feature = "mmap")]
+unsafe impl<T: Sync> Sync for Buf<T> {}
+
+impl<T> Deref for Buf<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match self {
+            Buf::Owned(b) => b,
+            #[cfg(feature = "mmap")]
+            Buf::Mapped(ptr, len) => unsafe {
+                // SAFETY: the pointer is aligned and points to `len` values inside the memory map,
+                //         which lives as long as the graph.
+                std::slice::from_raw_parts(*ptr, *len)
+            },
+        }
+    }
+}
+
+/// An integer type used to store the vertex and edge indices of a `CsrGraph`.
+///
+/// # Safety
+///
+/// Every bit pattern must be a valid value, since memory-mapped graphs are read directly from file
+/// contents.
+pub unsafe trait CsrIndex: Copy + Send + Sync + 'static {
+    fn from_usize(v: usize) -> Option<Self>;
+
+    fn index(self) -> usize;
+
+    fn from_le_bytes(bytes: &[u8]) -> Self;
+
+    fn write_le(self, w: &mut impl Write) -> std::io::Result<()>;
+}
+
+macro_rules! csr_index {
+    ($t:ty) => {
+        // SAFETY: all bit patterns are valid integers.
+        unsafe impl CsrIndex for $t {
+            #[inline(always)]
+            fn from_usize(v: usize) -> Option<Self> {
+                std::convert::TryFrom::try_from(v).ok()
+            }
+
+            #[inline(always)]
+            fn index(self) -> usize {
+                self as usize
+            }
+
+            fn from_le_bytes(bytes: &[u8]) -> Self {
+                let mut b = [0; std::mem::size_of::<$t>()];
+                b.copy_from_slice(bytes);
+                <$t>::from_le_bytes(b)
+            }
+
+            fn write_le(self, w: &mut impl Write) -> std::io::Result<()> {
+                w.write_all(&self.to_le_bytes())
+            }
+        }
+    };
+}
+
+csr_index!(u32);
+csr_index!(usize);
+
+const MAGIC: &[u8; 8] = b"CSRGRAPH";
+const VERSION: u32 = 1;
+const HEADER_LEN: usize = 32;
+
+impl<I: CsrIndex> CsrGraph<I> {
+    /// Builds the graph from `(from, to, cost)` edges. If there are duplicate edges, the last one is
+    /// kept, as with `DirectedGraph::try_add_edges`.
+    pub fn from_edges(
+        vertices: usize,
+        edges: &[(usize, usize, f64)],
+    ) -> Result<Self, &'static str> {
+        let forward = Csr::build(vertices, edges.len(), edges.iter().copied())?;
+        Ok(Self::with_forward(vertices, forward))
+    }
+
+    pub fn from_graph<V>(graph: &DirectedGraph<V>) -> Result<Self, &'static str> {
+        let edges = (0..graph.len()).flat_map(|from| {
+            graph
+                .outgoing_edges(from)
+                .iter()
+                .map(move |e| (from, e.destination, e.cost))
+        });
+        let forward = Csr::build(graph.len(), graph.total_edges(), edges)?;
+        Ok(Self::with_forward(graph.len(), forward))
+    }
+
+    fn with_forward(vertices: usize, forward: Csr<I>) -> Self {
+        let edges = (0..vertices).flat_map(|from| {
+            // SAFETY: the forward CSR was just built for this many vertices.
+            unsafe { forward.edges_unchecked(from) }.map(move |e| (e.destination, from, e.cost))
+        });
+        // the forward CSR has already been checked, so this can't fail
+        let reverse = Csr::build(vertices, forward.costs.len(), edges).unwrap();
+        CsrGraph {
+            forward,
+            reverse,
+            #[cfg(feature = "mmap")]
+            _map: None,
+        }
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.forward.offsets.len() - 1
+    }
+
+    #[inline(always)]
+    pub fn total_edges(&self) -> usize {
+        self.forward.costs.len()
+    }
+
+    #[track_caller]
+    pub fn outgoing_edges(&self, vertex: usize) -> CsrEdges<'_, I> {
+        assert!(vertex < self.len(), "Vertex {} is out of bounds.", vertex);
+        unsafe {
+            // SAFETY: bounds checked above
+            self.forward.edges_unchecked(vertex)
+        }
+    }
+
+    #[track_caller]
+    pub fn incoming_edges(&self, vertex: usize) -> CsrEdges<'_, I> {
+        assert!(vertex < self.len(), "Vertex {} is out of bounds.", vertex);
+        unsafe {
+            // SAFETY: bounds checked above
+            self.reverse.edges_unchecked(vertex)
+        }
+    }
+
+    pub unsafe fn outgoing_edges_unchecked(&self, vertex: usize) -> CsrEdges<'_, I> {
+        self.forward.edges_unchecked(vertex)
+    }
+
+    pub unsafe fn incoming_edges_unchecked(&self, vertex: usize) -> CsrEdges<'_, I> {
+        self.reverse.edges_unchecked(vertex)
+    }
+
+    /// note: runtime is logarithmic in the number of edges on the from vertex.
+    #[track_caller]
+    pub fn find_edge(&self, from: usize, to: usize) -> Option<Edge<usize>> {
+        let edges = self.outgoing_edges(from);
+        let i = edges
+            .targets
+            .binary_search_by_key(&to, |t| t.index())
+            .ok()?;
+        Some(Edge {
+            destination: to,
+            cost: edges.costs[i],
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write_to(&mut w)?;
+        w.flush()
+    }
+
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// Writes the graph in a binary format which can be read by `read_from` or memory-mapped by
+    /// `map_file`. Graphs can only be read with the index type they were written with.
+    pub fn write_to(&self, mut w: impl Write) -> std::io::Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&VERSION.to_le_bytes())?;
+        w.write_all(&(std::mem::size_of::<I>() as u32).to_le_bytes())?;
+        w.write_all(&(self.len() as u64).to_le_bytes())?;
+        w.write_all(&(self.total_edges() as u64).to_le_bytes())?;
+        for csr in [&self.forward, &self.reverse] {
+            for &v in csr.offsets.iter() {
+                v.write_le(&mut w)?;
+            }
+            w.write_all(&[0; 8][..padding(csr.offsets.len() * std::mem::size_of::<I>())])?;
+            for &v in csr.targets.iter() {
+                v.write_le(&mut w)?;
+            }
+            w.write_all(&[0; 8][..padding(csr.targets.len() * std::mem::size_of::<I>())])?;
+            for &c in csr.costs.iter() {
+                w.write_all(&c.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read_from(mut r: impl Read) -> std::io::Result<Self> {
+        let mut header = [0; HEADER_LEN];
+        r.read_exact(&mut header)?;
+        let (vertices, edges) = parse_header::<I>(&header)?;
+
+        fn read<T>(
+            r: &mut impl Read,
+            len: usize,
+            size: usize,
+            f: impl Fn(&[u8]) -> T,
+        ) -> std::io::Result<Buf<T>> {
+            let n = len.checked_mul(size).ok_or_else(too_large)?;
+            // read incrementally rather than trusting the header with the allocation
+            let mut bytes = vec![];
+            r.take(n as u64).read_to_end(&mut bytes)?;
+            if bytes.len() != n {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+            let mut pad = [0; 8];
+            r.read_exact(&mut pad[..padding(n)])?;
+            Ok(Buf::Owned(bytes.chunks_exact(size).map(f).collect()))
+        }
+
+        let size = std::mem::size_of::<I>();
+        let offsets = vertices.checked_add(1).ok_or_else(too_large)?;
+        let mut csr = || -> std::io::Result<Csr<I>> {
+            let csr = Csr {
+                offsets: read(&mut r, offsets, size, I::from_le_bytes)?,
+                targets: read(&mut r, edges, size, I::from_le_bytes)?,
+                costs: read(&mut r, edges, 8, |b| {
+                    f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]])
+                })?,
+            };
+            csr.validate(vertices)?;
+            Ok(csr)
+        };
+        let forward = csr()?;
+        let reverse = csr()?;
+        Ok(CsrGraph {
+            forward,
+            reverse,
+            #[cfg(feature = "mmap")]
+            _map: None,
+        })
+    }
+
+    /// Memory-maps a graph written by `write_to`. The file is validated, which takes time linear
+    /// in its size, but nothing is copied.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified while the graph exists.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn map_file(path: &Path) -> std::io::Result<Self> {
+        if cfg!(target_endian = "big") {
+            return Err(invalid(
+                "memory-mapped graphs require a little-endian target",
+            ));
+        }
+        let map = memmap2::Mmap::map(&File::open(path)?)?;
+        if map.len() < HEADER_LEN {
+            return Err(invalid("truncated CSR graph"));
+        }
+        let (vertices, edges) = parse_header::<I>(&map[..HEADER_LEN])?;
+
+        let size = std::mem::size_of::<I>();
+        let offsets = vertices.checked_add(1).ok_or_else(too_large)?;
+        let lens = [
+            offsets.checked_mul(size).ok_or_else(too_large)?,
+            edges.checked_mul(size).ok_or_else(too_large)?,
+            edges.checked_mul(8).ok_or_else(too_large)?,
+        ];
+        let total = lens
+            .iter()
+            .chain(&lens)
+            .try_fold(HEADER_LEN, |total, &l| {
+                total.checked_add(l)?.checked_add(padding(l))
+            })
+            .ok_or_else(too_large)?;
+        if map.len() != total {
+            return Err(invalid("truncated CSR graph"));
+        }
+
+        // SAFETY: the map is page-aligned and every section starts at a multiple of 8 bytes, so
+        //         all pointers are aligned. the length check above keeps them inside the map.
+        let mut at = map.as_ptr().add(HEADER_LEN);
+        let mut section = |len: usize| {
+            let ptr = at;
+            at = at.add(len + padding(len));
+            ptr
+        };
+        let mut csr = || Csr {
+            offsets: Buf::Mapped(section(lens[0]) as *const I, offsets),
+            targets: Buf::Mapped(section(lens[1]) as *const I, edges),
+            costs: Buf::Mapped(section(lens[2]) as *const f64, edges),
+        };
+        let forward = csr();
+        let reverse = csr();
+        forward.validate(vertices)?;
+        reverse.validate(vertices)?;
+        Ok(CsrGraph {
+            forward,
+            reverse,
+            _map: Some(map),
+        })
+    }
+}
+
+impl<I: CsrIndex> Csr<I> {
+    /// Builds the CSR of the `(row, column, cost)` edges, sorting the edges of each row by column
+    /// and keeping the last of any duplicates.
+    fn build(
+        rows: usize,
+        len: usize,
+        edges: impl Iterator<Item = (usize, usize, f64)> + Clone,
+    ) -> Result<Self, &'static str> {
+        if I::from_usize(rows).is_none() || I::from_usize(len).is_none() {
+            return Err("Graph is too large for the index type");
+        }
+
+        let mut starts = vec![0; rows + 1];
+        for (row, column, _) in edges.clone() {
+            if row >= rows || column >= rows {
+                return Err("Edge vertices don't exist");
+            }
+            starts[row + 1] += 1;
+        }
+        for i in 0..rows {
+            starts[i + 1] += starts[i];
+        }
+        let mut placed = vec![(0, 0.0); starts[rows]];
+        let mut next = starts.clone();
+        for (row, column, cost) in edges {
+            placed[next[row]] = (column, cost);
+            next[row] += 1;
+        }
+
+        let mut offsets = Vec::with_capacity(rows + 1);
+        let mut targets: Vec<I> = Vec::with_capacity(placed.len());
+        let mut costs = Vec::with_capacity(placed.len());
+        offsets.push(I::from_usize(0).unwrap());
+        for row in 0..rows {
+            let row_edges = &mut placed[starts[row]..starts[row + 1]];
+            // stable, so duplicates stay in the order they were given
+            row_edges.sort_by_key(|&(column, _)| column);
+            let row_start = targets.len();
+            for &(column, cost) in row_edges.iter() {
+                match targets.last() {
+                    Some(t) if targets.len() > row_start && t.index() == column => {
+                        *costs.last_mut().unwrap() = cost;
+                    }
+                    _ => {
+                        targets.push(I::from_usize(column).unwrap());
+                        costs.push(cost);
+                    }
+                }
+            }
+            offsets.push(I::from_usize(targets.len()).unwrap());
+        }
+
+        Ok(Csr {
+            offsets: Buf::Owned(offsets.into_boxed_slice()),
+            targets: Buf::Owned(targets.into_boxed_slice()),
+            costs: Buf::Owned(costs.into_boxed_slice()),
+        })
+    }
+
+    /// Checks the invariants which `edges_unchecked` and the expansion policies rely on.
+    fn validate(&self, vertices: usize) -> std::io::Result<()> {
+        let offsets = &self.offsets;
+        if offsets[0].index() != 0 || offsets[vertices].index() != self.targets.len() {
+            return Err(invalid("invalid CSR offsets"));
+        }
+        if offsets.windows(2).any(|w| w[0].index() > w[1].index()) {
+            return Err(invalid("invalid CSR offsets"));
+        }
+        if self.targets.iter().any(|t| t.index() >= vertices) {
+            return Err(invalid("CSR edge vertices don't exist"));
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    unsafe fn edges_unchecked(&self, row: usize) -> CsrEdges<'_, I> {
+        let start = self.offsets.get_unchecked(row).index();
+        let end = self.offsets.get_unchecked(row + 1).index();
+        CsrEdges {
+            targets: self.targets.get_unchecked(start..end),
+            costs: self.costs.get_unchecked(start..end),
+        }
+    }
+}
+
+fn parse_header<I: CsrIndex>(header: &[u8]) -> std::io::Result<(usize, usize)> {
+    let u32_at =
+        |i: usize| u32::from_le_bytes([header[i], header[i + 1], header[i + 2], header[i + 3]]);
+    let u64_at = |i: usize| {
+        let mut b = [0; 8];
+        b.copy_from_slice(&header[i..i + 8]);
+        u64::from_le_bytes(b)
+    };
+    if &header[..8] != MAGIC || u32_at(8) != VERSION {
+        return Err(invalid("not a CSR graph"));
+    }
+    if u32_at(12) as usize != std::mem::size_of::<I>() {
+        return Err(invalid("CSR graph was written with a different index type"));
+    }
+    match (
+        I::from_usize(u64_at(16) as usize),
+        I::from_usize(u64_at(24) as usize),
+    ) {
+        (Some(vertices), Some(edges)) => Ok((vertices.index(), edges.index())),
+        _ => Err(invalid("CSR graph is too large for the index type")),
+    }
+}
+
+/// The number of padding bytes after a section of `len` bytes to reach a multiple of 8 bytes.
+fn padding(len: usize) -> usize {
+    (8 - len % 8) % 8
+}
+
+fn invalid(msg: &'static str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
+
+fn too_large() -> std::io::Error {
+    invalid("CSR graph is too large")
+}
+
+/// Iterator over the edges of one vertex of a `CsrGraph`.
+#[derive(Clone)]
+pub struct CsrEdges<'a, I> {
+    targets: &'a [I],
+    costs: &'a [f64],
+}
+
+impl<I: CsrIndex> Iterator for CsrEdges<'_, I> {
+    type Item = Edge<usize>;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Edge<usize>> {
+        let (&t, targets) = self.targets.split_first()?;
+        let (&cost, costs) = self.costs.split_first()?;
+        self.targets = targets;
+        self.costs = costs;
+        Some(Edge {
+            destination: t.index(),
+            cost,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.targets.len(), Some(self.targets.len()))
+    }
+}
+
+impl<I: CsrIndex> ExactSizeIterator for CsrEdges<'_, I> {}
+
+#[cfg(feature = "serde")]
+mod serde {
+    use serde::ser::SerializeStruct;
+    use serde::{Deserialize, Serialize};
+
+    use super::{Buf, Csr, CsrGraph, CsrIndex};
+
+    // only the forward CSR is stored; the reverse is rebuilt when deserializing.
+    impl<I: CsrIndex + Serialize> Serialize for CsrGraph<I> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            let mut s = serializer.serialize_struct("CsrGraph", 3)?;
+            s.serialize_field("offsets", &*self.forward.offsets)?;
+            s.serialize_field("targets", &*self.forward.targets)?;
+            s.serialize_field("costs", &*self.forward.costs)?;
+            s.end()
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct CsrGraphTransport<I> {
+        offsets: Vec<I>,
+        targets: Vec<I>,
+        costs: Vec<f64>,
+    }
+
+    impl<'de, I: CsrIndex + Deserialize<'de>> Deserialize<'de> for CsrGraph<I> {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            let v = CsrGraphTransport::deserialize(deserializer)?;
+            if v.offsets.is_empty() || v.targets.len() != v.costs.len() {
+                return Err(serde::de::Error::custom("invalid CSR graph"));
+            }
+            let vertices = v.offsets.len() - 1;
+            let forward = Csr {
+                offsets: Buf::Owned(v.offsets.into_boxed_slice()),
+                targets: Buf::Owned(v.targets.into_boxed_slice()),
+                costs: Buf::Owned(v.costs.into_boxed_slice()),
+            };
+            forward
+                .validate(vertices)
+                .map_err(serde::de::Error::custom)?;
+            Ok(CsrGraph::with_forward(vertices, forward))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expansion_policy::graph::{CsrIncomingEdges, CsrOutgoingEdges, OutgoingEdges};
+    use crate::node_pool::IndexPool;
+    use crate::util::{assert_same_cost, index_search, zero_heuristic};
+    use crate::Owner;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    fn random_graph(rng: &mut Pcg64) -> DirectedGraph<()> {
+        let mut graph = DirectedGraph::new();
+        for _ in 0..100 {
+            graph.add_vertex(());
+        }
+        let edges: Vec<_> = (0..400)
+            .map(|_| {
+                (
+                    rng.gen_range(0..100),
+                    rng.gen_range(0..100),
+                    rng.gen_range(1.0..10.0),
+                )
+            })
+            .collect();
+        graph.try_add_edges(&edges).unwrap();
+        graph
+    }
+
+    fn assert_same<I: CsrIndex>(graph: &DirectedGraph<()>, csr: &CsrGraph<I>) {
+        assert_eq!(csr.len(), graph.len());
+        assert_eq!(csr.total_edges(), graph.total_edges());
+        for v in 0..graph.len() {
+            assert!(csr
+                .outgoing_edges(v)
+                .eq(graph.outgoing_edges(v).iter().copied()));
+            assert!(csr
+                .incoming_edges(v)
+                .eq(graph.incoming_edges(v).iter().copied()));
+        }
+    }
+
+    #[test]
+    fn check_build() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let graph = random_graph(&mut rng);
+        assert_same(&graph, &CsrGraph::<u32>::from_graph(&graph).unwrap());
+
+        let edges = [(0, 1, 1.0), (1, 2, 2.0), (0, 1, 3.0), (2, 0, 4.0)];
+        let csr = CsrGraph::<usize>::from_edges(3, &edges).unwrap();
+        assert_eq!(csr.total_edges(), 3);
+        assert_eq!(csr.find_edge(0, 1).map(|e| e.cost), Some(3.0));
+        assert_eq!(csr.find_edge(1, 0), None);
+        assert!(CsrGraph::<u32>::from_edges(2, &[(0, 2, 1.0)]).is_err());
+
+        let mut pool = IndexPool::new(graph.len());
+        let mut owner = Owner::new();
+        let csr = CsrGraph::<u32>::from_graph(&graph).unwrap();
+        for _ in 0..20 {
+            let (s, g) = (rng.gen_range(0..100), rng.gen_range(0..100));
+            let mut ep = OutgoingEdges::new(&graph);
+            index_search(&mut pool, &mut owner, &mut ep, zero_heuristic(), s, g);
+            let expected = pool.get(g, &owner).map(|n| owner.ro(n).g);
+            let mut ep = CsrOutgoingEdges::new(&csr);
+            index_search(&mut pool, &mut owner, &mut ep, zero_heuristic(), s, g);
+            assert_eq!(pool.get(g, &owner).map(|n| owner.ro(n).g), expected);
+            // the same search backwards
+            let mut ep = CsrIncomingEdges::new(&csr);
+            index_search(&mut pool, &mut owner, &mut ep, zero_heuristic(), g, s);
+            // costs are summed in the opposite order, so may differ slightly
+            let backwards = pool.get(s, &owner).map(|n| owner.ro(n).g);
+            assert_same_cost(backwards, expected);
+        }
+    }
+
+    #[test]
+    fn check_files() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let graph = random_graph(&mut rng);
+        let csr = CsrGraph::<u32>::from_graph(&graph).unwrap();
+        let mut bytes = vec![];
+        csr.write_to(&mut bytes).unwrap();
+        assert_same(&graph, &CsrGraph::<u32>::read_from(&bytes[..]).unwrap());
+        assert!(CsrGraph::<usize>::read_from(&bytes[..]).is_err());
+        assert!(CsrGraph::<u32>::read_from(&bytes[..bytes.len() - 1]).is_err());
+
+        // headers claiming sizes which overflow or aren't backed by data
+        let mut huge = vec![];
+        CsrGraph::<usize>::from_graph(&graph)
+            .unwrap()
+            .write_to(&mut huge)
+            .unwrap();
+        let mut headers = vec![];
+        for &(at, n) in &[(16, u64::MAX), (24, u64::MAX / 4), (24, 1 << 40)] {
+            let mut bytes = huge.clone();
+            bytes[at..at + 8].copy_from_slice(&n.to_le_bytes());
+            assert!(CsrGraph::<usize>::read_from(&bytes[..]).is_err());
+            headers.push(bytes);
+        }
+
+        #[cfg(feature = "mmap")]
+        {
+            let path = std::env::temp_dir().join("pathfinding-check-csr-files.csr");
+            csr.save(&path).unwrap();
+            // SAFETY: nothing else writes to the file.
+            let mapped = unsafe { CsrGraph::<u32>::map_file(&path) }.unwrap();
+            assert_same(&graph, &mapped);
+            drop(mapped);
+            for bytes in &headers {
+                std::fs::write(&path, bytes).unwrap();
+                // SAFETY: nothing else writes to the file.
+                assert!(unsafe { CsrGraph::<usize>::map_file(&path) }.is_err());
+            }
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+}
@@ -54,7 +54,7 @@ impl<V> DirectedGraph<V> {
             }
         }
 
-        let incoming = &mut self.vertices[from].incoming;
+        let incoming = &mut self.vertices[to].incoming;
         match incoming.binary_search_by_key(&from, |e| e.destination) {
             Ok(i) => incoming[i].cost = cost,
             Err(i) => incoming.insert(
@@ -79,7 +79,7 @@ impl<V> DirectedGraph<V> {
                 destination: to,
                 cost,
             });
-            self.vertices[to].outgoing.push(Edge {
+            self.vertices[to].incoming.push(Edge {
                 destination: from,
                 cost,
             });
@@ -113,6 +113,139 @@ impl<V> DirectedGraph<V> {
         result
     }
 
+    /// Removes the edge, returning its cost if it was present.
+    ///
+    /// note: runtime is linear in the number of edges on the relevant vertices.
+    pub fn remove_edge(&mut self, from: usize, to: usize) -> Option<f64> {
+        assert!(
+            from < self.vertices.len() && to < self.vertices.len(),
+            "from and to vertices must exist"
+        );
+
+        let outgoing = &mut self.vertices[from].outgoing;
+        let i = outgoing.binary_search_by_key(&to, |e| e.destination).ok()?;
+        let cost = outgoing.remove(i).cost;
+
+        let incoming = &mut self.vertices[to].incoming;
+        if let Ok(i) = incoming.binary_search_by_key(&from, |e| e.destination) {
+            incoming.remove(i);
+        }
+        self.edges -= 1;
+        Some(cost)
+    }
+
+    /// Removes the vertex along with its edges, returning its data.
+    ///
+    /// To keep vertex IDs contiguous, the vertex with the highest ID takes the ID of the removed
+    /// vertex, as with `Vec::swap_remove`.
+    ///
+    /// note: runtime is linear in the number of edges on the removed vertex, the moved vertex, and
+    ///       their neighbors.
+    pub fn remove_vertex(&mut self, vertex: usize) -> V {
+        assert!(vertex < self.vertices.len(), "vertex must exist");
+
+        while let Some(e) = self.vertices[vertex].outgoing.last() {
+            let to = e.destination;
+            self.remove_edge(vertex, to);
+        }
+        while let Some(e) = self.vertices[vertex].incoming.last() {
+            let from = e.destination;
+            self.remove_edge(from, vertex);
+        }
+
+        let last = self.vertices.len() - 1;
+        let data = self.vertices.swap_remove(vertex).data;
+        if vertex == last {
+            return data;
+        }
+
+        // self-loops of the moved vertex refer to it by its old ID
+        rename(&mut self.vertices[vertex].outgoing, last, vertex);
+        rename(&mut self.vertices[vertex].incoming, last, vertex);
+        for i in 0..self.vertices[vertex].outgoing.len() {
+            let to = self.vertices[vertex].outgoing[i].destination;
+            if to != vertex {
+                rename(&mut self.vertices[to].incoming, last, vertex);
+            }
+        }
+        for i in 0..self.vertices[vertex].incoming.len() {
+            let from = self.vertices[vertex].incoming[i].destination;
+            if from != vertex {
+                rename(&mut self.vertices[from].outgoing, last, vertex);
+            }
+        }
+        data
+    }
+
+    /// Sets the costs of many existing `(from, to, cost)` edges. If any of the edges don't exist,
+    /// no costs are changed.
+    pub fn update_costs(&mut self, updates: &[(usize, usize, f64)]) -> Result<(), &'static str> {
+        for &(from, to, _) in updates {
+            if from >= self.vertices.len() || to >= self.vertices.len() {
+                return Err("Edge vertices don't exist");
+            }
+            if self.find_edge(from, to).is_none() {
+                return Err("Edge doesn't exist");
+            }
+        }
+        for &(from, to, cost) in updates {
+            self.set_cost(from, to, cost);
+        }
+        Ok(())
+    }
+
+    /// Replaces the cost of every edge with the result of `f(from, to, cost)`.
+    pub fn map_costs(&mut self, mut f: impl FnMut(usize, usize, f64) -> f64) {
+        for from in 0..self.vertices.len() {
+            for i in 0..self.vertices[from].outgoing.len() {
+                let e = self.vertices[from].outgoing[i];
+                let cost = f(from, e.destination, e.cost);
+                self.set_cost(from, e.destination, cost);
+            }
+        }
+    }
+
+    fn set_cost(&mut self, from: usize, to: usize, cost: f64) {
+        let outgoing = &mut self.vertices[from].outgoing;
+        if let Ok(i) = outgoing.binary_search_by_key(&to, |e| e.destination) {
+            outgoing[i].cost = cost;
+        }
+        let incoming = &mut self.vertices[to].incoming;
+        if let Ok(i) = incoming.binary_search_by_key(&from, |e| e.destination) {
+            incoming[i].cost = cost;
+        }
+    }
+
+    /// Iterates over all edges as `(from, to, cost)`, ordered by `from` and then `to`.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, f64)> + '_ {
+        self.vertices.iter().enumerate().flat_map(|(from, v)| {
+            v.outgoing
+                .iter()
+                .map(move |e| (from, e.destination, e.cost))
+        })
+    }
+
+    /// Removes all vertices with IDs `len` and above, along with their edges.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        for v in len..self.vertices.len() {
+            for i in 0..self.vertices[v].outgoing.len() {
+                let to = self.vertices[v].outgoing[i].destination;
+                if to < len {
+                    self.vertices[to].incoming.retain(|e| e.destination != v);
+                }
+            }
+            for i in 0..self.vertices[v].incoming.len() {
+                let from = self.vertices[v].incoming[i].destination;
+                if from < len {
+                    self.vertices[from].outgoing.retain(|e| e.destination != v);
+                    self.edges -= 1;
+                }
+            }
+            self.edges -= self.vertices[v].outgoing.len();
+        }
+        self.vertices.truncate(len);
+    }
+
     pub fn vertex_data(&self, vertex: usize) -> &V {
         &self.vertices[vertex].data
     }
@@ -155,6 +288,18 @@ impl<V> DirectedGraph<V> {
     }
 }
 
+/// Changes the destination of the edge to `old` in the sorted list to `new`.
+fn rename(edges: &mut Vec<Edge<usize>>, old: usize, new: usize) {
+    if let Ok(i) = edges.binary_search_by_key(&old, |e| e.destination) {
+        let mut edge = edges.remove(i);
+        edge.destination = new;
+        let i = edges
+            .binary_search_by_key(&new, |e| e.destination)
+            .unwrap_err();
+        edges.insert(i, edge);
+    }
+}
+
 #[cfg(feature = "serde")]
 mod serde {
     use serde::ser::{SerializeSeq, SerializeStruct};
@@ -177,13 +322,7 @@ mod serde {
                 "edges",
                 &StreamingSequence {
                     count: self.edges,
-                    iter: || {
-                        self.vertices.iter().enumerate().flat_map(|(from, v)| {
-                            v.outgoing
-                                .iter()
-                                .map(move |e| (from, e.destination, e.cost))
-                        })
-                    },
+                    iter: || self.edges(),
                 },
             )?;
             s.end()
@@ -236,3 +375,116 @@ mod serde {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    fn assert_mirrored<V>(graph: &DirectedGraph<V>) {
+        let mut incoming = 0;
+        for v in 0..graph.len() {
+            let out = graph.outgoing_edges(v);
+            let inc = graph.incoming_edges(v);
+            assert!(out.windows(2).all(|w| w[0].destination < w[1].destination));
+            assert!(inc.windows(2).all(|w| w[0].destination < w[1].destination));
+            for e in out {
+                let mirror = graph.incoming_edges(e.destination);
+                let i = mirror.binary_search_by_key(&v, |e| e.destination).unwrap();
+                assert_eq!(mirror[i].cost, e.cost);
+            }
+            for e in inc {
+                assert_eq!(graph.find_edge(e.destination, v).unwrap().cost, e.cost);
+            }
+            incoming += inc.len();
+        }
+        assert_eq!(incoming, graph.total_edges());
+        assert_eq!(graph.edges().count(), graph.total_edges());
+    }
+
+    #[test]
+    fn check_incoming_mirrors_outgoing() {
+        let mut graph = DirectedGraph::new();
+        for i in 0..4 {
+            graph.add_vertex(i);
+        }
+        graph.add_edge(0, 1, 1.0);
+        graph.add_edge(2, 1, 2.0);
+        graph.add_edge(3, 0, 3.0);
+        graph.add_edge(0, 1, 4.0);
+        assert_mirrored(&graph);
+        assert_eq!(graph.incoming_edges(1).len(), 2);
+        assert_eq!(graph.incoming_edges(1)[0].cost, 4.0);
+
+        graph
+            .try_add_edges(&[(1, 3, 5.0), (2, 3, 6.0), (1, 3, 7.0), (3, 0, 8.0)])
+            .unwrap();
+        assert_mirrored(&graph);
+        assert_eq!(graph.incoming_edges(3).len(), 2);
+        assert_eq!(graph.find_edge(1, 3).unwrap().cost, 7.0);
+        assert_eq!(graph.incoming_edges(0)[0].cost, 8.0);
+    }
+
+    #[test]
+    fn check_mutation() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let mut graph = DirectedGraph::new();
+        for i in 0..50 {
+            graph.add_vertex(i);
+        }
+        let edges: Vec<_> = (0..300)
+            .map(|_| {
+                (
+                    rng.gen_range(0..50),
+                    rng.gen_range(0..50),
+                    rng.gen_range(1.0..10.0),
+                )
+            })
+            .collect();
+        graph.try_add_edges(&edges).unwrap();
+        assert_mirrored(&graph);
+
+        for _ in 0..200 {
+            let (from, to) = (rng.gen_range(0..graph.len()), rng.gen_range(0..graph.len()));
+            match rng.gen_range(0..4) {
+                0 => {
+                    let expected = graph.find_edge(from, to).map(|e| e.cost);
+                    assert_eq!(graph.remove_edge(from, to), expected);
+                    assert!(graph.find_edge(from, to).is_none());
+                }
+                1 => graph.add_edge(from, to, rng.gen_range(1.0..10.0)),
+                2 => {
+                    let last = *graph.vertex_data(graph.len() - 1);
+                    let removed = *graph.vertex_data(from);
+                    assert_eq!(graph.remove_vertex(from), removed);
+                    if from < graph.len() {
+                        assert_eq!(*graph.vertex_data(from), last);
+                    }
+                    graph.add_vertex(removed);
+                }
+                _ => {
+                    let updates: Vec<_> = graph
+                        .edges()
+                        .filter(|_| rng.gen_bool(0.1))
+                        .map(|(from, to, cost)| (from, to, cost * 2.0))
+                        .collect();
+                    graph.update_costs(&updates).unwrap();
+                    for &(from, to, cost) in &updates {
+                        assert_eq!(graph.find_edge(from, to).unwrap().cost, cost);
+                    }
+                }
+            }
+            assert_mirrored(&graph);
+        }
+
+        let before: Vec<_> = graph.edges().collect();
+        assert!(graph.update_costs(&[(0, 0, 1.0), (0, 50, 1.0)]).is_err());
+        assert!(graph.edges().eq(before.iter().copied()));
+        graph.map_costs(|from, to, cost| cost + (from + to) as f64);
+        assert_mirrored(&graph);
+        for (&(from, to, old), (_, _, new)) in before.iter().zip(graph.edges()) {
+            assert_eq!(new, old + (from + to) as f64);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use crate::domains::DirectedGraph;
+use crate::util::GridDomain;
+use crate::{ExpansionPolicy, SearchNode};
+
+/// Marks a cell which has no vertex.
+const NO_VERTEX: u32 = u32::MAX;
+
+/// The mapping from the cells of a grid to the vertices of a graph built by `grid_to_graph`. The
+/// reverse mapping is the vertex data of the graph.
+///
+/// note: removing vertices from the graph renumbers them, which invalidates the mapping.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GridVertexMap {
+    width: i32,
+    height: i32,
+    /// The vertex of each cell in row-major order.
+    vertices: Box<[u32]>,
+}
+
+impl GridVertexMap {
+    #[inline(always)]
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Returns the vertex of the cell, or `None` if the cell has no edges.
+    #[track_caller]
+    pub fn vertex(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            panic!("Grid cell ({}, {}) is out of bounds.", x, y);
+        }
+        match self.vertices[(x + y * self.width) as usize] {
+            NO_VERTEX => None,
+            v => Some(v as usize),
+        }
+    }
+}
+
+/// Builds a graph with the edges the expansion policy produces from every cell of the grid.
+///
+/// Cells with no edges in either direction get no vertex, so policies which give obstructed cells
+/// no edges, such as those wrapped in `SymmetricGrid`, leave them out of the graph. The other
+/// cells are numbered in row-major order, and stored as vertex data.
+pub fn grid_to_graph<E>(expansion_policy: &mut E) -> (DirectedGraph<(i32, i32)>, GridVertexMap)
+where
+    E: ExpansionPolicy<(i32, i32)> + GridDomain,
+{
+    let (width, height) = (expansion_policy.width(), expansion_policy.height());
+    let mut vertices = vec![NO_VERTEX; width as usize * height as usize];
+    let index = |(x, y): (i32, i32)| (x + y * width) as usize;
+
+    let mut edges = vec![];
+    let mut cell_edges = vec![];
+    for y in 0..height {
+        for x in 0..width {
+            let node = SearchNode {
+                search_num: 0,
+                pqueue_location: 0,
+                expansions: 0,
+                id: (x, y),
+                parent: None,
+                g: 0.0,
+                lb: 0.0,
+            };
+            cell_edges.clear();
+            unsafe {
+                // SAFETY: the cell is in-bounds, and GridDomain guarantees that the destinations
+                //         are too.
+                expansion_policy.expand_unchecked(&node, &mut cell_edges);
+            }
+            for e in &cell_edges {
+                // mark both cells as having a vertex; they are numbered below
+                vertices[index((x, y))] = 0;
+                vertices[index(e.destination)] = 0;
+                edges.push((index((x, y)), index(e.destination), e.cost));
+            }
+        }
+    }
+
+    let mut graph = DirectedGraph::new();
+    for y in 0..height {
+        for x in 0..width {
+            let v = &mut vertices[index((x, y))];
+            if *v != NO_VERTEX {
+                *v = graph.add_vertex((x, y)) as u32;
+            }
+        }
+    }
+    for (from, to, _) in &mut edges {
+        *from = vertices[*from] as usize;
+        *to = vertices[*to] as usize;
+    }
+    // every endpoint has a vertex, so this can't fail
+    graph.try_add_edges(&edges).unwrap();
+
+    let map = GridVertexMap {
+        width,
+        height,
+        vertices: vertices.into_boxed_slice(),
+    };
+    (graph, map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::{random_grid, UndirectedGraph, WeightedGrid};
+    use crate::expansion_policy::bitgrid::no_corner_cutting::NoCornerCutting;
+    use crate::expansion_policy::graph::{OutgoingEdges, UndirectedEdges};
+    use crate::expansion_policy::weighted_grid::avg_four::AverageOfFour;
+    use crate::incremental::SymmetricGrid;
+    use crate::node_pool::{GridPool, IndexPool};
+    use crate::util::{assert_same_cost, grid_search, index_search, zero_heuristic};
+    use crate::Owner;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    #[test]
+    fn check_same_distances() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let map = random_grid(&mut rng, 20, 15, 0.25);
+        let mut weights = WeightedGrid::new(20, 15);
+        for y in 0..15 {
+            for x in 0..20 {
+                if rng.gen_bool(0.8) {
+                    *weights.get_mut(x, y) = Some(rng.gen_range(1..10u8) as f64);
+                }
+            }
+        }
+
+        let (graph, vertices) =
+            grid_to_graph(&mut SymmetricGrid::new(&map, NoCornerCutting::new(&map)));
+        let undirected = UndirectedGraph::from_directed(&graph).unwrap();
+        assert_eq!(undirected.total_edges() * 2, graph.total_edges());
+        let (weighted, weighted_vertices) = grid_to_graph(&mut AverageOfFour::new(&weights));
+
+        let mut grid_pool = GridPool::new(20, 15);
+        let mut pool = IndexPool::new(20 * 15);
+        let mut owner = Owner::new();
+        for _ in 0..30 {
+            let s = (rng.gen_range(0..20), rng.gen_range(0..15));
+            let g = (rng.gen_range(0..20), rng.gen_range(0..15));
+
+            let mut ep = SymmetricGrid::new(&map, NoCornerCutting::new(&map));
+            grid_search(&mut grid_pool, &mut owner, &mut ep, zero_heuristic(), s, g);
+            let expected = grid_pool.get(g.0, g.1, &owner).map(|n| owner.ro(n).g);
+            match (vertices.vertex(s.0, s.1), vertices.vertex(g.0, g.1)) {
+                (Some(sv), Some(gv)) => {
+                    assert_eq!(*graph.vertex_data(sv), s);
+                    let mut ep = OutgoingEdges::new(&graph);
+                    index_search(&mut pool, &mut owner, &mut ep, zero_heuristic(), sv, gv);
+                    assert_same_cost(pool.get(gv, &owner).map(|n| owner.ro(n).g), expected);
+                    let mut ep = UndirectedEdges::new(&undirected);
+                    index_search(&mut pool, &mut owner, &mut ep, zero_heuristic(), sv, gv);
+                    assert_same_cost(pool.get(gv, &owner).map(|n| owner.ro(n).g), expected);
+                }
+                _ => assert!(expected.is_none() || s == g),
+            }
+
+            let mut ep = AverageOfFour::new(&weights);
+            grid_search(&mut grid_pool, &mut owner, &mut ep, zero_heuristic(), s, g);
+            let expected = grid_pool.get(g.0, g.1, &owner).map(|n| owner.ro(n).g);
+            let sv = weighted_vertices.vertex(s.0, s.1);
+            let gv = weighted_vertices.vertex(g.0, g.1);
+            if let (Some(sv), Some(gv)) = (sv, gv) {
+                let mut ep = OutgoingEdges::new(&weighted);
+                index_search(&mut pool, &mut owner, &mut ep, zero_heuristic(), sv, gv);
+                assert_same_cost(pool.get(gv, &owner).map(|n| owner.ro(n).g), expected);
+            }
+        }
+    }
+}
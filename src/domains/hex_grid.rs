@@ -0,0 +1,140 @@
+use crate::domains::{BitGrid, WeightedGrid};
+use crate::util::HexLayout;
+
+/// A bit-packed hexagonal grid of obstructed cells, addressed by axial coordinates.
+///
+/// Cells outside the map can be read and are always obstructed, as long as they neighbor the map.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HexBitGrid {
+    layout: HexLayout,
+    cells: BitGrid,
+}
+
+impl HexBitGrid {
+    /// Constructs a fully-unobstructed hex grid.
+    pub fn new(width: i32, height: i32, layout: HexLayout) -> Self {
+        HexBitGrid {
+            layout,
+            cells: BitGrid::new(width, height),
+        }
+    }
+
+    #[inline(always)]
+    pub fn width(&self) -> i32 {
+        self.cells.width()
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> i32 {
+        self.cells.height()
+    }
+
+    #[inline(always)]
+    pub fn layout(&self) -> HexLayout {
+        self.layout
+    }
+
+    /// Returns whether the cell is part of the map.
+    #[inline(always)]
+    pub fn in_bounds(&self, q: i32, r: i32) -> bool {
+        let (x, y) = self.layout.to_offset((q, r));
+        (0..self.width()).contains(&x) && (0..self.height()).contains(&y)
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    pub fn get(&self, q: i32, r: i32) -> bool {
+        let (x, y) = self.layout.to_offset((q, r));
+        if !(-1..self.width() + 1).contains(&x) || !(-1..self.height() + 1).contains(&y) {
+            panic!("Hex cell ({}, {}) is out of bounds.", q, r);
+        }
+        unsafe { self.cells.get_unchecked(x, y) }
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    pub fn set(&mut self, q: i32, r: i32, v: bool) {
+        if !self.in_bounds(q, r) {
+            panic!("Hex cell ({}, {}) is out of bounds.", q, r);
+        }
+        let (x, y) = self.layout.to_offset((q, r));
+        unsafe { self.cells.set_unchecked(x, y, v) }
+    }
+
+    /// SAFETY: `(q, r)` must be in-bounds, or a neighbor of an in-bounds cell.
+    /// Padding cells can be relied upon to yield `true`.
+    #[inline(always)]
+    pub unsafe fn get_unchecked(&self, q: i32, r: i32) -> bool {
+        let (x, y) = self.layout.to_offset((q, r));
+        self.cells.get_unchecked(x, y)
+    }
+}
+
+/// A hexagonal grid of cell costs, addressed by axial coordinates. `None` cells are obstructed.
+///
+/// Cells outside the map can be read and are always obstructed, as long as they neighbor the map.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WeightedHexGrid<V> {
+    layout: HexLayout,
+    cells: WeightedGrid<V>,
+}
+
+impl<V> WeightedHexGrid<V> {
+    /// Constructs a fully-obstructed weighted hex grid.
+    pub fn new(width: i32, height: i32, layout: HexLayout) -> Self {
+        WeightedHexGrid {
+            layout,
+            cells: WeightedGrid::new(width, height),
+        }
+    }
+
+    #[inline(always)]
+    pub fn width(&self) -> i32 {
+        self.cells.width()
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> i32 {
+        self.cells.height()
+    }
+
+    #[inline(always)]
+    pub fn layout(&self) -> HexLayout {
+        self.layout
+    }
+
+    /// Returns whether the cell is part of the map.
+    #[inline(always)]
+    pub fn in_bounds(&self, q: i32, r: i32) -> bool {
+        let (x, y) = self.layout.to_offset((q, r));
+        (0..self.width()).contains(&x) && (0..self.height()).contains(&y)
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    pub fn get(&self, q: i32, r: i32) -> Option<&V> {
+        let (x, y) = self.layout.to_offset((q, r));
+        if !(-1..self.width() + 1).contains(&x) || !(-1..self.height() + 1).contains(&y) {
+            panic!("Hex cell ({}, {}) is out of bounds.", q, r);
+        }
+        unsafe { self.cells.get_unchecked(x, y) }
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    pub fn get_mut(&mut self, q: i32, r: i32) -> &mut Option<V> {
+        if !self.in_bounds(q, r) {
+            panic!("Hex cell ({}, {}) is out of bounds.", q, r);
+        }
+        let (x, y) = self.layout.to_offset((q, r));
+        unsafe { self.cells.get_unchecked_mut(x, y) }
+    }
+
+    /// SAFETY: `(q, r)` must be in-bounds, or a neighbor of an in-bounds cell.
+    /// Padding cells can be relied upon being `None`.
+    #[inline(always)]
+    pub unsafe fn get_unchecked(&self, q: i32, r: i32) -> Option<&V> {
+        let (x, y) = self.layout.to_offset((q, r));
+        self.cells.get_unchecked(x, y)
+    }
+}
@@ -1,6 +1,28 @@
 mod bitgrid;
 pub use bitgrid::BitGrid;
+#[cfg(test)]
+pub(crate) use bitgrid::random_grid;
 mod weighted_grid;
 pub use weighted_grid::WeightedGrid;
+mod hex_grid;
+pub use hex_grid::{HexBitGrid, WeightedHexGrid};
+mod voxel_grid;
+pub use voxel_grid::VoxelGrid;
 mod directed_graph;
-pub use directed_graph::DirectedGraph;
\ No newline at end of file
+pub use directed_graph::DirectedGraph;
+mod csr_graph;
+pub use csr_graph::{CsrEdges, CsrGraph, CsrIndex};
+mod undirected_graph;
+pub use undirected_graph::UndirectedGraph;
+mod grid_graph;
+pub use grid_graph::{grid_to_graph, GridVertexMap};
+mod navmesh;
+pub use navmesh::{load_obj, parse_obj, NavMesh, NavMeshError, Portal};
+mod visibility_graph;
+pub use visibility_graph::VisibilityGraph;
+mod clearance;
+pub use clearance::ClearanceMap;
+mod terrain_grid;
+pub use terrain_grid::TerrainGrid;
+mod components;
+pub use components::ComponentMap;
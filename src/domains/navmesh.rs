@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::util::{cross, distance};
+
+/// A navigation mesh of convex polygons. Polygons sharing an edge are connected through a portal.
+#[derive(Clone, Debug)]
+pub struct NavMesh {
+    vertices: Vec<(f64, f64)>,
+    polygons: Vec<Polygon>,
+}
+
+#[derive(Clone, Debug)]
+struct Polygon {
+    vertices: Vec<usize>,
+    portals: Vec<Portal>,
+    center: (f64, f64),
+}
+
+/// An edge shared with a neighboring polygon. `left` and `right` are vertex IDs, as seen when
+/// crossing the portal into the neighbor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Portal {
+    pub polygon: usize,
+    pub left: usize,
+    pub right: usize,
+}
+
+impl NavMesh {
+    /// Constructs a navmesh from indexed polygons. Polygons may have either winding order, but must
+    /// be convex, and each edge may be shared by at most two polygons.
+    pub fn new(
+        vertices: Vec<(f64, f64)>,
+        polygons: Vec<Vec<usize>>,
+    ) -> Result<NavMesh, NavMeshError> {
+        let mut mesh = NavMesh {
+            polygons: Vec::with_capacity(polygons.len()),
+            vertices,
+        };
+
+        let mut edges = HashMap::new();
+        for mut poly in polygons {
+            let n = poly.len();
+            if poly.iter().any(|&v| v >= mesh.vertices.len()) {
+                return Err(NavMeshError::InvalidVertex);
+            }
+            if n < 3 || (0..n).any(|i| poly[i] == poly[(i + 1) % n]) {
+                return Err(NavMeshError::DegeneratePolygon);
+            }
+
+            let v = |i: usize| mesh.vertices[poly[i % n]];
+            let area: f64 = (0..n).map(|i| cross((0.0, 0.0), v(i), v(i + 1))).sum();
+            if area == 0.0 || !area.is_finite() {
+                return Err(NavMeshError::DegeneratePolygon);
+            }
+            let flip = area < 0.0;
+            for i in 0..n {
+                let (a, b, c) = (v(i), v(i + 1), v(i + 2));
+                // allow collinear vertices, with some slack for rounding
+                let turn = if flip {
+                    -cross(a, b, c)
+                } else {
+                    cross(a, b, c)
+                };
+                if turn < -1e-9 * distance(a, b) * distance(b, c) {
+                    return Err(NavMeshError::NonConvexPolygon);
+                }
+            }
+            if flip {
+                poly.reverse();
+            }
+
+            let id = mesh.polygons.len();
+            for i in 0..n {
+                if edges.insert((poly[i], poly[(i + 1) % n]), id).is_some() {
+                    return Err(NavMeshError::NonManifoldEdge);
+                }
+            }
+            let (sx, sy) = poly.iter().fold((0.0, 0.0), |(sx, sy), &v| {
+                (sx + mesh.vertices[v].0, sy + mesh.vertices[v].1)
+            });
+            mesh.polygons.push(Polygon {
+                vertices: poly,
+                portals: vec![],
+                center: (sx / n as f64, sy / n as f64),
+            });
+        }
+
+        for poly in &mut mesh.polygons {
+            let n = poly.vertices.len();
+            for i in 0..n {
+                let (a, b) = (poly.vertices[i], poly.vertices[(i + 1) % n]);
+                // polygons are CCW, so leaving through the edge from `a` to `b`, `a` is on the right
+                if let Some(&polygon) = edges.get(&(b, a)) {
+                    poly.portals.push(Portal {
+                        polygon,
+                        left: b,
+                        right: a,
+                    });
+                }
+            }
+        }
+
+        Ok(mesh)
+    }
+
+    /// Constructs a navmesh from polygons given by their vertex positions, such as a triangle soup.
+    /// Vertices at exactly the same position are merged.
+    pub fn from_soup<P: AsRef<[(f64, f64)]>>(
+        polygons: impl IntoIterator<Item = P>,
+    ) -> Result<NavMesh, NavMeshError> {
+        let mut vertices = vec![];
+        let mut ids = HashMap::new();
+        let polygons = polygons
+            .into_iter()
+            .map(|poly| {
+                poly.as_ref()
+                    .iter()
+                    .map(|&(x, y)| {
+                        // note: adding 0.0 normalizes -0.0 to 0.0
+                        *ids.entry(((x + 0.0).to_bits(), (y + 0.0).to_bits()))
+                            .or_insert_with(|| {
+                                vertices.push((x, y));
+                                vertices.len() - 1
+                            })
+                    })
+                    .collect()
+            })
+            .collect();
+        NavMesh::new(vertices, polygons)
+    }
+
+    /// Returns the number of polygons.
+    pub fn len(&self) -> usize {
+        self.polygons.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.polygons.is_empty()
+    }
+
+    pub fn vertex(&self, vertex: usize) -> (f64, f64) {
+        self.vertices[vertex]
+    }
+
+    /// Returns the vertex IDs of the polygon in counter-clockwise order.
+    pub fn polygon(&self, polygon: usize) -> &[usize] {
+        &self.polygons[polygon].vertices
+    }
+
+    /// Returns the mean of the polygon's vertices.
+    pub fn center(&self, polygon: usize) -> (f64, f64) {
+        self.polygons[polygon].center
+    }
+
+    pub fn portals(&self, polygon: usize) -> &[Portal] {
+        &self.polygons[polygon].portals
+    }
+
+    /// Finds the portal leading from `from` to `to`, if they are adjacent.
+    pub fn find_portal(&self, from: usize, to: usize) -> Option<&Portal> {
+        self.polygons[from].portals.iter().find(|p| p.polygon == to)
+    }
+
+    /// Returns whether the point lies inside or on the boundary of the polygon.
+    pub fn contains(&self, polygon: usize, point: (f64, f64)) -> bool {
+        let poly = &self.polygons[polygon].vertices;
+        (0..poly.len()).all(|i| {
+            let a = self.vertices[poly[i]];
+            let b = self.vertices[poly[(i + 1) % poly.len()]];
+            cross(a, b, point) >= -1e-9 * distance(a, b)
+        })
+    }
+
+    /// Finds a polygon containing the point.
+    ///
+    /// note: runtime is linear in the number of polygons.
+    pub fn locate(&self, point: (f64, f64)) -> Option<usize> {
+        (0..self.polygons.len()).find(|&p| self.contains(p, point))
+    }
+
+    /// Euclidean distance between polygon centers.
+    pub fn heuristic(&self, goal: usize) -> impl Fn(usize) -> f64 + '_ {
+        let target = self.polygons[goal].center;
+        move |p| distance(self.polygons[p].center, target)
+    }
+
+    /// Shortens a corridor of adjacent polygons leading from the polygon containing `start` to the
+    /// polygon containing `goal` into the taut path through it, using the simple stupid funnel
+    /// algorithm. The returned path includes `start` and `goal`.
+    #[track_caller]
+    pub fn funnel(
+        &self,
+        start: (f64, f64),
+        goal: (f64, f64),
+        corridor: &[usize],
+    ) -> Vec<(f64, f64)> {
+        let mut portals = Vec::with_capacity(corridor.len() + 1);
+        portals.push((start, start));
+        for w in corridor.windows(2) {
+            let portal = match self.find_portal(w[0], w[1]) {
+                Some(p) => p,
+                None => panic!("Polygons {} and {} are not adjacent.", w[0], w[1]),
+            };
+            portals.push((self.vertices[portal.left], self.vertices[portal.right]));
+        }
+        portals.push((goal, goal));
+
+        let mut path = vec![start];
+        let (mut apex, mut left, mut right) = (start, start, start);
+        let (mut left_i, mut right_i) = (0, 0);
+        let mut i = 1;
+        while i < portals.len() {
+            let (l, r) = portals[i];
+
+            // tighten the right side of the funnel
+            if cross(apex, right, r) >= 0.0 {
+                if apex == right || cross(apex, left, r) < 0.0 {
+                    right = r;
+                    right_i = i;
+                } else {
+                    // the right side crossed over the left, so the left is a corner of the path
+                    if path.last() != Some(&left) {
+                        path.push(left);
+                    }
+                    apex = left;
+                    right = apex;
+                    right_i = left_i;
+                    i = left_i + 1;
+                    continue;
+                }
+            }
+
+            // tighten the left side of the funnel
+            if cross(apex, left, l) <= 0.0 {
+                if apex == left || cross(apex, right, l) > 0.0 {
+                    left = l;
+                    left_i = i;
+                } else {
+                    if path.last() != Some(&right) {
+                        path.push(right);
+                    }
+                    apex = right;
+                    left = apex;
+                    left_i = right_i;
+                    i = right_i + 1;
+                    continue;
+                }
+            }
+
+            i += 1;
+        }
+
+        if path.last() != Some(&goal) {
+            path.push(goal);
+        }
+        path
+    }
+}
+
+/// Loads a navmesh from a Wavefront OBJ file. See [`parse_obj`].
+pub fn load_obj(obj: &Path) -> Result<NavMesh, NavMeshError> {
+    parse_obj(BufReader::new(File::open(obj)?))
+}
+
+/// Parses a navmesh from the `v` and `f` statements of a Wavefront OBJ file. Vertices are projected
+/// onto the XZ plane, as OBJ files are conventionally Y-up. Other statements are ignored.
+pub fn parse_obj(obj: impl BufRead) -> Result<NavMesh, NavMeshError> {
+    let mut vertices = vec![];
+    let mut polygons = vec![];
+    for line in obj.lines() {
+        let line = line?;
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let mut coord = || -> Result<f64, NavMeshError> {
+                    Ok(tokens.next().ok_or(NavMeshError::InvalidData)?.parse()?)
+                };
+                let (x, _, z) = (coord()?, coord()?, coord()?);
+                vertices.push((x, z));
+            }
+            Some("f") => {
+                let poly = tokens
+                    .map(|t| {
+                        // faces may be written as `v`, `v/vt`, `v//vn` or `v/vt/vn`
+                        let v: i64 = t.split('/').next().unwrap_or("").parse()?;
+                        let id = match v {
+                            v if v > 0 => v as usize - 1,
+                            v if v < 0 && (-v) as usize <= vertices.len() => {
+                                vertices.len() - (-v) as usize
+                            }
+                            _ => return Err(NavMeshError::InvalidVertex),
+                        };
+                        Ok(id)
+                    })
+                    .collect::<Result<_, NavMeshError>>()?;
+                polygons.push(poly);
+            }
+            _ => {}
+        }
+    }
+    NavMesh::new(vertices, polygons)
+}
+
+#[derive(Debug)]
+pub enum NavMeshError {
+    Stdio(std::io::Error),
+    ParseFloat(std::num::ParseFloatError),
+    ParseInt(std::num::ParseIntError),
+    InvalidData,
+    InvalidVertex,
+    DegeneratePolygon,
+    NonConvexPolygon,
+    NonManifoldEdge,
+}
+
+impl From<std::io::Error> for NavMeshError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Stdio(e)
+    }
+}
+
+impl From<std::num::ParseFloatError> for NavMeshError {
+    fn from(e: std::num::ParseFloatError) -> Self {
+        Self::ParseFloat(e)
+    }
+}
+
+impl From<std::num::ParseIntError> for NavMeshError {
+    fn from(e: std::num::ParseIntError) -> Self {
+        Self::ParseInt(e)
+    }
+}
+
+impl std::fmt::Display for NavMeshError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stdio(e) => write!(f, "{}", e),
+            Self::ParseFloat(e) => write!(f, "{}", e),
+            Self::ParseInt(e) => write!(f, "{}", e),
+            Self::InvalidData => write!(f, "Invalid data provided"),
+            Self::InvalidVertex => write!(f, "Polygon refers to a vertex that doesn't exist"),
+            Self::DegeneratePolygon => write!(f, "Polygon has no area"),
+            Self::NonConvexPolygon => write!(f, "Polygon is not convex"),
+            Self::NonManifoldEdge => write!(f, "Edge is shared by more than two polygons"),
+        }
+    }
+}
+
+impl std::error::Error for NavMeshError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Stdio(e) => Some(e),
+            Self::ParseFloat(e) => Some(e),
+            Self::ParseInt(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use enumset::EnumSet;
+
+use crate::domains::BitGrid;
+use crate::util::Direction;
+
+/// A grid storing a terrain type from `0` to `63` in each cell.
+///
+/// Which terrain types are passable is decided per query by a capability mask, in which bit `t` is
+/// set if terrain type `t` is passable, e.g. a mask of `1 << GROUND | 1 << WATER` for amphibious
+/// units. Cells outside the map can be read and are never passable.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TerrainGrid {
+    width: i32,
+    height: i32,
+    cells: Box<[u8]>,
+}
+
+/// The terrain type of padding cells, which is not covered by any mask.
+const PADDING: u8 = u8::MAX;
+
+impl TerrainGrid {
+    /// Constructs a terrain grid with every cell set to `terrain`.
+    #[track_caller]
+    pub fn new(width: i32, height: i32, terrain: u8) -> Self {
+        assert!(width > 0 && height > 0, "width and height must be positive");
+        assert!(terrain < 64, "terrain types must be less than 64");
+        // same padding as WeightedGrid
+        let padded_width = width as usize + 1;
+        let padded_height = height as usize + 2;
+        let mut this = TerrainGrid {
+            width,
+            height,
+            cells: vec![PADDING; padded_width * padded_height + 1].into_boxed_slice(),
+        };
+        for y in 0..height {
+            for x in 0..width {
+                unsafe { this.set_unchecked(x, y, terrain) }
+            }
+        }
+        this
+    }
+
+    #[inline(always)]
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Returns the terrain type of the cell.
+    #[track_caller]
+    #[inline(always)]
+    pub fn get(&self, x: i32, y: i32) -> u8 {
+        self.unpadded_bounds_check(x, y);
+        unsafe { self.get_unchecked(x, y) }
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    pub fn set(&mut self, x: i32, y: i32, terrain: u8) {
+        self.unpadded_bounds_check(x, y);
+        assert!(terrain < 64, "terrain types must be less than 64");
+        unsafe { self.set_unchecked(x, y, terrain) }
+    }
+
+    /// Returns whether the cell is passable for the capability mask.
+    #[track_caller]
+    #[inline(always)]
+    pub fn passable(&self, x: i32, y: i32, mask: u64) -> bool {
+        self.padded_bounds_check(x, y);
+        unsafe { self.passable_unchecked(x, y, mask) }
+    }
+
+    /// Returns the neighbors of the cell which are impassable for the capability mask, in the same
+    /// form as `BitGrid::get_neighbors`.
+    #[track_caller]
+    #[inline(always)]
+    pub fn get_neighbors(&self, x: i32, y: i32, mask: u64) -> EnumSet<Direction> {
+        self.unpadded_bounds_check(x, y);
+        unsafe { self.get_neighbors_unchecked(x, y, mask) }
+    }
+
+    /// SAFETY: `x` must be in `-1..width+1`, `y` must be in `-1..height+1`.
+    /// Padding cells have a terrain type of 255.
+    #[inline(always)]
+    pub unsafe fn get_unchecked(&self, x: i32, y: i32) -> u8 {
+        *self.cells.get_unchecked(self.locate(x, y))
+    }
+
+    /// SAFETY: `x` must be in `0..width`, `y` must be in `0..height`, and `terrain` must be less
+    /// than 64.
+    #[inline(always)]
+    pub unsafe fn set_unchecked(&mut self, x: i32, y: i32, terrain: u8) {
+        let idx = self.locate(x, y);
+        *self.cells.get_unchecked_mut(idx) = terrain;
+    }
+
+    /// SAFETY: `x` must be in `-1..width+1`, `y` must be in `-1..height+1`.
+    /// Padding cells can be relied upon to be impassable.
+    #[inline(always)]
+    pub unsafe fn passable_unchecked(&self, x: i32, y: i32, mask: u64) -> bool {
+        let t = self.get_unchecked(x, y);
+        t < 64 && mask >> t & 1 != 0
+    }
+
+    /// SAFETY: `x` must be in `0..width`, `y` must be in `0..height`
+    #[inline(always)]
+    pub unsafe fn get_neighbors_unchecked(&self, x: i32, y: i32, mask: u64) -> EnumSet<Direction> {
+        let mut nbs = EnumSet::new();
+        let dirs = [
+            (Direction::NorthWest, -1, -1),
+            (Direction::North, 0, -1),
+            (Direction::NorthEast, 1, -1),
+            (Direction::West, -1, 0),
+            (Direction::East, 1, 0),
+            (Direction::SouthWest, -1, 1),
+            (Direction::South, 0, 1),
+            (Direction::SouthEast, 1, 1),
+        ];
+        for &(dir, dx, dy) in &dirs {
+            if !self.passable_unchecked(x + dx, y + dy, mask) {
+                nbs |= dir;
+            }
+        }
+        nbs
+    }
+
+    /// Builds a `BitGrid` in which the cells impassable for the capability mask are obstructed,
+    /// e.g. to search it with `JpsExpansionPolicy`.
+    ///
+    /// note: runtime is linear in the number of cells, but the `BitGrid` is written a word of 64
+    ///       cells at a time.
+    pub fn to_bitgrid(&self, mask: u64) -> BitGrid {
+        let mut map = BitGrid::new(self.width, self.height);
+        let mut bits = vec![0; self.width as usize / 64 + 1];
+        for y in 0..self.height {
+            let start = self.locate(0, y);
+            let row = &self.cells[start..start + self.width as usize];
+            bits.fill(0);
+            for (x, &t) in row.iter().enumerate() {
+                let blocked = t >= 64 || mask >> t & 1 == 0;
+                bits[x / 64] |= (blocked as u64) << (x % 64);
+            }
+            map.set_row_bits(y, &bits);
+        }
+        map
+    }
+
+    #[inline(always)]
+    fn locate(&self, x: i32, y: i32) -> usize {
+        #[cfg(debug_assertions)]
+        self.padded_bounds_check(x, y);
+
+        let padded_width = self.width as usize + 1;
+        (y + 1) as usize * padded_width + (x + 1) as usize
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    fn padded_bounds_check(&self, x: i32, y: i32) {
+        if !(-1..self.width + 1).contains(&x) || !(-1..self.height + 1).contains(&y) {
+            panic!("Grid cell ({}, {}) is out of bounds.", x, y);
+        }
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    fn unpadded_bounds_check(&self, x: i32, y: i32) {
+        if !(0..self.width).contains(&x) || !(0..self.height).contains(&y) {
+            panic!("Grid cell ({}, {}) is out of bounds.", x, y);
+        }
+    }
+}
@@ -0,0 +1,343 @@
+use crate::domains::DirectedGraph;
+use crate::Edge;
+
+/// An undirected graph in which every edge is stored once, and listed by both of its vertices.
+pub struct UndirectedGraph<V> {
+    vertices: Vec<Vertex<V>>,
+    edges: Vec<UndirectedEdge>,
+}
+
+struct Vertex<V> {
+    /// Indices into the edge store of the edges touching this vertex.
+    edges: Vec<usize>,
+    data: V,
+}
+
+#[derive(Copy, Clone)]
+struct UndirectedEdge {
+    a: usize,
+    b: usize,
+    cost: f64,
+}
+
+impl UndirectedEdge {
+    #[inline(always)]
+    fn to_edge(self, from: usize) -> Edge<usize> {
+        Edge {
+            destination: if self.a == from { self.b } else { self.a },
+            cost: self.cost,
+        }
+    }
+}
+
+impl<V> UndirectedGraph<V> {
+    pub fn new() -> Self {
+        UndirectedGraph {
+            vertices: vec![],
+            edges: vec![],
+        }
+    }
+
+    pub fn add_vertex(&mut self, data: V) -> usize {
+        let id = self.vertices.len();
+        self.vertices.push(Vertex {
+            edges: vec![],
+            data,
+        });
+        id
+    }
+
+    /// Adds an edge between `a` and `b`, or sets its cost if it is already present.
+    ///
+    /// note: runtime is linear in the number of edges on the relevant vertices.
+    pub fn add_edge(&mut self, a: usize, b: usize, cost: f64) {
+        assert!(
+            a < self.vertices.len() && b < self.vertices.len(),
+            "a and b vertices must exist"
+        );
+
+        match self.edge_index(a, b) {
+            Some(e) => self.edges[e].cost = cost,
+            None => {
+                let e = self.edges.len();
+                self.edges.push(UndirectedEdge { a, b, cost });
+                self.vertices[a].edges.push(e);
+                if a != b {
+                    self.vertices[b].edges.push(e);
+                }
+            }
+        }
+    }
+
+    /// bulk loading method. if there are duplicate edges, in either direction, the last one is
+    /// kept.
+    pub fn try_add_edges(&mut self, edges: &[(usize, usize, f64)]) -> Result<(), &'static str> {
+        let mut result = Ok(());
+        let mut all = std::mem::take(&mut self.edges);
+        for &(a, b, cost) in edges {
+            if a >= self.vertices.len() || b >= self.vertices.len() {
+                result = Err("Edge vertices don't exist");
+                break;
+            }
+            all.push(UndirectedEdge { a, b, cost });
+        }
+
+        // stable, so duplicates stay in the order they were added
+        all.sort_by_key(|e| (e.a.min(e.b), e.a.max(e.b)));
+        all.dedup_by(|a, b| {
+            // like DirectedGraph::try_add_edges, swap so that the later edge is kept
+            let same_bucket = (a.a.min(a.b), a.a.max(a.b)) == (b.a.min(b.b), b.a.max(b.b));
+            if same_bucket {
+                std::mem::swap(a, b);
+            }
+            same_bucket
+        });
+
+        for vertex in &mut self.vertices {
+            vertex.edges.clear();
+        }
+        for (i, e) in all.iter().enumerate() {
+            self.vertices[e.a].edges.push(i);
+            if e.a != e.b {
+                self.vertices[e.b].edges.push(i);
+            }
+        }
+        self.edges = all;
+
+        result
+    }
+
+    /// Removes the edge, returning its cost if it was present.
+    ///
+    /// note: runtime is linear in the number of edges on the relevant vertices.
+    pub fn remove_edge(&mut self, a: usize, b: usize) -> Option<f64> {
+        assert!(
+            a < self.vertices.len() && b < self.vertices.len(),
+            "a and b vertices must exist"
+        );
+
+        let e = self.edge_index(a, b)?;
+        self.unlist(a, e);
+        self.unlist(b, e);
+        let removed = self.edges.swap_remove(e);
+
+        // the last edge took the index of the removed one
+        if e < self.edges.len() {
+            let moved = self.edges[e];
+            let old = self.edges.len();
+            for v in [moved.a, moved.b] {
+                if let Some(i) = self.vertices[v].edges.iter().position(|&i| i == old) {
+                    self.vertices[v].edges[i] = e;
+                }
+            }
+        }
+        Some(removed.cost)
+    }
+
+    fn unlist(&mut self, vertex: usize, e: usize) {
+        let edges = &mut self.vertices[vertex].edges;
+        if let Some(i) = edges.iter().position(|&i| i == e) {
+            edges.swap_remove(i);
+        }
+    }
+
+    fn edge_index(&self, a: usize, b: usize) -> Option<usize> {
+        // search the vertex with fewer edges
+        let (from, to) = if self.vertices[a].edges.len() <= self.vertices[b].edges.len() {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.vertices[from]
+            .edges
+            .iter()
+            .copied()
+            .find(|&e| self.edges[e].to_edge(from).destination == to)
+    }
+
+    /// note: runtime is linear in the number of edges on the relevant vertices.
+    pub fn find_edge(&self, a: usize, b: usize) -> Option<f64> {
+        self.edge_index(a, b).map(|e| self.edges[e].cost)
+    }
+
+    pub fn vertex_data(&self, vertex: usize) -> &V {
+        &self.vertices[vertex].data
+    }
+
+    pub fn edges_of(&self, vertex: usize) -> impl Iterator<Item = Edge<usize>> + '_ {
+        self.vertices[vertex]
+            .edges
+            .iter()
+            .map(move |&e| self.edges[e].to_edge(vertex))
+    }
+
+    pub unsafe fn vertex_data_unchecked(&self, vertex: usize) -> &V {
+        &self.vertices.get_unchecked(vertex).data
+    }
+
+    pub unsafe fn edges_of_unchecked(
+        &self,
+        vertex: usize,
+    ) -> impl Iterator<Item = Edge<usize>> + '_ {
+        self.vertices
+            .get_unchecked(vertex)
+            .edges
+            .iter()
+            .map(move |&e| self.edges.get_unchecked(e).to_edge(vertex))
+    }
+
+    pub fn len(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn total_edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Iterates over all edges as `(a, b, cost)`, each once.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, f64)> + '_ {
+        self.edges.iter().map(|e| (e.a, e.b, e.cost))
+    }
+
+    /// Builds an undirected graph from a directed graph in which every edge has a reverse edge of
+    /// the same cost.
+    pub fn from_directed(graph: &DirectedGraph<V>) -> Result<Self, &'static str>
+    where
+        V: Clone,
+    {
+        let mut result = UndirectedGraph::new();
+        for v in 0..graph.len() {
+            result.add_vertex(graph.vertex_data(v).clone());
+        }
+        let mut edges = vec![];
+        for (from, to, cost) in graph.edges() {
+            match graph.find_edge(to, from) {
+                Some(e) if e.cost == cost => {}
+                Some(_) => return Err("Edge costs are asymmetric"),
+                None => return Err("Edge has no reverse edge"),
+            }
+            if from <= to {
+                edges.push((from, to, cost));
+            }
+        }
+        result.try_add_edges(&edges)?;
+        Ok(result)
+    }
+
+    /// Builds a directed graph with an edge in each direction for every edge.
+    pub fn to_directed(&self) -> DirectedGraph<V>
+    where
+        V: Clone,
+    {
+        let mut graph = DirectedGraph::new();
+        for v in &self.vertices {
+            graph.add_vertex(v.data.clone());
+        }
+        let edges: Vec<_> = self
+            .edges()
+            .flat_map(|(a, b, cost)| [(a, b, cost), (b, a, cost)])
+            .collect();
+        // the vertices exist, so this can't fail
+        graph.try_add_edges(&edges).unwrap();
+        graph
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize)]
+    struct UndirectedGraphRef<'a, V> {
+        vertices: Vec<&'a V>,
+        edges: Vec<(usize, usize, f64)>,
+    }
+
+    #[derive(Deserialize)]
+    struct UndirectedGraphTransport<V> {
+        vertices: Vec<V>,
+        edges: Vec<(usize, usize, f64)>,
+    }
+
+    impl<V: Serialize> Serialize for super::UndirectedGraph<V> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            UndirectedGraphRef {
+                vertices: self.vertices.iter().map(|v| &v.data).collect(),
+                edges: self.edges().collect(),
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de, V: Deserialize<'de>> Deserialize<'de> for super::UndirectedGraph<V> {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            let v = UndirectedGraphTransport::deserialize(deserializer)?;
+            let mut graph = super::UndirectedGraph::new();
+            for data in v.vertices {
+                graph.add_vertex(data);
+            }
+            if let Err(e) = graph.try_add_edges(&v.edges) {
+                return Err(serde::de::Error::custom(e));
+            }
+            Ok(graph)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    #[test]
+    fn check_single_store() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let mut graph = UndirectedGraph::new();
+        let mut directed = DirectedGraph::new();
+        for i in 0..30 {
+            graph.add_vertex(i);
+            directed.add_vertex(i);
+        }
+        for _ in 0..300 {
+            let (a, b) = (rng.gen_range(0..30), rng.gen_range(0..30));
+            if rng.gen_bool(0.7) {
+                let cost = rng.gen_range(1.0..10.0);
+                graph.add_edge(a, b, cost);
+                directed.add_edge(a, b, cost);
+                directed.add_edge(b, a, cost);
+            } else {
+                assert_eq!(graph.remove_edge(a, b), directed.remove_edge(a, b));
+                directed.remove_edge(b, a);
+            }
+        }
+
+        let loops = (0..30)
+            .filter(|&v| directed.find_edge(v, v).is_some())
+            .count();
+        assert_eq!(graph.total_edges() * 2 - loops, directed.total_edges());
+        for v in 0..30 {
+            let mut edges: Vec<_> = graph.edges_of(v).collect();
+            edges.sort_by_key(|e| e.destination);
+            assert_eq!(edges, directed.outgoing_edges(v));
+        }
+
+        let copy = UndirectedGraph::from_directed(&graph.to_directed()).unwrap();
+        let mut expected: Vec<_> = graph
+            .edges()
+            .map(|(a, b, c)| (a.min(b), a.max(b), c))
+            .collect();
+        expected.sort_by(|x, y| x.partial_cmp(y).unwrap());
+        assert!(copy.edges().eq(expected));
+
+        directed.add_edge(0, 1, 1.0);
+        directed.add_edge(1, 0, 2.0);
+        assert!(UndirectedGraph::from_directed(&directed).is_err());
+    }
+}
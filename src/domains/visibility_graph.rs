@@ -0,0 +1,278 @@
+use crate::util::{cross, distance};
+
+use super::DirectedGraph;
+
+/// The visibility graph of a set of polygonal obstacles. Vertices are the convex corners of the
+/// obstacles, and edges connect every pair of corners which can see each other, costing the
+/// Euclidean distance between them. Paths may touch the boundaries of obstacles, but not pass
+/// through their interiors.
+///
+/// Start and goal points are inserted with [`VisibilityGraph::insert_query`], which replaces the
+/// points inserted by the previous query.
+pub struct VisibilityGraph {
+    obstacles: Vec<Vec<(f64, f64)>>,
+    graph: DirectedGraph<(f64, f64)>,
+    corners: usize,
+}
+
+impl VisibilityGraph {
+    /// Builds the visibility graph of simple polygons, given in either winding order.
+    ///
+    /// note: runtime is cubic in the number of obstacle vertices.
+    #[track_caller]
+    pub fn new<P: AsRef<[(f64, f64)]>>(obstacles: impl IntoIterator<Item = P>) -> Self {
+        let obstacles: Vec<Vec<_>> = obstacles
+            .into_iter()
+            .map(|poly| {
+                let mut poly = poly.as_ref().to_vec();
+                assert!(poly.len() >= 3, "Obstacles must have at least 3 vertices.");
+                let n = poly.len();
+                let area: f64 = (0..n)
+                    .map(|i| cross((0.0, 0.0), poly[i], poly[(i + 1) % n]))
+                    .sum();
+                if area < 0.0 {
+                    poly.reverse();
+                }
+                poly
+            })
+            .collect();
+
+        let mut vg = VisibilityGraph {
+            obstacles,
+            graph: DirectedGraph::new(),
+            corners: 0,
+        };
+        for poly in &vg.obstacles {
+            let n = poly.len();
+            for i in 0..n {
+                let (a, b, c) = (poly[(i + n - 1) % n], poly[i], poly[(i + 1) % n]);
+                // reflex corners are never on shortest paths, nor are corners buried in other
+                // obstacles
+                if cross(a, b, c) > 0.0 && !vg.obstacles.iter().any(|o| strictly_inside(o, b)) {
+                    vg.graph.add_vertex(b);
+                }
+            }
+        }
+        vg.corners = vg.graph.len();
+
+        let mut edges = vec![];
+        for a in 0..vg.corners {
+            for b in a + 1..vg.corners {
+                let (pa, pb) = (*vg.graph.vertex_data(a), *vg.graph.vertex_data(b));
+                if vg.is_visible(pa, pb) {
+                    let cost = distance(pa, pb);
+                    edges.push((a, b, cost));
+                    edges.push((b, a, cost));
+                }
+            }
+        }
+        vg.graph
+            .try_add_edges(&edges)
+            .expect("corners are vertices of the graph");
+        vg
+    }
+
+    pub fn graph(&self) -> &DirectedGraph<(f64, f64)> {
+        &self.graph
+    }
+
+    pub fn into_graph(self) -> DirectedGraph<(f64, f64)> {
+        self.graph
+    }
+
+    /// The number of obstacle corners in the graph. Vertex IDs below this are corners.
+    pub fn corners(&self) -> usize {
+        self.corners
+    }
+
+    /// Inserts start and goal vertices connected to everything visible from them, removing those
+    /// of the previous query. Returns the IDs of the start and goal vertices.
+    pub fn insert_query(&mut self, start: (f64, f64), goal: (f64, f64)) -> (usize, usize) {
+        self.graph.truncate(self.corners);
+        let s = self.graph.add_vertex(start);
+        let g = self.graph.add_vertex(goal);
+        for &(v, p) in &[(s, start), (g, goal)] {
+            for c in 0..self.corners {
+                let corner = *self.graph.vertex_data(c);
+                if self.is_visible(p, corner) {
+                    let cost = distance(p, corner);
+                    self.graph.add_edge(v, c, cost);
+                    self.graph.add_edge(c, v, cost);
+                }
+            }
+        }
+        if self.is_visible(start, goal) {
+            let cost = distance(start, goal);
+            self.graph.add_edge(s, g, cost);
+            self.graph.add_edge(g, s, cost);
+        }
+        (s, g)
+    }
+
+    /// Returns whether the line segment between the points avoids the interiors of all obstacles.
+    pub fn is_visible(&self, a: (f64, f64), b: (f64, f64)) -> bool {
+        if a == b {
+            return !self.obstacles.iter().any(|o| strictly_inside(o, a));
+        }
+        let len = distance(a, b);
+        let ab = (b.0 - a.0, b.1 - a.1);
+
+        // the segment is split into pieces by the obstacle vertices lying on it. since no obstacle
+        // edge crosses the segment, each piece is either entirely inside an obstacle or not.
+        let mut splits = vec![0.0, 1.0];
+        for poly in &self.obstacles {
+            let n = poly.len();
+            for i in 0..n {
+                let (p, q) = (poly[i], poly[(i + 1) % n]);
+                if side(a, b, p) * side(a, b, q) < 0 && side(p, q, a) * side(p, q, b) < 0 {
+                    return false;
+                }
+                if side(a, b, p) == 0 {
+                    let t = ((p.0 - a.0) * ab.0 + (p.1 - a.1) * ab.1) / (len * len);
+                    if t > 0.0 && t < 1.0 {
+                        splits.push(t);
+                    }
+                }
+            }
+        }
+        splits.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        splits.windows(2).all(|w| {
+            let t = (w[0] + w[1]) / 2.0;
+            let mid = (a.0 + ab.0 * t, a.1 + ab.1 * t);
+            w[1] - w[0] < 1e-12 || !self.obstacles.iter().any(|o| strictly_inside(o, mid))
+        })
+    }
+}
+
+/// The side of the line through `a` and `b` that `c` is on; 1 for left, -1 for right, and 0 when
+/// it is within rounding distance of the line.
+fn side(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> i32 {
+    let d = cross(a, b, c);
+    let eps = 1e-9 * distance(a, b);
+    if d > eps {
+        1
+    } else if d < -eps {
+        -1
+    } else {
+        0
+    }
+}
+
+/// Returns whether the point is inside the polygon, and not on its boundary.
+fn strictly_inside(poly: &[(f64, f64)], p: (f64, f64)) -> bool {
+    let n = poly.len();
+    let mut inside = false;
+    for i in 0..n {
+        let (a, b) = (poly[i], poly[(i + 1) % n]);
+
+        // distance from the point to the edge
+        let ab = (b.0 - a.0, b.1 - a.1);
+        let t = ((p.0 - a.0) * ab.0 + (p.1 - a.1) * ab.1) / (ab.0 * ab.0 + ab.1 * ab.1);
+        let t = t.clamp(0.0, 1.0);
+        if distance(p, (a.0 + ab.0 * t, a.1 + ab.1 * t)) < 1e-9 {
+            return false;
+        }
+
+        if (a.1 > p.1) != (b.1 > p.1) && p.0 < a.0 + (p.1 - a.1) / (b.1 - a.1) * ab.0 {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expansion_policy::graph::OutgoingEdges;
+    use crate::node_pool::IndexPool;
+    use crate::util::{assert_same_cost, euclidean_graph_heuristic, index_search, zero_heuristic};
+    use crate::Owner;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    fn shortest(
+        vg: &mut VisibilityGraph,
+        h: bool,
+        start: (f64, f64),
+        goal: (f64, f64),
+    ) -> Option<f64> {
+        let (s, g) = vg.insert_query(start, goal);
+        let graph = vg.graph();
+        let mut pool = IndexPool::new(graph.len());
+        let mut owner = Owner::new();
+        let mut ep = OutgoingEdges::new(graph);
+        if h {
+            let h = euclidean_graph_heuristic(graph, g, 1.0);
+            index_search(&mut pool, &mut owner, &mut ep, h, s, g);
+        } else {
+            index_search(&mut pool, &mut owner, &mut ep, zero_heuristic(), s, g);
+        }
+        let node = pool.get(g, &owner)?;
+        Some(owner.ro(node).g)
+    }
+
+    #[test]
+    fn check_known_paths() {
+        let square = [(0.0, 0.0), (2.0, 0.0), (2.0, 1.0), (0.0, 1.0)];
+        let mut vg = VisibilityGraph::new([square]);
+        assert_eq!(vg.corners(), 4);
+        // the sides are visible, the diagonals are not
+        assert_eq!(vg.graph().total_edges(), 8);
+        let expected = 2.0 * 1.25f64.sqrt() + 2.0;
+        let actual = shortest(&mut vg, true, (-1.0, 0.5), (3.0, 0.5)).unwrap();
+        assert!((actual - expected).abs() < 1e-9);
+        assert_eq!(shortest(&mut vg, true, (-1.0, 2.0), (3.0, 2.0)), Some(4.0));
+        assert_eq!(vg.graph().len(), 6);
+
+        // a U-shaped obstacle, given clockwise
+        let mut u = vec![
+            (0.0, 0.0),
+            (3.0, 0.0),
+            (3.0, 3.0),
+            (2.0, 3.0),
+            (2.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 3.0),
+            (0.0, 3.0),
+        ];
+        u.reverse();
+        let mut vg = VisibilityGraph::new(&[u]);
+        assert_eq!(vg.corners(), 6);
+        let expected = 0.5f64.sqrt() + 1.0 + 3.0 + 3.25f64.sqrt();
+        let actual = shortest(&mut vg, true, (1.5, 2.5), (1.5, -1.0)).unwrap();
+        assert!((actual - expected).abs() < 1e-9);
+        assert_eq!(shortest(&mut vg, true, (1.5, 2.5), (0.5, 0.5)), None);
+    }
+
+    #[test]
+    fn check_random_rectangles() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let mut obstacles = vec![];
+        for y in 0..4 {
+            for x in 0..4 {
+                if rng.gen_bool(0.6) {
+                    let (x, y) = (x as f64 * 3.0, y as f64 * 3.0);
+                    let (w, h) = (rng.gen_range(0.5..2.5), rng.gen_range(0.5..2.5));
+                    obstacles.push([(x, y), (x + w, y), (x + w, y + h), (x, y + h)]);
+                }
+            }
+        }
+        let mut vg = VisibilityGraph::new(&obstacles);
+        assert_eq!(vg.corners(), obstacles.len() * 4);
+
+        for _ in 0..50 {
+            let mut point = || (rng.gen_range(-1.0..12.0), rng.gen_range(-1.0..12.0));
+            let (start, goal) = (point(), point());
+            let expected = shortest(&mut vg, false, start, goal);
+            let actual = shortest(&mut vg, true, start, goal);
+            assert_eq!(vg.graph().len(), vg.corners() + 2);
+            assert_same_cost(actual, expected);
+            if let Some(g) = actual {
+                assert!(g >= distance(start, goal) - 1e-9);
+                if vg.is_visible(start, goal) {
+                    assert!((g - distance(start, goal)).abs() < 1e-9);
+                }
+            }
+        }
+    }
+}
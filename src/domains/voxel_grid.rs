@@ -0,0 +1,359 @@
+/// A bit-packed 3D occupancy grid. Set bits are obstructed.
+///
+/// Like `BitGrid`, the grid is padded by one obstructed cell on every side, and rows along the x
+/// axis can be read 57 cells at a time.
+pub struct VoxelGrid {
+    width: i32,
+    height: i32,
+    depth: i32,
+    cells: Box<[u8]>,
+}
+
+impl VoxelGrid {
+    pub fn new(width: i32, height: i32, depth: i32) -> Self {
+        assert!(
+            width > 0 && height > 0 && depth > 0,
+            "width, height and depth must be positive"
+        );
+        // there is 1 padding bit at the end of each row
+        let padded_width = width as usize + 1;
+        // there is a padding row above and a padding row below in each layer
+        let padded_height = height as usize + 2;
+        // there is a padding layer in front and a padding layer behind
+        let padded_depth = depth as usize + 2;
+        // there is one extra bit so that the unpadded coordinate (width, height, depth),
+        // which is 1 cell out of bounds on each axis, can be dereferenced.
+        let padded_size = padded_width * padded_height * padded_depth + 1;
+        let bytes = (padded_size - 1) / u8::BITS as usize + 1;
+
+        let mut this = VoxelGrid {
+            width,
+            height,
+            depth,
+            cells: vec![0; 8 + bytes + 8].into_boxed_slice(),
+        };
+
+        // initialize padding to 1s
+        this.cells[..8].fill(!0);
+        let l = this.cells.len();
+        this.cells[l - 8..].fill(!0);
+        unsafe {
+            for z in -1..depth + 1 {
+                for y in -1..height + 1 {
+                    for x in -1..width {
+                        let inside = (0..height).contains(&y) && (0..depth).contains(&z);
+                        if x == -1 || !inside {
+                            this.set_unchecked(x, y, z, true);
+                        }
+                    }
+                }
+            }
+            this.set_unchecked(width, height, depth, true);
+        }
+
+        this
+    }
+
+    #[inline(always)]
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    #[inline(always)]
+    pub fn depth(&self) -> i32 {
+        self.depth
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    pub fn get(&self, x: i32, y: i32, z: i32) -> bool {
+        self.padded_bounds_check(x, y, z);
+        unsafe { self.get_unchecked(x, y, z) }
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    pub fn set(&mut self, x: i32, y: i32, z: i32, v: bool) {
+        self.unpadded_bounds_check(x, y, z);
+        unsafe { self.set_unchecked(x, y, z, v) }
+    }
+
+    /// Note: returns 57 tiles of information. The top 7 bits are always 0.
+    #[track_caller]
+    #[inline(always)]
+    pub fn get_row(&self, x: i32, y: i32, z: i32) -> u64 {
+        self.padded_bounds_check(x, y, z);
+        unsafe { self.get_row_unchecked(x, y, z) }
+    }
+
+    /// Note: returns 57 tiles of information. The bottom 7 bits are always 0.
+    #[track_caller]
+    #[inline(always)]
+    pub fn get_row_upper(&self, x: i32, y: i32, z: i32) -> u64 {
+        self.padded_bounds_check(x, y, z);
+        unsafe { self.get_row_upper_unchecked(x, y, z) }
+    }
+
+    /// Returns the 3x3x3 block of cells centered on `(x, y, z)`. The cell at offset
+    /// `(dx, dy, dz)` is bit `(dx + 1) + 3 * (dy + 1) + 9 * (dz + 1)`.
+    #[track_caller]
+    #[inline(always)]
+    pub fn get_neighbors(&self, x: i32, y: i32, z: i32) -> u32 {
+        self.unpadded_bounds_check(x, y, z);
+        unsafe { self.get_neighbors_unchecked(x, y, z) }
+    }
+
+    /// SAFETY: `x` must be in `-1..width+1`, `y` must be in `-1..height+1`, `z` must be in
+    /// `-1..depth+1`. Padding bits can be relied upon to yield `true`.
+    #[inline(always)]
+    pub unsafe fn get_unchecked(&self, x: i32, y: i32, z: i32) -> bool {
+        let (idx, bit) = self.locate(x, y, z);
+        self.cells.get_unchecked(idx) & 1 << bit != 0
+    }
+
+    /// SAFETY: `x` must be in `-1..width+1`, `y` must be in `-1..height+1`, `z` must be in
+    /// `-1..depth+1`. Setting padding bits to `false` breaks the guarantees of the other methods.
+    #[inline(always)]
+    pub unsafe fn set_unchecked(&mut self, x: i32, y: i32, z: i32, v: bool) {
+        let (idx, bit) = self.locate(x, y, z);
+        if v {
+            *self.cells.get_unchecked_mut(idx) |= 1 << bit;
+        } else {
+            *self.cells.get_unchecked_mut(idx) &= !(1 << bit);
+        }
+    }
+
+    /// SAFETY: `x` must be in `-1..width+1`, `y` must be in `-1..height+1`, `z` must be in
+    /// `-1..depth+1`. Padding bits can be relied upon to yield `true`.
+    ///
+    /// Note: returns 57 tiles of information. The top 7 bits are always 0.
+    #[inline(always)]
+    pub unsafe fn get_row_unchecked(&self, x: i32, y: i32, z: i32) -> u64 {
+        let (idx, bit) = self.locate(x, y, z);
+        let ptr: *const u8 = self.cells.get_unchecked(idx);
+        let w = (ptr as *const u64).read_unaligned().to_le();
+        (w >> bit) & (1 << 57) - 1
+    }
+
+    /// SAFETY: `x` must be in `-1..width+1`, `y` must be in `-1..height+1`, `z` must be in
+    /// `-1..depth+1`. Padding bits can be relied upon to yield `true`.
+    ///
+    /// Note: returns 57 tiles of information. The bottom 7 bits are always 0.
+    #[inline(always)]
+    pub unsafe fn get_row_upper_unchecked(&self, x: i32, y: i32, z: i32) -> u64 {
+        let (idx, bit) = self.locate(x, y, z);
+        let ptr: *const u8 = self.cells.get_unchecked(idx - 7);
+        let w = (ptr as *const u64).read_unaligned().to_le();
+        (w << 7 - bit) & !0 << 7
+    }
+
+    /// SAFETY: `x` must be in `0..width`, `y` must be in `0..height`, `z` must be in `0..depth`
+    #[inline(always)]
+    pub unsafe fn get_neighbors_unchecked(&self, x: i32, y: i32, z: i32) -> u32 {
+        let mut bits = 0;
+        for dz in 0..3 {
+            for dy in 0..3 {
+                let row = self.get_row_unchecked(x - 1, y + dy - 1, z + dz - 1) & 0b111;
+                bits |= (row as u32) << (3 * dy + 9 * dz);
+            }
+        }
+        bits
+    }
+
+    #[inline(always)]
+    fn locate(&self, x: i32, y: i32, z: i32) -> (usize, usize) {
+        #[cfg(debug_assertions)]
+        self.padded_bounds_check(x, y, z);
+
+        let padded_z = (z + 1) as usize;
+        let padded_height = self.height as usize + 2;
+        let padded_y = (y + 1) as usize;
+        let padded_width = self.width as usize + 1;
+        let padded_x = (x + 1) as usize;
+        let id = (padded_z * padded_height + padded_y) * padded_width + padded_x;
+
+        debug_assert!(
+            id < padded_width * padded_height * (self.depth as usize + 2) + 1,
+            "bit index out of range"
+        );
+
+        (id / u8::BITS as usize + 8, id % u8::BITS as usize)
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    fn padded_bounds_check(&self, x: i32, y: i32, z: i32) {
+        if !(-1..self.width + 1).contains(&x)
+            || !(-1..self.height + 1).contains(&y)
+            || !(-1..self.depth + 1).contains(&z)
+        {
+            panic!("Voxel ({}, {}, {}) is out of bounds.", x, y, z);
+        }
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    fn unpadded_bounds_check(&self, x: i32, y: i32, z: i32) {
+        if !(0..self.width).contains(&x)
+            || !(0..self.height).contains(&y)
+            || !(0..self.depth).contains(&z)
+        {
+            panic!("Voxel ({}, {}, {}) is out of bounds.", x, y, z);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde {
+    use serde::de::{Error, Visitor};
+    use serde::ser::SerializeSeq;
+    use serde::{Deserialize, Serialize};
+
+    impl Serialize for super::VoxelGrid {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            let cells = self.width as usize * self.height as usize * self.depth as usize;
+            let mut s = serializer.serialize_seq(Some(cells + 3))?;
+            s.serialize_element(&self.width)?;
+            s.serialize_element(&self.height)?;
+            s.serialize_element(&self.depth)?;
+            for z in 0..self.depth {
+                for y in 0..self.height {
+                    for x in 0..self.width {
+                        s.serialize_element(&self.get(x, y, z))?;
+                    }
+                }
+            }
+            s.end()
+        }
+    }
+
+    impl<'de> Deserialize<'de> for super::VoxelGrid {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            deserializer.deserialize_seq(VoxelGridVisitor)
+        }
+    }
+
+    struct VoxelGridVisitor;
+    impl<'de> Visitor<'de> for VoxelGridVisitor {
+        type Value = super::VoxelGrid;
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::SeqAccess<'de>,
+        {
+            let width: i32 = seq
+                .next_element()?
+                .ok_or_else(|| Error::invalid_length(0, &self))?;
+            let height: i32 = seq
+                .next_element()?
+                .ok_or_else(|| Error::invalid_length(1, &self))?;
+            let depth: i32 = seq
+                .next_element()?
+                .ok_or_else(|| Error::invalid_length(2, &self))?;
+
+            let mut grid = super::VoxelGrid::new(width, height, depth);
+            let mut i = 3;
+            for z in 0..depth {
+                for y in 0..height {
+                    for x in 0..width {
+                        grid.set(
+                            x,
+                            y,
+                            z,
+                            seq.next_element()?
+                                .ok_or_else(|| Error::invalid_length(i, &self))?,
+                        );
+                        i += 1;
+                    }
+                }
+            }
+
+            Ok(grid)
+        }
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(formatter, "a sequence of values")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    #[test]
+    fn check_padding() {
+        let grid = VoxelGrid::new(67, 5, 4);
+        for z in -1..grid.depth() + 1 {
+            for y in -1..grid.height() + 1 {
+                for x in -1..grid.width() + 1 {
+                    let inside = (0..grid.width()).contains(&x)
+                        && (0..grid.height()).contains(&y)
+                        && (0..grid.depth()).contains(&z);
+                    assert_eq!(grid.get(x, y, z), !inside);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn check_random() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let (w, h, d) = (71, 6, 5);
+        let canonical: Vec<bool> = (0..w * h * d).map(|_| rng.gen()).collect();
+        let mut grid = VoxelGrid::new(w, h, d);
+        let at = |x: i32, y: i32, z: i32| canonical[(x + w * (y + h * z)) as usize];
+        for z in 0..d {
+            for y in 0..h {
+                for x in 0..w {
+                    grid.set(x, y, z, at(x, y, z));
+                }
+            }
+        }
+        for z in 0..d {
+            for y in 0..h {
+                for x in 0..w {
+                    assert_eq!(grid.get(x, y, z), at(x, y, z));
+                    let r = grid.get_row(x, y, z);
+                    for i in 0..57 {
+                        let v = if x + i < w { at(x + i, y, z) } else { true };
+                        assert_eq!(r & 1 << i != 0, v);
+                        if x + i >= w {
+                            break;
+                        }
+                    }
+                    let r = grid.get_row_upper(x, y, z);
+                    for i in (7..64).rev() {
+                        let v = if x + i >= 63 {
+                            at(x + i - 63, y, z)
+                        } else {
+                            true
+                        };
+                        assert_eq!(r & 1 << i != 0, v);
+                        if x + i < 63 {
+                            break;
+                        }
+                    }
+                    let nbs = grid.get_neighbors(x, y, z);
+                    for i in 0..27 {
+                        let (dx, dy, dz) = (i % 3 - 1, i / 3 % 3 - 1, i / 9 - 1);
+                        assert_eq!(nbs & 1 << i != 0, grid.get(x + dx, y + dy, z + dz));
+                    }
+                }
+            }
+        }
+    }
+}
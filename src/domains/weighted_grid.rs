@@ -75,7 +75,7 @@ impl<V> WeightedGrid<V> {
         self.unpadded_bounds_check(x, y);
 
         let idx = self.locate(x, y);
-        let padded_width = self.width as usize - 1;
+        let padded_width = self.width as usize + 1;
         Neighborhood {
             nw: self.cells.get_unchecked(idx - padded_width - 1).as_ref(),
             n: self.cells.get_unchecked(idx - padded_width).as_ref(),
@@ -189,3 +189,39 @@ mod serde {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_neighborhood() {
+        let mut grid = WeightedGrid::new(5, 4);
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                if (x + y) % 4 != 0 {
+                    *grid.get_mut(x, y) = Some(x + y * grid.width());
+                }
+            }
+        }
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                let n = grid.get_neighborhood(x, y);
+                let cells = [
+                    (n.nw, -1, -1),
+                    (n.n, 0, -1),
+                    (n.ne, 1, -1),
+                    (n.w, -1, 0),
+                    (n.c, 0, 0),
+                    (n.e, 1, 0),
+                    (n.sw, -1, 1),
+                    (n.s, 0, 1),
+                    (n.se, 1, 1),
+                ];
+                for &(cell, dx, dy) in &cells {
+                    assert_eq!(cell, grid.get(x + dx, y + dy));
+                }
+            }
+        }
+    }
+}
@@ -1,2 +1,3 @@
 pub mod no_corner_cutting;
-pub mod jps;
\ No newline at end of file
+pub mod jps;
+pub mod size_aware;
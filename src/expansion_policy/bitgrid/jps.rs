@@ -8,13 +8,7 @@ use crate::util::{Direction, GridDomain};
 use crate::{astar_unchecked, Edge, ExpansionPolicy, Owner, SearchNode};
 
 pub fn create_tmap(map: &BitGrid) -> BitGrid {
-    let mut tmap = BitGrid::new(map.height(), map.width());
-    for x in 0..map.width() {
-        for y in 0..map.height() {
-            tmap.set(y, x, map.get(x, y));
-        }
-    }
-    tmap
+    map.transpose()
 }
 
 pub struct JpsExpansionPolicy<'a> {
@@ -382,7 +376,7 @@ unsafe fn jump_southeast_unchecked(
     }
 }
 
-fn get_direction((x, y): (i32, i32), parent: Option<(i32, i32)>) -> Option<Direction> {
+pub(crate) fn get_direction((x, y): (i32, i32), parent: Option<(i32, i32)>) -> Option<Direction> {
     parent.map(|(px, py)| match y.cmp(&py) {
         std::cmp::Ordering::Less => match x.cmp(&px) {
             std::cmp::Ordering::Less => Direction::NorthWest,
@@ -402,7 +396,7 @@ fn get_direction((x, y): (i32, i32), parent: Option<(i32, i32)>) -> Option<Direc
     })
 }
 
-fn canonical_successors(
+pub(crate) fn canonical_successors(
     map: &BitGrid,
     (x, y): (i32, i32),
     dir: Option<Direction>,
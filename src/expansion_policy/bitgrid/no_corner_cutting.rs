@@ -1,5 +1,7 @@
 use std::f64::consts::SQRT_2;
 
+use enumset::EnumSet;
+
 use crate::domains::BitGrid;
 use crate::util::{Direction, GridDomain};
 use crate::{Edge, ExpansionPolicy, SearchNode};
@@ -38,53 +40,63 @@ impl ExpansionPolicy<(i32, i32)> for NoCornerCutting<'_> {
     ) {
         let &mut Self(map) = self;
         let nbs = map.get_neighbors_unchecked(node.id.0, node.id.1);
-        if nbs.is_disjoint(Direction::North.into()) {
-            edges.push(Edge {
-                destination: (node.id.0, node.id.1 - 1),
-                cost: 1.0,
-            });
-        }
-        if nbs.is_disjoint(Direction::South.into()) {
-            edges.push(Edge {
-                destination: (node.id.0, node.id.1 + 1),
-                cost: 1.0,
-            });
-        }
-        if nbs.is_disjoint(Direction::West.into()) {
-            edges.push(Edge {
-                destination: (node.id.0 - 1, node.id.1),
-                cost: 1.0,
-            });
-        }
-        if nbs.is_disjoint(Direction::East.into()) {
-            edges.push(Edge {
-                destination: (node.id.0 + 1, node.id.1),
-                cost: 1.0,
-            });
-        }
-        if nbs.is_disjoint(Direction::North | Direction::West | Direction::NorthWest) {
-            edges.push(Edge {
-                destination: (node.id.0 - 1, node.id.1 - 1),
-                cost: SQRT_2,
-            });
-        }
-        if nbs.is_disjoint(Direction::North | Direction::East | Direction::NorthEast) {
-            edges.push(Edge {
-                destination: (node.id.0 + 1, node.id.1 - 1),
-                cost: SQRT_2,
-            });
-        }
-        if nbs.is_disjoint(Direction::South | Direction::West | Direction::SouthWest) {
-            edges.push(Edge {
-                destination: (node.id.0 - 1, node.id.1 + 1),
-                cost: SQRT_2,
-            });
-        }
-        if nbs.is_disjoint(Direction::South | Direction::East | Direction::SouthEast) {
-            edges.push(Edge {
-                destination: (node.id.0 + 1, node.id.1 + 1),
-                cost: SQRT_2,
-            });
-        }
+        push_edges(node.id, nbs, edges);
+    }
+}
+
+/// Pushes the edges to the unobstructed neighbors of the cell, without cutting corners.
+#[inline(always)]
+pub(crate) fn push_edges(
+    (x, y): (i32, i32),
+    nbs: EnumSet<Direction>,
+    edges: &mut Vec<Edge<(i32, i32)>>,
+) {
+    if nbs.is_disjoint(Direction::North.into()) {
+        edges.push(Edge {
+            destination: (x, y - 1),
+            cost: 1.0,
+        });
+    }
+    if nbs.is_disjoint(Direction::South.into()) {
+        edges.push(Edge {
+            destination: (x, y + 1),
+            cost: 1.0,
+        });
+    }
+    if nbs.is_disjoint(Direction::West.into()) {
+        edges.push(Edge {
+            destination: (x - 1, y),
+            cost: 1.0,
+        });
+    }
+    if nbs.is_disjoint(Direction::East.into()) {
+        edges.push(Edge {
+            destination: (x + 1, y),
+            cost: 1.0,
+        });
+    }
+    if nbs.is_disjoint(Direction::North | Direction::West | Direction::NorthWest) {
+        edges.push(Edge {
+            destination: (x - 1, y - 1),
+            cost: SQRT_2,
+        });
+    }
+    if nbs.is_disjoint(Direction::North | Direction::East | Direction::NorthEast) {
+        edges.push(Edge {
+            destination: (x + 1, y - 1),
+            cost: SQRT_2,
+        });
+    }
+    if nbs.is_disjoint(Direction::South | Direction::West | Direction::SouthWest) {
+        edges.push(Edge {
+            destination: (x - 1, y + 1),
+            cost: SQRT_2,
+        });
+    }
+    if nbs.is_disjoint(Direction::South | Direction::East | Direction::SouthEast) {
+        edges.push(Edge {
+            destination: (x + 1, y + 1),
+            cost: SQRT_2,
+        });
     }
 }
@@ -0,0 +1,162 @@
+use std::f64::consts::SQRT_2;
+
+use crate::domains::ClearanceMap;
+use crate::util::GridDomain;
+use crate::{Edge, ExpansionPolicy, SearchNode};
+
+/// Like `NoCornerCutting`, but for agents occupying a `size` by `size` square of cells, located
+/// by their top-left cell. Cells are traversable if they have at least `size` clearance.
+pub struct SizeAware<'a> {
+    map: &'a ClearanceMap,
+    size: u16,
+}
+
+impl SizeAware<'_> {
+    pub fn new(map: &ClearanceMap, size: u16) -> SizeAware<'_> {
+        assert!(size > 0, "agent size must be positive");
+        SizeAware { map, size }
+    }
+
+    pub fn size(&self) -> u16 {
+        self.size
+    }
+}
+
+unsafe impl GridDomain for SizeAware<'_> {
+    fn width(&self) -> i32 {
+        self.map.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.map.height()
+    }
+}
+
+impl ExpansionPolicy<(i32, i32)> for SizeAware<'_> {
+    fn expand(&mut self, node: &SearchNode<(i32, i32)>, edges: &mut Vec<Edge<(i32, i32)>>) {
+        let (x, y) = node.id;
+        if !(0..self.map.width()).contains(&x) || !(0..self.map.height()).contains(&y) {
+            panic!("Grid cell ({}, {}) is out of bounds.", x, y);
+        }
+        unsafe {
+            // SAFETY: bounds checked above
+            self.expand_unchecked(node, edges)
+        }
+    }
+
+    unsafe fn expand_unchecked(
+        &mut self,
+        node: &SearchNode<(i32, i32)>,
+        edges: &mut Vec<Edge<(i32, i32)>>,
+    ) {
+        let (x, y) = node.id;
+        // SAFETY: neighbors of in-bounds cells are either in-bounds or padding, and padding cells
+        //         have no clearance.
+        let fits = |dx: i32, dy: i32| self.map.get_unchecked(x + dx, y + dy) >= self.size;
+        if !fits(0, 0) {
+            return;
+        }
+        let (n, s, w, e) = (fits(0, -1), fits(0, 1), fits(-1, 0), fits(1, 0));
+        for &(ok, dx, dy) in &[(n, 0, -1), (s, 0, 1), (w, -1, 0), (e, 1, 0)] {
+            if ok {
+                edges.push(Edge {
+                    destination: (x + dx, y + dy),
+                    cost: 1.0,
+                });
+            }
+        }
+        let diagonals = [
+            (n && w, -1, -1),
+            (n && e, 1, -1),
+            (s && w, -1, 1),
+            (s && e, 1, 1),
+        ];
+        for &(ok, dx, dy) in &diagonals {
+            if ok && fits(dx, dy) {
+                edges.push(Edge {
+                    destination: (x + dx, y + dy),
+                    cost: SQRT_2,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::random_grid;
+    use crate::expansion_policy::bitgrid::jps::{create_tmap, JpsExpansionPolicy};
+    use crate::expansion_policy::bitgrid::no_corner_cutting::NoCornerCutting;
+    use crate::incremental::SymmetricGrid;
+    use crate::node_pool::{GridPool, NodePool};
+    use crate::util::{assert_same_cost, grid_search, octile_heuristic};
+    use crate::Owner;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    #[test]
+    fn check_matches_inflated() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let map = random_grid(&mut rng, 40, 30, 0.05);
+        let clearance = ClearanceMap::new(&map);
+        let mut pool = GridPool::new(map.width(), map.height());
+        let mut owner = Owner::new();
+
+        for size in 1..4 {
+            let inflated = clearance.inflate(size);
+            let tmap = create_tmap(&inflated);
+            let mut sorted_edges = |ep: &mut dyn ExpansionPolicy<(i32, i32)>, x, y| {
+                let node = pool.generate((x, y), &mut owner);
+                let mut edges = vec![];
+                ep.expand(owner.ro(node), &mut edges);
+                let mut edges: Vec<_> = edges.iter().map(|e| (e.destination, e.cost)).collect();
+                edges.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                edges
+            };
+            for y in 0..map.height() {
+                for x in 0..map.width() {
+                    assert_eq!(
+                        sorted_edges(&mut SizeAware::new(&clearance, size), x, y),
+                        sorted_edges(
+                            &mut SymmetricGrid::new(&inflated, NoCornerCutting::new(&inflated)),
+                            x,
+                            y
+                        ),
+                    );
+                }
+            }
+
+            for _ in 0..20 {
+                let mut cell = || {
+                    (
+                        rng.gen_range(0..map.width()),
+                        rng.gen_range(0..map.height()),
+                    )
+                };
+                let (from, to) = (cell(), cell());
+                if inflated.get(from.0, from.1) || inflated.get(to.0, to.1) {
+                    continue;
+                }
+                grid_search(
+                    &mut pool,
+                    &mut owner,
+                    &mut SizeAware::new(&clearance, size),
+                    octile_heuristic(to, 1.0),
+                    from,
+                    to,
+                );
+                let expected = pool.get(to.0, to.1, &owner).map(|n| owner.ro(n).g);
+                JpsExpansionPolicy::new(&inflated, &tmap).search(
+                    &mut pool,
+                    &mut owner,
+                    octile_heuristic(to, 1.0),
+                    from,
+                    to,
+                );
+                let actual = pool.get(to.0, to.1, &owner).map(|n| owner.ro(n).g);
+                assert_same_cost(actual, expected);
+            }
+        }
+    }
+}
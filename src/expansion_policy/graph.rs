@@ -1,4 +1,4 @@
-use crate::domains::DirectedGraph;
+use crate::domains::{CsrGraph, CsrIndex, DirectedGraph, UndirectedGraph};
 use crate::util::IndexDomain;
 
 use super::ExpansionPolicy;
@@ -68,3 +68,94 @@ unsafe impl<V> IndexDomain for IncomingEdges<'_, V> {
         self.0.len()
     }
 }
+
+pub struct CsrOutgoingEdges<'a, I>(&'a CsrGraph<I>);
+
+impl<'a, I> CsrOutgoingEdges<'a, I> {
+    pub fn new(graph: &'a CsrGraph<I>) -> Self {
+        CsrOutgoingEdges(graph)
+    }
+}
+
+impl<I: CsrIndex> ExpansionPolicy<usize> for CsrOutgoingEdges<'_, I> {
+    unsafe fn expand_unchecked(
+        &mut self,
+        node: &crate::SearchNode<usize>,
+        edges: &mut Vec<crate::Edge<usize>>,
+    ) {
+        edges.extend(self.0.outgoing_edges_unchecked(node.id));
+    }
+
+    fn expand(&mut self, node: &crate::SearchNode<usize>, edges: &mut Vec<crate::Edge<usize>>) {
+        edges.extend(self.0.outgoing_edges(node.id));
+    }
+}
+
+// SAFETY: CsrGraph validates its edges when built or loaded, so all edges are in-bounds.
+unsafe impl<I: CsrIndex> IndexDomain for CsrOutgoingEdges<'_, I> {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+pub struct CsrIncomingEdges<'a, I>(&'a CsrGraph<I>);
+
+impl<'a, I> CsrIncomingEdges<'a, I> {
+    pub fn new(graph: &'a CsrGraph<I>) -> Self {
+        CsrIncomingEdges(graph)
+    }
+}
+
+impl<I: CsrIndex> ExpansionPolicy<usize> for CsrIncomingEdges<'_, I> {
+    unsafe fn expand_unchecked(
+        &mut self,
+        node: &crate::SearchNode<usize>,
+        edges: &mut Vec<crate::Edge<usize>>,
+    ) {
+        edges.extend(self.0.incoming_edges_unchecked(node.id));
+    }
+
+    fn expand(&mut self, node: &crate::SearchNode<usize>, edges: &mut Vec<crate::Edge<usize>>) {
+        edges.extend(self.0.incoming_edges(node.id));
+    }
+}
+
+// SAFETY: CsrGraph validates its edges when built or loaded, so all edges are in-bounds.
+unsafe impl<I: CsrIndex> IndexDomain for CsrIncomingEdges<'_, I> {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+pub struct UndirectedEdges<'a, V>(&'a UndirectedGraph<V>);
+
+impl<'a, V> UndirectedEdges<'a, V> {
+    pub fn new(graph: &'a UndirectedGraph<V>) -> Self {
+        UndirectedEdges(graph)
+    }
+}
+
+impl<V> ExpansionPolicy<usize> for UndirectedEdges<'_, V> {
+    unsafe fn expand_unchecked(
+        &mut self,
+        node: &crate::SearchNode<usize>,
+        edges: &mut Vec<crate::Edge<usize>>,
+    ) {
+        edges.extend(self.0.edges_of_unchecked(node.id));
+    }
+
+    fn expand(&mut self, node: &crate::SearchNode<usize>, edges: &mut Vec<crate::Edge<usize>>) {
+        assert!(node.id < self.0.len());
+        unsafe {
+            // SAFETY: Bounds checked above
+            self.expand_unchecked(node, edges)
+        }
+    }
+}
+
+// SAFETY: UndirectedGraph always contains valid edges, so all edges are in-bounds.
+unsafe impl<V> IndexDomain for UndirectedEdges<'_, V> {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
@@ -0,0 +1,258 @@
+use crate::domains::{HexBitGrid, WeightedHexGrid};
+use crate::util::{Cost, HexDomain, HexLayout, HEX_NEIGHBORS};
+use crate::{Edge, ExpansionPolicy, SearchNode};
+
+/// Moves between adjacent unobstructed hex cells, each costing 1.
+pub struct HexNeighbors<'a>(&'a HexBitGrid);
+
+impl HexNeighbors<'_> {
+    pub fn new(map: &HexBitGrid) -> HexNeighbors {
+        HexNeighbors(map)
+    }
+}
+
+unsafe impl HexDomain for HexNeighbors<'_> {
+    fn width(&self) -> i32 {
+        self.0.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.0.height()
+    }
+
+    fn layout(&self) -> HexLayout {
+        self.0.layout()
+    }
+}
+
+impl ExpansionPolicy<(i32, i32)> for HexNeighbors<'_> {
+    fn expand(&mut self, node: &SearchNode<(i32, i32)>, edges: &mut Vec<Edge<(i32, i32)>>) {
+        if !self.0.in_bounds(node.id.0, node.id.1) {
+            panic!("Hex cell ({}, {}) is out of bounds.", node.id.0, node.id.1);
+        }
+        unsafe {
+            // SAFETY: bounds are checked above
+            self.expand_unchecked(node, edges)
+        }
+    }
+
+    unsafe fn expand_unchecked(
+        &mut self,
+        node: &SearchNode<(i32, i32)>,
+        edges: &mut Vec<Edge<(i32, i32)>>,
+    ) {
+        let &mut HexNeighbors(map) = self;
+        let (q, r) = node.id;
+        if map.get_unchecked(q, r) {
+            return;
+        }
+        for &(dq, dr) in &HEX_NEIGHBORS {
+            // SAFETY: neighbors of in-bounds cells are either in-bounds or padding, and padding
+            //         cells are obstructed.
+            if !map.get_unchecked(q + dq, r + dr) {
+                edges.push(Edge {
+                    destination: (q + dq, r + dr),
+                    cost: 1.0,
+                });
+            }
+        }
+    }
+}
+
+/// Moves between adjacent traversable hex cells, costing the average of the costs of both cells.
+pub struct WeightedHexNeighbors<'a, T>(&'a WeightedHexGrid<T>);
+
+impl<T> WeightedHexNeighbors<'_, T> {
+    pub fn new(map: &WeightedHexGrid<T>) -> WeightedHexNeighbors<T> {
+        WeightedHexNeighbors(map)
+    }
+}
+
+unsafe impl<T> HexDomain for WeightedHexNeighbors<'_, T> {
+    fn width(&self) -> i32 {
+        self.0.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.0.height()
+    }
+
+    fn layout(&self) -> HexLayout {
+        self.0.layout()
+    }
+}
+
+impl<T: Cost> ExpansionPolicy<(i32, i32)> for WeightedHexNeighbors<'_, T> {
+    fn expand(&mut self, node: &SearchNode<(i32, i32)>, edges: &mut Vec<Edge<(i32, i32)>>) {
+        if !self.0.in_bounds(node.id.0, node.id.1) {
+            panic!("Hex cell ({}, {}) is out of bounds.", node.id.0, node.id.1);
+        }
+        unsafe {
+            // SAFETY: bounds are checked above
+            self.expand_unchecked(node, edges)
+        }
+    }
+
+    unsafe fn expand_unchecked(
+        &mut self,
+        node: &SearchNode<(i32, i32)>,
+        edges: &mut Vec<Edge<(i32, i32)>>,
+    ) {
+        let &mut WeightedHexNeighbors(map) = self;
+        let (q, r) = node.id;
+        let c = match map.get_unchecked(q, r) {
+            Some(c) => c.cost(),
+            None => return,
+        };
+        for &(dq, dr) in &HEX_NEIGHBORS {
+            // SAFETY: neighbors of in-bounds cells are either in-bounds or padding, and padding
+            //         cells are obstructed.
+            if let Some(cost) = map.get_unchecked(q + dq, r + dr).map(Cost::cost) {
+                edges.push(Edge {
+                    destination: (q + dq, r + dr),
+                    cost: (c + cost) / 2.0,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_pool::HexPool;
+    use crate::util::{assert_same_cost, hex_heuristic, hex_search, zero_heuristic};
+    use crate::Owner;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+    use std::collections::VecDeque;
+
+    /// The offset-coordinate neighbors of a cell, computed independently of the axial conversion.
+    fn offset_neighbors(layout: HexLayout, (x, y): (i32, i32)) -> [(i32, i32); 6] {
+        let shoved = match layout {
+            HexLayout::Axial => unreachable!(),
+            HexLayout::OddRows => y & 1 == 1,
+            HexLayout::EvenRows => y & 1 == 0,
+        };
+        let d = if shoved { 0 } else { -1 };
+        [
+            (x + 1, y),
+            (x - 1, y),
+            (x + d, y - 1),
+            (x + d + 1, y - 1),
+            (x + d, y + 1),
+            (x + d + 1, y + 1),
+        ]
+    }
+
+    fn bfs(map: &HexBitGrid, from: (i32, i32), to: (i32, i32)) -> Option<usize> {
+        let layout = map.layout();
+        let (w, h) = (map.width(), map.height());
+        let mut dist = vec![None; (w * h) as usize];
+        let mut queue = VecDeque::new();
+        dist[(from.0 + from.1 * w) as usize] = Some(0);
+        queue.push_back(from);
+        while let Some(c) = queue.pop_front() {
+            let d = dist[(c.0 + c.1 * w) as usize].unwrap();
+            for &(x, y) in &offset_neighbors(layout, c) {
+                if !(0..w).contains(&x) || !(0..h).contains(&y) {
+                    continue;
+                }
+                let (q, r) = layout.from_offset((x, y));
+                if map.get(q, r) || dist[(x + y * w) as usize].is_some() {
+                    continue;
+                }
+                dist[(x + y * w) as usize] = Some(d + 1);
+                queue.push_back((x, y));
+            }
+        }
+        dist[(to.0 + to.1 * w) as usize]
+    }
+
+    #[test]
+    fn check_offset_layouts() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        for &layout in &[HexLayout::OddRows, HexLayout::EvenRows] {
+            let mut map = HexBitGrid::new(17, 11, layout);
+            for y in 0..map.height() {
+                for x in 0..map.width() {
+                    let (q, r) = layout.from_offset((x, y));
+                    assert_eq!(layout.to_offset((q, r)), (x, y));
+                    map.set(q, r, rng.gen_bool(0.25));
+                }
+            }
+            let mut pool = HexPool::new(map.width(), map.height(), layout);
+            let mut owner = Owner::new();
+            for _ in 0..100 {
+                let from = (
+                    rng.gen_range(0..map.width()),
+                    rng.gen_range(0..map.height()),
+                );
+                let to = (
+                    rng.gen_range(0..map.width()),
+                    rng.gen_range(0..map.height()),
+                );
+                let (source, goal) = (layout.from_offset(from), layout.from_offset(to));
+                if map.get(source.0, source.1) {
+                    continue;
+                }
+                hex_search(
+                    &mut pool,
+                    &mut owner,
+                    &mut HexNeighbors::new(&map),
+                    hex_heuristic(goal, 1.0),
+                    source,
+                    goal,
+                );
+                let g = pool
+                    .get(goal.0, goal.1, &owner)
+                    .map(|n| owner.ro(n).g)
+                    .filter(|g| g.is_finite());
+                assert_eq!(g, bfs(&map, from, to).map(|d| d as f64));
+            }
+        }
+    }
+
+    #[test]
+    fn check_weighted() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        for &layout in &[HexLayout::Axial, HexLayout::OddRows, HexLayout::EvenRows] {
+            let mut map = WeightedHexGrid::new(13, 9, layout);
+            for y in 0..map.height() {
+                for x in 0..map.width() {
+                    let (q, r) = layout.from_offset((x, y));
+                    if rng.gen_bool(0.8) {
+                        *map.get_mut(q, r) = Some(rng.gen_range(1..5u8));
+                    }
+                }
+            }
+            let mut pool = HexPool::new(map.width(), map.height(), layout);
+            let mut owner = Owner::new();
+            for _ in 0..50 {
+                let from = (
+                    rng.gen_range(0..map.width()),
+                    rng.gen_range(0..map.height()),
+                );
+                let to = (
+                    rng.gen_range(0..map.width()),
+                    rng.gen_range(0..map.height()),
+                );
+                let (source, goal) = (layout.from_offset(from), layout.from_offset(to));
+                let mut search = |h: &dyn Fn((i32, i32)) -> f64| {
+                    hex_search(
+                        &mut pool,
+                        &mut owner,
+                        &mut WeightedHexNeighbors::new(&map),
+                        h,
+                        source,
+                        goal,
+                    );
+                    pool.get(goal.0, goal.1, &owner).map(|n| owner.ro(n).g)
+                };
+                let expected = search(&zero_heuristic());
+                let actual = search(&hex_heuristic(goal, 1.0));
+                assert_same_cost(actual, expected);
+            }
+        }
+    }
+}
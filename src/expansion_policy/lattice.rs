@@ -0,0 +1,404 @@
+use std::f64::consts::SQRT_2;
+
+use crate::domains::BitGrid;
+use crate::node_pool::VoxelPool;
+use crate::util::{voxel_search, zero_heuristic, VoxelDomain};
+use crate::{Edge, ExpansionPolicy, Owner, SearchNode};
+
+/// A move of a vehicle from a state with heading `start_heading` to the state `offset` away with
+/// heading `end_heading`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MotionPrimitive {
+    pub start_heading: i32,
+    pub end_heading: i32,
+    pub offset: (i32, i32),
+    /// The cells the vehicle passes through, relative to the start cell. These must all be
+    /// unobstructed to make the move.
+    pub swept: Vec<(i32, i32)>,
+    pub cost: f64,
+}
+
+/// The 8 headings of [`StateLattice::with_default_primitives`], as unit steps. Heading 0 faces east,
+/// and headings increase clockwise, since y increases southwards.
+pub const HEADINGS: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// A state lattice over a `BitGrid`, with vertex IDs `(x, y, heading)`. Vehicles move between
+/// states using motion primitives, and may only start in unobstructed cells.
+///
+/// Use with a `VoxelPool` with a depth of `headings`.
+pub struct StateLattice<'a> {
+    map: &'a BitGrid,
+    headings: i32,
+    turn_penalty: f64,
+    primitives: Vec<Vec<MotionPrimitive>>,
+}
+
+impl StateLattice<'_> {
+    /// Constructs a state lattice without any motion primitives.
+    pub fn new(map: &BitGrid, headings: i32) -> StateLattice<'_> {
+        assert!(headings > 0, "there must be at least one heading");
+        StateLattice {
+            map,
+            headings,
+            turn_penalty: 0.0,
+            primitives: vec![vec![]; headings as usize],
+        }
+    }
+
+    /// Constructs a state lattice with the 8 headings of [`HEADINGS`]. From each heading, vehicles
+    /// can step forward, or step forward and then step forward again after turning 45 degrees.
+    /// Diagonal steps may not cut corners.
+    pub fn with_default_primitives(map: &BitGrid) -> StateLattice<'_> {
+        let mut lattice = StateLattice::new(map, 8);
+        for h in 0..8 {
+            lattice.add_primitive(primitive_from_steps(&[h]));
+            lattice.add_primitive(primitive_from_steps(&[h, (h + 1) % 8]));
+            lattice.add_primitive(primitive_from_steps(&[h, (h + 7) % 8]));
+        }
+        lattice
+    }
+
+    /// Adds a motion primitive. The destination cell is added to the swept cells if missing.
+    #[track_caller]
+    pub fn add_primitive(&mut self, mut primitive: MotionPrimitive) {
+        assert!(
+            (0..self.headings).contains(&primitive.start_heading)
+                && (0..self.headings).contains(&primitive.end_heading),
+            "primitive headings must be less than the number of headings"
+        );
+        assert!(
+            primitive.cost >= 0.0,
+            "primitive costs must be non-negative"
+        );
+        if !primitive.swept.contains(&primitive.offset) {
+            primitive.swept.push(primitive.offset);
+        }
+        self.primitives[primitive.start_heading as usize].push(primitive);
+    }
+
+    pub fn primitives(&self, heading: i32) -> &[MotionPrimitive] {
+        &self.primitives[heading as usize]
+    }
+
+    pub fn headings(&self) -> i32 {
+        self.headings
+    }
+
+    /// Sets the cost added to primitives per heading step they turn, in either direction.
+    pub fn set_turn_penalty(&mut self, penalty: f64) {
+        assert!(penalty >= 0.0, "turn penalty must be non-negative");
+        self.turn_penalty = penalty;
+    }
+
+    pub fn turn_penalty(&self) -> f64 {
+        self.turn_penalty
+    }
+
+    /// The cost of a primitive, including its turn penalty.
+    pub fn cost(&self, primitive: &MotionPrimitive) -> f64 {
+        let d = (primitive.start_heading - primitive.end_heading).abs();
+        let turns = d.min(self.headings - d);
+        primitive.cost + self.turn_penalty * turns as f64
+    }
+
+    /// Precomputes the costs of moving between all states within `radius` cells of each other in
+    /// the absence of obstacles, for use as a heuristic.
+    ///
+    /// note: the costs are found by searching a window extending `2 * radius` cells out, so they
+    ///       can be overestimates for primitive sets which need to take long detours to reach
+    ///       nearby states.
+    pub fn heuristic_table(&self, radius: i32) -> LatticeHeuristic {
+        assert!(radius >= 0, "radius must be non-negative");
+        let size = 4 * radius + 1;
+        let c = 2 * radius;
+        let empty = BitGrid::new(size, size);
+        let mut free = StateLattice {
+            map: &empty,
+            headings: self.headings,
+            turn_penalty: self.turn_penalty,
+            primitives: self.primitives.clone(),
+        };
+        let mut pool = VoxelPool::new(size, size, self.headings);
+        let mut owner = Owner::new();
+
+        let side = 2 * radius + 1;
+        let mut table = vec![f64::INFINITY; (side * side * self.headings * self.headings) as usize];
+        for hs in 0..self.headings {
+            // there is no goal, so this explores the entire window
+            voxel_search(
+                &mut pool,
+                &mut owner,
+                &mut free,
+                zero_heuristic(),
+                (c, c, hs),
+                (-1, -1, -1),
+            );
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    for hg in 0..self.headings {
+                        if let Some(node) = pool.get(c + dx, c + dy, hg, &owner) {
+                            let i = ((hs * self.headings + hg) * side + dy + radius) * side
+                                + dx
+                                + radius;
+                            table[i as usize] = owner.ro(node).g;
+                        }
+                    }
+                }
+            }
+        }
+
+        let scale = self
+            .primitives
+            .iter()
+            .flatten()
+            .filter(|p| p.offset != (0, 0))
+            .map(|p| self.cost(p) / octile(p.offset.0, p.offset.1))
+            .fold(f64::INFINITY, f64::min);
+
+        LatticeHeuristic {
+            radius,
+            headings: self.headings,
+            scale: if scale.is_finite() { scale } else { 0.0 },
+            table: table.into_boxed_slice(),
+        }
+    }
+}
+
+/// Builds a primitive which takes a unit step in each of the given headings of [`HEADINGS`] in
+/// turn, without cutting corners.
+fn primitive_from_steps(steps: &[usize]) -> MotionPrimitive {
+    let (mut x, mut y) = (0, 0);
+    let mut swept = vec![];
+    let mut cost = 0.0;
+    for &h in steps {
+        let (dx, dy) = HEADINGS[h];
+        if dx != 0 && dy != 0 {
+            swept.push((x + dx, y));
+            swept.push((x, y + dy));
+            cost += SQRT_2;
+        } else {
+            cost += 1.0;
+        }
+        x += dx;
+        y += dy;
+        swept.push((x, y));
+    }
+    MotionPrimitive {
+        start_heading: steps[0] as i32,
+        end_heading: steps[steps.len() - 1] as i32,
+        offset: (x, y),
+        swept,
+        cost,
+    }
+}
+
+fn octile(dx: i32, dy: i32) -> f64 {
+    let (dx, dy) = (dx.abs() as f64, dy.abs() as f64);
+    dx.max(dy) + (SQRT_2 - 1.0) * dx.min(dy)
+}
+
+unsafe impl VoxelDomain for StateLattice<'_> {
+    fn width(&self) -> i32 {
+        self.map.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.map.height()
+    }
+
+    fn depth(&self) -> i32 {
+        self.headings
+    }
+}
+
+impl ExpansionPolicy<(i32, i32, i32)> for StateLattice<'_> {
+    fn expand(
+        &mut self,
+        node: &SearchNode<(i32, i32, i32)>,
+        edges: &mut Vec<Edge<(i32, i32, i32)>>,
+    ) {
+        let (x, y, h) = node.id;
+        if !(0..self.map.width()).contains(&x)
+            || !(0..self.map.height()).contains(&y)
+            || !(0..self.headings).contains(&h)
+        {
+            panic!("Lattice state ({}, {}, {}) is out of bounds.", x, y, h);
+        }
+        unsafe {
+            // SAFETY: bounds checked above
+            self.expand_unchecked(node, edges)
+        }
+    }
+
+    unsafe fn expand_unchecked(
+        &mut self,
+        node: &SearchNode<(i32, i32, i32)>,
+        edges: &mut Vec<Edge<(i32, i32, i32)>>,
+    ) {
+        let (x, y, h) = node.id;
+        if self.map.get_unchecked(x, y) {
+            return;
+        }
+        let (width, height) = (self.map.width(), self.map.height());
+        for p in self.primitives.get_unchecked(h as usize) {
+            // the destination is one of the swept cells, so this also keeps edges in-bounds
+            let clear = p.swept.iter().all(|&(dx, dy)| {
+                let (x, y) = (x + dx, y + dy);
+                (0..width).contains(&x) && (0..height).contains(&y) && !self.map.get_unchecked(x, y)
+            });
+            if clear {
+                edges.push(Edge {
+                    destination: (x + p.offset.0, y + p.offset.1, p.end_heading),
+                    cost: self.cost(p),
+                });
+            }
+        }
+    }
+}
+
+/// Free-space costs between nearby lattice states, combined with octile distance scaled by the
+/// cheapest cost per unit of distance of any primitive.
+#[derive(Clone, Debug)]
+pub struct LatticeHeuristic {
+    radius: i32,
+    headings: i32,
+    scale: f64,
+    table: Box<[f64]>,
+}
+
+impl LatticeHeuristic {
+    pub fn heuristic(&self, goal: (i32, i32, i32)) -> impl Fn((i32, i32, i32)) -> f64 + '_ {
+        let (gx, gy, hg) = goal;
+        let r = self.radius;
+        let side = 2 * r + 1;
+        move |(x, y, hs)| {
+            let (dx, dy) = (gx - x, gy - y);
+            let octile = octile(dx, dy) * self.scale;
+            if dx.abs() > r || dy.abs() > r {
+                return octile;
+            }
+            let i = ((hs * self.headings + hg) * side + dy + r) * side + dx + r;
+            let free = self.table[i as usize];
+            // states not reached while building the table may still be reachable with detours
+            if free.is_finite() {
+                free.max(octile)
+            } else {
+                octile
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::random_grid;
+    use crate::util::assert_same_cost;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    fn node(id: (i32, i32, i32)) -> SearchNode<(i32, i32, i32)> {
+        SearchNode {
+            search_num: 0,
+            pqueue_location: 0,
+            expansions: 0,
+            id,
+            parent: None,
+            g: 0.0,
+            lb: 0.0,
+        }
+    }
+
+    #[test]
+    fn check_swept_cells() {
+        let mut map = BitGrid::new(4, 4);
+        map.set(2, 0, true);
+        let mut lattice = StateLattice::with_default_primitives(&map);
+        lattice.set_turn_penalty(0.5);
+        let mut edges = vec![];
+        lattice.expand(&node((0, 0, 0)), &mut edges);
+        // turning south-east from (1, 0) would cut the corner of (2, 0), and turning north-east
+        // leaves the map
+        assert_eq!(
+            edges,
+            vec![Edge {
+                destination: (1, 0, 0),
+                cost: 1.0
+            }]
+        );
+        edges.clear();
+        lattice.expand(&node((0, 1, 0)), &mut edges);
+        assert_eq!(edges.len(), 2);
+        assert!(edges.contains(&Edge {
+            destination: (2, 2, 1),
+            cost: 1.0 + SQRT_2 + 0.5
+        }));
+    }
+
+    #[test]
+    fn check_heuristic() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let map = random_grid(&mut rng, 24, 20, 0.1);
+        let mut lattice = StateLattice::with_default_primitives(&map);
+        lattice.set_turn_penalty(0.25);
+        let table = lattice.heuristic_table(5);
+        let mut pool = VoxelPool::new(map.width(), map.height(), 8);
+        let mut owner = Owner::new();
+
+        for _ in 0..40 {
+            let mut state = || {
+                (
+                    rng.gen_range(0..map.width()),
+                    rng.gen_range(0..map.height()),
+                    rng.gen_range(0..8),
+                )
+            };
+            let (from, to) = (state(), state());
+            let mut search = |h: &dyn Fn((i32, i32, i32)) -> f64| {
+                voxel_search(&mut pool, &mut owner, &mut lattice, h, from, to);
+                pool.get(to.0, to.1, to.2, &owner)
+                    .map(|n| owner.ro(n).g)
+                    .filter(|g| g.is_finite())
+            };
+            let expected = search(&zero_heuristic());
+            let h = table.heuristic(to);
+            let actual = search(&h);
+            assert_same_cost(actual, expected);
+            if let Some(g) = expected {
+                assert!(h(from) <= g + 1e-9);
+            }
+        }
+
+        // without obstacles, the table is exact
+        let empty = BitGrid::new(41, 41);
+        let mut lattice = StateLattice::with_default_primitives(&empty);
+        lattice.set_turn_penalty(0.25);
+        let mut pool = VoxelPool::new(41, 41, 8);
+        for &to in &[(23, 18, 2), (20, 20, 4), (17, 25, 7)] {
+            let h = table.heuristic(to);
+            for hs in 0..8 {
+                let from = (20, 20, hs);
+                voxel_search(
+                    &mut pool,
+                    &mut owner,
+                    &mut lattice,
+                    zero_heuristic(),
+                    from,
+                    to,
+                );
+                let g = pool.get(to.0, to.1, to.2, &owner).map(|n| owner.ro(n).g);
+                assert_eq!(g, Some(h(from)));
+            }
+        }
+    }
+}
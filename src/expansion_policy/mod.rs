@@ -1,8 +1,79 @@
 use crate::{Edge, SearchNode};
 
+/// Implements the domain traits for a wrapper whose first type parameter is the type of its
+/// `inner` policy, by forwarding them to `inner`.
+///
+/// SAFETY: the wrapper must only produce edges which `inner` produced.
+macro_rules! forward_domains {
+    ($wrapper:ident<E $(, $param:ident)*>) => {
+        // SAFETY: edges are those of the inner policy, so its bounds hold.
+        unsafe impl<E: crate::util::GridDomain $(, $param)*> crate::util::GridDomain
+            for $wrapper<E $(, $param)*>
+        {
+            fn width(&self) -> i32 {
+                self.inner.width()
+            }
+
+            fn height(&self) -> i32 {
+                self.inner.height()
+            }
+        }
+
+        // SAFETY: edges are those of the inner policy, so its bounds hold.
+        unsafe impl<E: crate::util::IndexDomain $(, $param)*> crate::util::IndexDomain
+            for $wrapper<E $(, $param)*>
+        {
+            fn len(&self) -> usize {
+                self.inner.len()
+            }
+        }
+
+        // SAFETY: edges are those of the inner policy, so its bounds hold.
+        unsafe impl<E: crate::util::HexDomain $(, $param)*> crate::util::HexDomain
+            for $wrapper<E $(, $param)*>
+        {
+            fn width(&self) -> i32 {
+                self.inner.width()
+            }
+
+            fn height(&self) -> i32 {
+                self.inner.height()
+            }
+
+            fn layout(&self) -> crate::util::HexLayout {
+                self.inner.layout()
+            }
+        }
+
+        // SAFETY: edges are those of the inner policy, so its bounds hold.
+        unsafe impl<E: crate::util::VoxelDomain $(, $param)*> crate::util::VoxelDomain
+            for $wrapper<E $(, $param)*>
+        {
+            fn width(&self) -> i32 {
+                self.inner.width()
+            }
+
+            fn height(&self) -> i32 {
+                self.inner.height()
+            }
+
+            fn depth(&self) -> i32 {
+                self.inner.depth()
+            }
+        }
+    };
+}
+
 pub mod bitgrid;
 pub mod weighted_grid;
 pub mod graph;
+pub mod hex;
+pub mod voxel;
+pub mod navmesh;
+pub mod terrain;
+pub mod lattice;
+pub mod overlay;
+pub mod observed;
 
 pub trait ExpansionPolicy<VertexId> {
     fn expand(&mut self, node: &SearchNode<VertexId>, edges: &mut Vec<Edge<VertexId>>);
@@ -0,0 +1,299 @@
+use crate::domains::NavMesh;
+use crate::node_pool::IndexPool;
+use crate::util::{cross, distance, index_search, IndexDomain};
+use crate::{Edge, ExpansionPolicy, Owner, SearchNode};
+
+/// How the cost of moving between adjacent polygons is measured.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NavMeshCost {
+    /// The straight-line distance between the polygon centers.
+    Centers,
+    /// The distance from the center of the polygon to the midpoint of the portal, and from there
+    /// to the center of the neighbor.
+    PortalMidpoints,
+    /// The length of the shortest path from the center of the polygon to the center of the
+    /// neighbor which crosses the portal. This is the straight-line distance if the line between
+    /// the centers crosses the portal, and otherwise the distance via the nearer portal endpoint.
+    PortalEdges,
+}
+
+/// Moves between adjacent polygons of a navmesh.
+pub struct NavMeshNeighbors<'a> {
+    mesh: &'a NavMesh,
+    edges: Vec<Vec<Edge<usize>>>,
+}
+
+impl NavMeshNeighbors<'_> {
+    pub fn new(mesh: &NavMesh, cost: NavMeshCost) -> NavMeshNeighbors<'_> {
+        let edges = (0..mesh.len())
+            .map(|p| {
+                let from = mesh.center(p);
+                mesh.portals(p)
+                    .iter()
+                    .map(|portal| {
+                        let to = mesh.center(portal.polygon);
+                        let (l, r) = (mesh.vertex(portal.left), mesh.vertex(portal.right));
+                        let cost = match cost {
+                            NavMeshCost::Centers => distance(from, to),
+                            NavMeshCost::PortalMidpoints => {
+                                let mid = ((l.0 + r.0) / 2.0, (l.1 + r.1) / 2.0);
+                                distance(from, mid) + distance(mid, to)
+                            }
+                            // the centers are on opposite sides of the portal, so the line between
+                            // them crosses the portal if the endpoints are on opposite sides of it
+                            NavMeshCost::PortalEdges
+                                if cross(from, to, l) * cross(from, to, r) <= 0.0 =>
+                            {
+                                distance(from, to)
+                            }
+                            NavMeshCost::PortalEdges => (distance(from, l) + distance(l, to))
+                                .min(distance(from, r) + distance(r, to)),
+                        };
+                        Edge {
+                            destination: portal.polygon,
+                            cost,
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        NavMeshNeighbors { mesh, edges }
+    }
+
+    /// Finds the polygon corridor from the polygon containing `start` to the polygon containing
+    /// `goal`, and shortens it into a path of waypoints. Returns `None` if either point is outside
+    /// of the navmesh or there is no corridor between them.
+    pub fn find_path(
+        &mut self,
+        pool: &mut IndexPool,
+        owner: &mut Owner,
+        start: (f64, f64),
+        goal: (f64, f64),
+    ) -> Option<Vec<(f64, f64)>> {
+        let mesh = self.mesh;
+        let source = mesh.locate(start)?;
+        let target = mesh.locate(goal)?;
+        index_search(pool, owner, self, mesh.heuristic(target), source, target);
+
+        let mut corridor = vec![target];
+        let mut node = pool.get(target, owner)?;
+        while let Some(parent) = owner.ro(node).parent {
+            corridor.push(parent);
+            node = pool.get(parent, owner).unwrap();
+        }
+        corridor.reverse();
+
+        Some(mesh.funnel(start, goal, &corridor))
+    }
+}
+
+// SAFETY: edges are built from portals, which always lead to polygons of the navmesh.
+unsafe impl IndexDomain for NavMeshNeighbors<'_> {
+    fn len(&self) -> usize {
+        self.mesh.len()
+    }
+}
+
+impl ExpansionPolicy<usize> for NavMeshNeighbors<'_> {
+    fn expand(&mut self, node: &SearchNode<usize>, edges: &mut Vec<Edge<usize>>) {
+        edges.extend_from_slice(&self.edges[node.id]);
+    }
+
+    unsafe fn expand_unchecked(&mut self, node: &SearchNode<usize>, edges: &mut Vec<Edge<usize>>) {
+        edges.extend_from_slice(self.edges.get_unchecked(node.id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::{parse_obj, random_grid};
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+    use std::collections::VecDeque;
+
+    fn square(x: i32, y: i32) -> [(f64, f64); 4] {
+        let (x, y) = (x as f64, y as f64);
+        [(x, y), (x + 1.0, y), (x + 1.0, y + 1.0), (x, y + 1.0)]
+    }
+
+    fn length(path: &[(f64, f64)]) -> f64 {
+        path.windows(2)
+            .map(|w| (w[0].0 - w[1].0).hypot(w[0].1 - w[1].1))
+            .sum()
+    }
+
+    #[test]
+    fn check_funnel_corner() {
+        let cells = [(0, 0), (1, 0), (2, 0), (2, 1), (2, 2), (1, 2)];
+        let mesh = NavMesh::from_soup(cells.iter().map(|&(x, y)| square(x, y))).unwrap();
+        let mut pool = IndexPool::new(mesh.len());
+        let mut owner = Owner::new();
+        for &cost in &[
+            NavMeshCost::Centers,
+            NavMeshCost::PortalMidpoints,
+            NavMeshCost::PortalEdges,
+        ] {
+            let path = NavMeshNeighbors::new(&mesh, cost).find_path(
+                &mut pool,
+                &mut owner,
+                (0.5, 0.5),
+                (1.25, 2.5),
+            );
+            assert_eq!(
+                path,
+                Some(vec![(0.5, 0.5), (2.0, 1.0), (2.0, 2.0), (1.25, 2.5)])
+            );
+        }
+        assert!(NavMeshNeighbors::new(&mesh, NavMeshCost::Centers)
+            .find_path(&mut pool, &mut owner, (0.5, 0.5), (0.5, 1.5))
+            .is_none());
+    }
+
+    #[test]
+    fn check_portal_edges() {
+        // the line between the centers passes beside the portal at (3, 1)-(4, 1)
+        let polygons = vec![
+            vec![(0.0, 0.0), (4.0, 0.0), (4.0, 1.0), (3.0, 1.0), (0.0, 1.0)],
+            vec![(3.0, 1.0), (4.0, 1.0), (4.0, 2.0), (3.0, 2.0)],
+        ];
+        let mesh = NavMesh::from_soup(&polygons).unwrap();
+        let node = SearchNode {
+            search_num: 0,
+            pqueue_location: 0,
+            expansions: 0,
+            id: mesh.locate((0.5, 0.5)).unwrap(),
+            parent: None,
+            g: 0.0,
+            lb: 0.0,
+        };
+        let cost = |cost| {
+            let mut edges = vec![];
+            NavMeshNeighbors::new(&mesh, cost).expand(&node, &mut edges);
+            assert_eq!(edges.len(), 1);
+            edges[0].cost
+        };
+        let (from, to) = (mesh.center(0), mesh.center(1));
+        let expected = distance(from, (3.0, 1.0)) + distance((3.0, 1.0), to);
+        assert!((cost(NavMeshCost::PortalEdges) - expected).abs() < 1e-9);
+        assert!(cost(NavMeshCost::Centers) < expected);
+        assert!(cost(NavMeshCost::PortalMidpoints) > expected);
+    }
+
+    #[test]
+    fn check_random_grid_meshes() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let map = random_grid(&mut rng, 16, 12, 0.3);
+        let mut cells = vec![];
+        let mut squares = vec![];
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                if !map.get(x, y) {
+                    cells.push((x, y));
+                    squares.push(square(x, y));
+                }
+            }
+        }
+        let mesh = NavMesh::from_soup(&squares).unwrap();
+        let mut pool = IndexPool::new(mesh.len());
+        let mut owner = Owner::new();
+
+        for _ in 0..100 {
+            let (from, to) = (
+                *cells.choose(&mut rng).unwrap(),
+                *cells.choose(&mut rng).unwrap(),
+            );
+            let start = (
+                from.0 as f64 + rng.gen::<f64>(),
+                from.1 as f64 + rng.gen::<f64>(),
+            );
+            let goal = (
+                to.0 as f64 + rng.gen::<f64>(),
+                to.1 as f64 + rng.gen::<f64>(),
+            );
+
+            // 4-connected BFS over the grid decides reachability
+            let mut seen = vec![false; cells.len()];
+            let mut queue = VecDeque::new();
+            let start_cell = cells.iter().position(|&c| c == from).unwrap();
+            seen[start_cell] = true;
+            queue.push_back(from);
+            while let Some((x, y)) = queue.pop_front() {
+                for &(nx, ny) in &[(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                    if let Some(i) = cells.iter().position(|&c| c == (nx, ny)) {
+                        if !seen[i] {
+                            seen[i] = true;
+                            queue.push_back((nx, ny));
+                        }
+                    }
+                }
+            }
+            let reachable = seen[cells.iter().position(|&c| c == to).unwrap()];
+
+            let mut ep = NavMeshNeighbors::new(&mesh, NavMeshCost::PortalMidpoints);
+            let path = ep.find_path(&mut pool, &mut owner, start, goal);
+            assert_eq!(path.is_some(), reachable);
+            let path = match path {
+                Some(path) => path,
+                None => continue,
+            };
+            assert_eq!(path.first(), Some(&start));
+            assert_eq!(path.last(), Some(&goal));
+
+            // the path stays within the mesh
+            for w in path.windows(2) {
+                for i in 0..=20 {
+                    let t = i as f64 / 20.0;
+                    let p = (
+                        w[0].0 + (w[1].0 - w[0].0) * t,
+                        w[0].1 + (w[1].1 - w[0].1) * t,
+                    );
+                    assert!(mesh.locate(p).is_some());
+                }
+            }
+
+            // the path is no longer than the path through the corridor's portal midpoints
+            let mut corridor = vec![mesh.locate(goal).unwrap()];
+            while let Some(p) = owner
+                .ro(pool.get(*corridor.last().unwrap(), &owner).unwrap())
+                .parent
+            {
+                corridor.push(p);
+            }
+            corridor.reverse();
+            let mut midpoints = vec![start];
+            for w in corridor.windows(2) {
+                let portal = mesh.find_portal(w[0], w[1]).unwrap();
+                let (l, r) = (mesh.vertex(portal.left), mesh.vertex(portal.right));
+                midpoints.push(((l.0 + r.0) / 2.0, (l.1 + r.1) / 2.0));
+            }
+            midpoints.push(goal);
+            assert!(length(&path) <= length(&midpoints) + 1e-9);
+            assert!(length(&path) >= length(&[start, goal]) - 1e-9);
+        }
+    }
+
+    #[test]
+    fn check_obj() {
+        let obj = "\
+# two triangles forming a unit square
+o floor
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 1.0 0.0 1.0
+v 0.0 0.0 1.0
+vn 0.0 1.0 0.0
+f 1//1 3//1 2//1
+f -4/1/1 -1/1/1 -2/1/1
+";
+        let mesh = parse_obj(obj.as_bytes()).unwrap();
+        assert_eq!(mesh.len(), 2);
+        assert_eq!(mesh.portals(0).len(), 1);
+        assert_eq!(mesh.portals(0)[0].polygon, 1);
+        assert_eq!(mesh.portals(1)[0].polygon, 0);
+        assert_eq!(mesh.locate((0.9, 0.1)), Some(0));
+        assert_eq!(mesh.locate((0.1, 0.9)), Some(1));
+        assert!(parse_obj("v 0 0 0\nf 1 2 3\n".as_bytes()).is_err());
+    }
+}
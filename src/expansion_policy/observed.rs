@@ -0,0 +1,90 @@
+use crate::{Edge, ExpansionPolicy, SearchNode};
+
+/// Wraps an expansion policy, calling a function with every node it expands and the edges it
+/// produces. This allows observing a search as it runs, e.g. to record an animation.
+pub struct Observed<E, F> {
+    inner: E,
+    observer: F,
+}
+
+impl<E, F> Observed<E, F> {
+    pub fn new(inner: E, observer: F) -> Self {
+        Observed { inner, observer }
+    }
+
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+}
+
+impl<V, E, F> ExpansionPolicy<V> for Observed<E, F>
+where
+    E: ExpansionPolicy<V>,
+    F: FnMut(&SearchNode<V>, &[Edge<V>]),
+{
+    fn expand(&mut self, node: &SearchNode<V>, edges: &mut Vec<Edge<V>>) {
+        let start = edges.len();
+        self.inner.expand(node, edges);
+        (self.observer)(node, &edges[start..]);
+    }
+
+    unsafe fn expand_unchecked(&mut self, node: &SearchNode<V>, edges: &mut Vec<Edge<V>>) {
+        let start = edges.len();
+        self.inner.expand_unchecked(node, edges);
+        (self.observer)(node, &edges[start..]);
+    }
+}
+
+forward_domains!(Observed<E, F>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::BitGrid;
+    use crate::expansion_policy::bitgrid::no_corner_cutting::NoCornerCutting;
+    use crate::node_pool::GridPool;
+    use crate::util::{grid_search, octile_heuristic};
+    use crate::Owner;
+
+    #[test]
+    fn check_observes_expansions() {
+        let mut map = BitGrid::new(8, 8);
+        map.fill_rect(2, 0, 1, 6, true);
+        let mut pool = GridPool::new(8, 8);
+        let mut owner = Owner::new();
+        let mut expanded = vec![];
+        let mut ep = Observed::new(NoCornerCutting::new(&map), |n: &SearchNode<_>, e: &[_]| {
+            expanded.push((n.id, e.to_vec()))
+        });
+        grid_search(
+            &mut pool,
+            &mut owner,
+            &mut ep,
+            octile_heuristic((7, 0), 1.0),
+            (0, 0),
+            (7, 0),
+        );
+
+        assert_eq!(expanded.first().map(|e| e.0), Some((0, 0)));
+        let mut expansions = 0;
+        for y in 0..8 {
+            for x in 0..8 {
+                if let Some(n) = pool.get(x, y, &owner) {
+                    expansions += owner.ro(n).expansions;
+                }
+            }
+        }
+        // the goal is popped but not expanded
+        assert_eq!(expanded.len() + 1, expansions);
+        for (id, edges) in &expanded {
+            let node = pool.get(id.0, id.1, &owner).unwrap();
+            let mut expected = vec![];
+            NoCornerCutting::new(&map).expand(owner.ro(node), &mut expected);
+            assert_eq!(edges, &expected);
+        }
+    }
+}
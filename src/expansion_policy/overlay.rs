@@ -0,0 +1,375 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{Edge, ExpansionPolicy, SearchNode};
+
+/// A modification of the cost of an edge.
+///
+/// Modifiers never make costs negative: the overlays reject negative amounts. Modifiers which
+/// lower costs, i.e. multipliers below 1, make the heuristics of the inner policy inadmissible,
+/// so they must be scaled down by the smallest multiplier to keep searches optimal.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Modifier {
+    /// Adds a non-negative amount to the cost.
+    Add(f64),
+    /// Multiplies the cost by a non-negative factor.
+    Multiply(f64),
+    /// Removes the edge.
+    Block,
+}
+
+impl Modifier {
+    #[track_caller]
+    fn validate(self) {
+        match self {
+            Modifier::Add(c) => assert!(c >= 0.0, "added cost must not be negative"),
+            Modifier::Multiply(m) => assert!(m >= 0.0, "multiplier must not be negative"),
+            Modifier::Block => {}
+        }
+    }
+
+    /// Applies the modifier to a cost, returning `None` if the edge is blocked.
+    pub fn apply(self, cost: f64) -> Option<f64> {
+        match self {
+            Modifier::Add(c) => Some(cost + c),
+            Modifier::Multiply(m) => Some(cost * m),
+            Modifier::Block => None,
+        }
+    }
+}
+
+/// Modifies the costs of the edges produced by an expansion policy.
+pub trait Overlay<VertexId> {
+    /// Returns the new cost of the edge from `from` to `to`, or `None` to remove it.
+    fn modify(&self, from: VertexId, to: VertexId, cost: f64) -> Option<f64>;
+}
+
+impl<V, O: Overlay<V>> Overlay<V> for &O {
+    fn modify(&self, from: V, to: V, cost: f64) -> Option<f64> {
+        (**self).modify(from, to, cost)
+    }
+}
+
+/// Wraps an expansion policy, passing the cost of every edge it produces through an overlay.
+///
+/// Since the overlay can only change costs and remove edges, the bounds of the inner policy are
+/// forwarded as-is.
+pub struct Overlaid<E, O> {
+    inner: E,
+    overlay: O,
+}
+
+impl<E, O> Overlaid<E, O> {
+    pub fn new(inner: E, overlay: O) -> Self {
+        Overlaid { inner, overlay }
+    }
+
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    pub fn overlay(&self) -> &O {
+        &self.overlay
+    }
+
+    pub fn overlay_mut(&mut self) -> &mut O {
+        &mut self.overlay
+    }
+
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+}
+
+/// Passes the edges from index `start` onwards through the overlay, removing blocked edges.
+fn apply<V: Copy>(overlay: &impl Overlay<V>, from: V, edges: &mut Vec<Edge<V>>, start: usize) {
+    let mut kept = start;
+    for i in start..edges.len() {
+        let edge = edges[i];
+        if let Some(cost) = overlay.modify(from, edge.destination, edge.cost) {
+            edges[kept] = Edge {
+                destination: edge.destination,
+                cost,
+            };
+            kept += 1;
+        }
+    }
+    edges.truncate(kept);
+}
+
+impl<V, E, O> ExpansionPolicy<V> for Overlaid<E, O>
+where
+    V: Copy,
+    E: ExpansionPolicy<V>,
+    O: Overlay<V>,
+{
+    fn expand(&mut self, node: &SearchNode<V>, edges: &mut Vec<Edge<V>>) {
+        let start = edges.len();
+        self.inner.expand(node, edges);
+        apply(&self.overlay, node.id, edges, start);
+    }
+
+    unsafe fn expand_unchecked(&mut self, node: &SearchNode<V>, edges: &mut Vec<Edge<V>>) {
+        let start = edges.len();
+        self.inner.expand_unchecked(node, edges);
+        apply(&self.overlay, node.id, edges, start);
+    }
+}
+
+forward_domains!(Overlaid<E, O>);
+
+/// A region of grid cells.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Area {
+    Rect {
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    },
+    /// The cells whose centers are within `radius` of the center of the `center` cell.
+    Circle { center: (i32, i32), radius: f64 },
+}
+
+impl Area {
+    pub fn contains(&self, (x, y): (i32, i32)) -> bool {
+        match *self {
+            Area::Rect {
+                x: rx,
+                y: ry,
+                width,
+                height,
+            } => (rx..rx + width).contains(&x) && (ry..ry + height).contains(&y),
+            Area::Circle { center, radius } => {
+                let (dx, dy) = ((x - center.0) as f64, (y - center.1) as f64);
+                dx * dx + dy * dy <= radius * radius
+            }
+        }
+    }
+}
+
+/// An overlay for grid policies. Area modifiers apply to edges entering the area, in the order they
+/// were added. Edge modifiers apply after area modifiers.
+///
+/// note: blocking an area only removes the edges entering it, so an inner policy such as
+///       `NoCornerCutting` still moves diagonally past the corners of blocked cells. Obstruct the
+///       cells in the map instead if corners must not be cut.
+///
+/// note: runtime of `modify` is linear in the number of areas.
+#[derive(Clone, Debug, Default)]
+pub struct GridOverlay {
+    areas: Vec<Option<(Area, Modifier)>>,
+    edges: HashMap<((i32, i32), (i32, i32)), Modifier>,
+}
+
+impl GridOverlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an area modifier, returning an ID which can be used to remove it.
+    #[track_caller]
+    pub fn add_area(&mut self, area: Area, modifier: Modifier) -> usize {
+        modifier.validate();
+        self.areas.push(Some((area, modifier)));
+        self.areas.len() - 1
+    }
+
+    pub fn remove_area(&mut self, id: usize) -> Option<(Area, Modifier)> {
+        self.areas.get_mut(id)?.take()
+    }
+
+    #[track_caller]
+    pub fn set_edge(&mut self, from: (i32, i32), to: (i32, i32), modifier: Modifier) {
+        modifier.validate();
+        self.edges.insert((from, to), modifier);
+    }
+
+    pub fn remove_edge(&mut self, from: (i32, i32), to: (i32, i32)) -> Option<Modifier> {
+        self.edges.remove(&(from, to))
+    }
+
+    pub fn clear(&mut self) {
+        self.areas.clear();
+        self.edges.clear();
+    }
+}
+
+impl Overlay<(i32, i32)> for GridOverlay {
+    fn modify(&self, from: (i32, i32), to: (i32, i32), mut cost: f64) -> Option<f64> {
+        for &(area, modifier) in self.areas.iter().flatten() {
+            if area.contains(to) {
+                cost = modifier.apply(cost)?;
+            }
+        }
+        match self.edges.get(&(from, to)) {
+            Some(modifier) => modifier.apply(cost),
+            None => Some(cost),
+        }
+    }
+}
+
+/// An overlay of vertex and edge modifiers, e.g. for graph policies. Vertex modifiers apply to
+/// edges entering the vertex. Edge modifiers apply after vertex modifiers.
+#[derive(Clone, Debug)]
+pub struct GraphOverlay<V = usize> {
+    vertices: HashMap<V, Modifier>,
+    edges: HashMap<(V, V), Modifier>,
+}
+
+impl<V> Default for GraphOverlay<V> {
+    fn default() -> Self {
+        GraphOverlay {
+            vertices: HashMap::new(),
+            edges: HashMap::new(),
+        }
+    }
+}
+
+impl<V: Hash + Eq> GraphOverlay<V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[track_caller]
+    pub fn set_vertex(&mut self, vertex: V, modifier: Modifier) {
+        modifier.validate();
+        self.vertices.insert(vertex, modifier);
+    }
+
+    pub fn remove_vertex(&mut self, vertex: &V) -> Option<Modifier> {
+        self.vertices.remove(vertex)
+    }
+
+    #[track_caller]
+    pub fn set_edge(&mut self, from: V, to: V, modifier: Modifier) {
+        modifier.validate();
+        self.edges.insert((from, to), modifier);
+    }
+
+    pub fn remove_edge(&mut self, from: V, to: V) -> Option<Modifier> {
+        self.edges.remove(&(from, to))
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.edges.clear();
+    }
+}
+
+impl<V: Hash + Eq + Copy> Overlay<V> for GraphOverlay<V> {
+    fn modify(&self, from: V, to: V, mut cost: f64) -> Option<f64> {
+        if let Some(modifier) = self.vertices.get(&to) {
+            cost = modifier.apply(cost)?;
+        }
+        match self.edges.get(&(from, to)) {
+            Some(modifier) => modifier.apply(cost),
+            None => Some(cost),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::{BitGrid, DirectedGraph};
+    use crate::expansion_policy::bitgrid::no_corner_cutting::NoCornerCutting;
+    use crate::expansion_policy::graph::OutgoingEdges;
+    use crate::node_pool::{GridPool, IndexPool};
+    use crate::util::{grid_search, index_search, octile_heuristic, zero_heuristic};
+    use crate::Owner;
+
+    #[test]
+    fn check_grid_overlay() {
+        let map = BitGrid::new(10, 10);
+        let mut overlay = GridOverlay::new();
+        // a wall across the map with a gap at x = 9
+        let wall = overlay.add_area(
+            Area::Rect {
+                x: 0,
+                y: 5,
+                width: 9,
+                height: 1,
+            },
+            Modifier::Block,
+        );
+        overlay.add_area(
+            Area::Circle {
+                center: (9, 5),
+                radius: 0.5,
+            },
+            Modifier::Add(10.0),
+        );
+        let mut pool = GridPool::new(10, 10);
+        let mut owner = Owner::new();
+        let mut ep = Overlaid::new(NoCornerCutting::new(&map), &overlay);
+        grid_search(
+            &mut pool,
+            &mut owner,
+            &mut ep,
+            octile_heuristic((0, 9), 1.0),
+            (0, 0),
+            (0, 9),
+        );
+        let g = owner.ro(pool.get(0, 9, &owner).unwrap()).g;
+        // to (8, 4), diagonally through the gap past the corner of the blocked (8, 5), then to
+        // (0, 9)
+        let expected = 10.0 + 9.0 * std::f64::consts::SQRT_2 + 9.0;
+        assert!((g - expected).abs() < 1e-9);
+
+        overlay.remove_area(wall);
+        overlay.set_edge((0, 4), (0, 5), Modifier::Multiply(2.0));
+        let mut ep = Overlaid::new(NoCornerCutting::new(&map), &overlay);
+        grid_search(
+            &mut pool,
+            &mut owner,
+            &mut ep,
+            octile_heuristic((0, 9), 1.0),
+            (0, 0),
+            (0, 9),
+        );
+        let g = owner.ro(pool.get(0, 9, &owner).unwrap()).g;
+        // stepping diagonally across the wall avoids the modified edge
+        assert!((g - (7.0 + 2.0 * std::f64::consts::SQRT_2)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn check_graph_overlay() {
+        let mut graph = DirectedGraph::new();
+        for _ in 0..4 {
+            graph.add_vertex(());
+        }
+        graph.add_edge(0, 1, 1.0);
+        graph.add_edge(1, 3, 1.0);
+        graph.add_edge(0, 2, 2.0);
+        graph.add_edge(2, 3, 2.0);
+        let mut pool = IndexPool::new(graph.len());
+        let mut owner = Owner::new();
+        let mut cost = |overlay: &GraphOverlay| {
+            let mut ep = Overlaid::new(OutgoingEdges::new(&graph), overlay);
+            index_search(&mut pool, &mut owner, &mut ep, zero_heuristic(), 0, 3);
+            pool.get(3, &owner).map(|n| owner.ro(n).g)
+        };
+
+        let mut overlay = GraphOverlay::new();
+        assert_eq!(cost(&overlay), Some(2.0));
+        overlay.set_vertex(1, Modifier::Add(2.5));
+        assert_eq!(cost(&overlay), Some(4.0));
+        overlay.set_edge(2, 3, Modifier::Multiply(0.5));
+        assert_eq!(cost(&overlay), Some(3.0));
+        overlay.set_edge(0, 2, Modifier::Block);
+        assert_eq!(cost(&overlay), Some(4.5));
+        overlay.set_vertex(1, Modifier::Block);
+        assert_eq!(cost(&overlay), None);
+        overlay.clear();
+        assert_eq!(cost(&overlay), Some(2.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn check_negative_modifier() {
+        GraphOverlay::new().set_vertex(0, Modifier::Add(-1.0));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::domains::TerrainGrid;
+use crate::expansion_policy::bitgrid::no_corner_cutting::push_edges;
+use crate::util::GridDomain;
+use crate::{Edge, ExpansionPolicy, SearchNode};
+
+/// Like `NoCornerCutting`, but cells are passable if their terrain type is in the capability mask.
+pub struct TerrainNeighbors<'a> {
+    map: &'a TerrainGrid,
+    mask: u64,
+}
+
+impl TerrainNeighbors<'_> {
+    pub fn new(map: &TerrainGrid, mask: u64) -> TerrainNeighbors<'_> {
+        TerrainNeighbors { map, mask }
+    }
+
+    pub fn mask(&self) -> u64 {
+        self.mask
+    }
+}
+
+unsafe impl GridDomain for TerrainNeighbors<'_> {
+    fn width(&self) -> i32 {
+        self.map.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.map.height()
+    }
+}
+
+impl ExpansionPolicy<(i32, i32)> for TerrainNeighbors<'_> {
+    fn expand(&mut self, node: &SearchNode<(i32, i32)>, edges: &mut Vec<Edge<(i32, i32)>>) {
+        self.map.get_neighbors(node.id.0, node.id.1, self.mask);
+        unsafe {
+            // SAFETY: Bounds checked by above call
+            self.expand_unchecked(node, edges)
+        }
+    }
+
+    unsafe fn expand_unchecked(
+        &mut self,
+        node: &SearchNode<(i32, i32)>,
+        edges: &mut Vec<Edge<(i32, i32)>>,
+    ) {
+        let (x, y) = node.id;
+        if !self.map.passable_unchecked(x, y, self.mask) {
+            return;
+        }
+        let nbs = self.map.get_neighbors_unchecked(x, y, self.mask);
+        push_edges(node.id, nbs, edges);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expansion_policy::bitgrid::jps::{create_tmap, JpsExpansionPolicy};
+    use crate::expansion_policy::bitgrid::no_corner_cutting::NoCornerCutting;
+    use crate::incremental::SymmetricGrid;
+    use crate::node_pool::{GridPool, NodePool};
+    use crate::util::{assert_same_cost, grid_search, octile_heuristic};
+    use crate::Owner;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    const GROUND: u8 = 0;
+    const WATER: u8 = 1;
+    const CLIFF: u8 = 2;
+
+    #[test]
+    fn check_masks() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let mut map = TerrainGrid::new(37, 29, GROUND);
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                let t = [GROUND, GROUND, GROUND, WATER, CLIFF]
+                    .choose(&mut rng)
+                    .unwrap();
+                map.set(x, y, *t);
+            }
+        }
+        let mut pool = GridPool::new(map.width(), map.height());
+        let mut owner = Owner::new();
+
+        let masks = [
+            1 << GROUND,
+            1 << GROUND | 1 << WATER,
+            1 << WATER | 1 << CLIFF,
+        ];
+        for &mask in &masks {
+            let bitgrid = map.to_bitgrid(mask);
+            let tmap = create_tmap(&bitgrid);
+            for y in 0..map.height() {
+                for x in 0..map.width() {
+                    assert_eq!(bitgrid.get(x, y), !map.passable(x, y, mask));
+                    let node = pool.generate((x, y), &mut owner);
+                    let (mut expected, mut actual) = (vec![], vec![]);
+                    SymmetricGrid::new(&bitgrid, NoCornerCutting::new(&bitgrid))
+                        .expand(owner.ro(node), &mut expected);
+                    TerrainNeighbors::new(&map, mask).expand(owner.ro(node), &mut actual);
+                    assert_eq!(expected, actual);
+                }
+            }
+
+            for _ in 0..20 {
+                let mut cell = || {
+                    (
+                        rng.gen_range(0..map.width()),
+                        rng.gen_range(0..map.height()),
+                    )
+                };
+                let (from, to) = (cell(), cell());
+                if bitgrid.get(from.0, from.1) || bitgrid.get(to.0, to.1) {
+                    continue;
+                }
+                grid_search(
+                    &mut pool,
+                    &mut owner,
+                    &mut TerrainNeighbors::new(&map, mask),
+                    octile_heuristic(to, 1.0),
+                    from,
+                    to,
+                );
+                let expected = pool.get(to.0, to.1, &owner).map(|n| owner.ro(n).g);
+                JpsExpansionPolicy::new(&bitgrid, &tmap).search(
+                    &mut pool,
+                    &mut owner,
+                    octile_heuristic(to, 1.0),
+                    from,
+                    to,
+                );
+                let actual = pool.get(to.0, to.1, &owner).map(|n| owner.ro(n).g);
+                assert_same_cost(actual, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use crate::domains::VoxelGrid;
+use crate::util::VoxelDomain;
+use crate::{Edge, ExpansionPolicy, SearchNode};
+
+/// The set of moves available from each voxel.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VoxelConnectivity {
+    /// Moves along one axis.
+    Six,
+    /// Moves along one or two axes.
+    Eighteen,
+    /// Moves along any number of axes.
+    TwentySix,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Move {
+    offset: (i32, i32, i32),
+    /// The voxels which must be unobstructed to make the move, as a `get_neighbors` bitset.
+    clearance: u32,
+    cost: f64,
+}
+
+/// Moves between unobstructed voxels. Moves along more than one axis may not cut corners, that is,
+/// every voxel in the box spanned by the source and destination must be unobstructed.
+pub struct VoxelNeighbors<'a> {
+    map: &'a VoxelGrid,
+    connectivity: VoxelConnectivity,
+    moves: Vec<Move>,
+}
+
+impl VoxelNeighbors<'_> {
+    pub fn new(map: &VoxelGrid, connectivity: VoxelConnectivity) -> VoxelNeighbors {
+        let max_axes = match connectivity {
+            VoxelConnectivity::Six => 1,
+            VoxelConnectivity::Eighteen => 2,
+            VoxelConnectivity::TwentySix => 3,
+        };
+        let bit = |x: i32, y: i32, z: i32| 1 << ((x + 1) + 3 * (y + 1) + 9 * (z + 1));
+
+        let mut moves = vec![];
+        for axes in 1..=max_axes {
+            for dz in -1..=1i32 {
+                for dy in -1..=1i32 {
+                    for dx in -1..=1i32 {
+                        if dx.abs() + dy.abs() + dz.abs() != axes {
+                            continue;
+                        }
+                        let mut clearance = 0;
+                        for &x in &[0, dx] {
+                            for &y in &[0, dy] {
+                                for &z in &[0, dz] {
+                                    clearance |= bit(x, y, z);
+                                }
+                            }
+                        }
+                        moves.push(Move {
+                            offset: (dx, dy, dz),
+                            clearance,
+                            cost: (axes as f64).sqrt(),
+                        });
+                    }
+                }
+            }
+        }
+
+        VoxelNeighbors {
+            map,
+            connectivity,
+            moves,
+        }
+    }
+
+    pub fn connectivity(&self) -> VoxelConnectivity {
+        self.connectivity
+    }
+}
+
+unsafe impl VoxelDomain for VoxelNeighbors<'_> {
+    fn width(&self) -> i32 {
+        self.map.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.map.height()
+    }
+
+    fn depth(&self) -> i32 {
+        self.map.depth()
+    }
+}
+
+impl ExpansionPolicy<(i32, i32, i32)> for VoxelNeighbors<'_> {
+    fn expand(
+        &mut self,
+        node: &SearchNode<(i32, i32, i32)>,
+        edges: &mut Vec<Edge<(i32, i32, i32)>>,
+    ) {
+        let (x, y, z) = node.id;
+        self.map.get_neighbors(x, y, z);
+        unsafe {
+            // SAFETY: bounds are checked by above call
+            self.expand_unchecked(node, edges)
+        }
+    }
+
+    unsafe fn expand_unchecked(
+        &mut self,
+        node: &SearchNode<(i32, i32, i32)>,
+        edges: &mut Vec<Edge<(i32, i32, i32)>>,
+    ) {
+        let (x, y, z) = node.id;
+        let nbs = self.map.get_neighbors_unchecked(x, y, z);
+        // the clearance of every move includes the center voxel, so obstructed voxels have no
+        // edges
+        for m in self.moves.iter() {
+            if nbs & m.clearance == 0 {
+                let (dx, dy, dz) = m.offset;
+                edges.push(Edge {
+                    destination: (x + dx, y + dy, z + dz),
+                    cost: m.cost,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_pool::VoxelPool;
+    use crate::util::{
+        assert_same_cost, euclidean_heuristic_3d, manhattan_heuristic_3d, octile_heuristic_3d,
+        voxel_search, zero_heuristic,
+    };
+    use crate::Owner;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    fn node(id: (i32, i32, i32)) -> SearchNode<(i32, i32, i32)> {
+        SearchNode {
+            search_num: 0,
+            pqueue_location: 0,
+            expansions: 0,
+            id,
+            parent: None,
+            g: 0.0,
+            lb: 0.0,
+        }
+    }
+
+    #[test]
+    fn check_corner_cutting() {
+        let mut map = VoxelGrid::new(3, 3, 3);
+        map.set(2, 1, 1, true);
+        let mut edges = vec![];
+        let mut expand = |connectivity| {
+            edges.clear();
+            VoxelNeighbors::new(&map, connectivity).expand(&node((1, 1, 1)), &mut edges);
+            edges.iter().map(|e| e.destination).collect::<Vec<_>>()
+        };
+
+        let six = expand(VoxelConnectivity::Six);
+        assert_eq!(six.len(), 5);
+        let eighteen = expand(VoxelConnectivity::Eighteen);
+        // the 4 moves in the +x direction along 2 axes are blocked
+        assert_eq!(eighteen.len(), 17 - 4);
+        assert!(!eighteen.contains(&(2, 2, 1)));
+        assert!(eighteen.contains(&(1, 2, 2)));
+        let twenty_six = expand(VoxelConnectivity::TwentySix);
+        assert_eq!(twenty_six.len(), 25 - 4 - 4);
+        assert!(!twenty_six.contains(&(2, 2, 2)));
+        assert!(twenty_six.contains(&(0, 2, 2)));
+    }
+
+    #[test]
+    fn check_heuristics_optimal() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let mut map = VoxelGrid::new(13, 11, 9);
+        for z in 0..map.depth() {
+            for y in 0..map.height() {
+                for x in 0..map.width() {
+                    map.set(x, y, z, rng.gen_bool(0.3));
+                }
+            }
+        }
+        let mut pool = VoxelPool::new(map.width(), map.height(), map.depth());
+        let mut owner = Owner::new();
+        let connectivities = [
+            VoxelConnectivity::Six,
+            VoxelConnectivity::Eighteen,
+            VoxelConnectivity::TwentySix,
+        ];
+        for &connectivity in &connectivities {
+            for _ in 0..30 {
+                let mut voxel = || {
+                    (
+                        rng.gen_range(0..map.width()),
+                        rng.gen_range(0..map.height()),
+                        rng.gen_range(0..map.depth()),
+                    )
+                };
+                let (from, to) = (voxel(), voxel());
+                let mut search = |h: &dyn Fn((i32, i32, i32)) -> f64| {
+                    voxel_search(
+                        &mut pool,
+                        &mut owner,
+                        &mut VoxelNeighbors::new(&map, connectivity),
+                        h,
+                        from,
+                        to,
+                    );
+                    pool.get(to.0, to.1, to.2, &owner)
+                        .map(|n| owner.ro(n).g)
+                        .filter(|g| g.is_finite())
+                };
+                let expected = search(&zero_heuristic());
+                let mut actual = vec![
+                    search(&euclidean_heuristic_3d(to, 1.0)),
+                    search(&octile_heuristic_3d(to, 1.0)),
+                ];
+                if connectivity == VoxelConnectivity::Six {
+                    actual.push(search(&manhattan_heuristic_3d(to, 1.0)));
+                }
+                for actual in actual {
+                    assert_same_cost(actual, expected);
+                }
+            }
+        }
+    }
+}
@@ -1 +1,325 @@
-pub mod avg_four;
\ No newline at end of file
+use std::marker::PhantomData;
+
+use crate::domains::WeightedGrid;
+use crate::util::{manhattan_heuristic, octile_heuristic, Cost, GridDomain};
+use crate::{Edge, ExpansionPolicy, SearchNode};
+
+pub mod avg_four;
+pub mod destination;
+pub mod jpsw;
+pub mod max;
+pub mod source;
+
+/// The set of moves available from each cell.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Connectivity {
+    /// Only orthogonal moves.
+    Four,
+    /// Orthogonal and diagonal moves. Diagonal moves may not cut corners.
+    Eight,
+}
+
+impl Connectivity {
+    /// Returns a heuristic which is admissible for any cost model where a move costs at least
+    /// `min_cost` times its length.
+    pub fn heuristic(self, goal: (i32, i32), min_cost: f64) -> impl Fn((i32, i32)) -> f64 {
+        let manhattan = manhattan_heuristic(goal, min_cost);
+        let octile = octile_heuristic(goal, min_cost);
+        move |v| match self {
+            Connectivity::Four => manhattan(v),
+            Connectivity::Eight => octile(v),
+        }
+    }
+}
+
+/// The smallest cost of any traversable cell, or 0 if there are no traversable cells.
+pub fn min_cost<T: Cost>(map: &WeightedGrid<T>) -> f64 {
+    let mut min = f64::INFINITY;
+    for y in 0..map.height() {
+        for x in 0..map.width() {
+            if let Some(c) = map.get(x, y) {
+                min = min.min(c.cost());
+            }
+        }
+    }
+    if min.is_finite() {
+        min
+    } else {
+        0.0
+    }
+}
+
+/// How the cost of a move is derived from the costs of the cells it touches. Each move costs its
+/// length times a value between the smallest and largest cost of the cells it touches, which is
+/// what makes `WeightedGridPolicy::heuristic` admissible.
+pub trait CostModel {
+    /// The cost of an orthogonal move, given the costs of the source and destination cells.
+    fn orthogonal(source: f64, destination: f64) -> f64;
+
+    /// The cost of a diagonal move, given the costs of the source cell, the two orthogonally
+    /// adjacent cells the move passes between, and the destination cell.
+    fn diagonal(source: f64, a: f64, b: f64, destination: f64) -> f64;
+}
+
+/// Expands the cells of a `WeightedGrid` with the move costs given by the cost model `M`.
+/// Obstructed cells have no edges.
+pub struct WeightedGridPolicy<'a, T, M> {
+    map: &'a WeightedGrid<T>,
+    connectivity: Connectivity,
+    _model: PhantomData<M>,
+}
+
+impl<T, M> WeightedGridPolicy<'_, T, M> {
+    pub fn new(map: &WeightedGrid<T>) -> WeightedGridPolicy<T, M> {
+        WeightedGridPolicy::with_connectivity(map, Connectivity::Eight)
+    }
+
+    pub fn with_connectivity(
+        map: &WeightedGrid<T>,
+        connectivity: Connectivity,
+    ) -> WeightedGridPolicy<T, M> {
+        WeightedGridPolicy {
+            map,
+            connectivity,
+            _model: PhantomData,
+        }
+    }
+
+    pub fn connectivity(&self) -> Connectivity {
+        self.connectivity
+    }
+}
+
+impl<T: Cost, M> WeightedGridPolicy<'_, T, M> {
+    /// Returns an admissible heuristic for this cost model.
+    ///
+    /// note: this scans the map for the minimum cell cost.
+    pub fn heuristic(&self, goal: (i32, i32)) -> impl Fn((i32, i32)) -> f64 {
+        self.connectivity.heuristic(goal, min_cost(self.map))
+    }
+}
+
+unsafe impl<T, M> GridDomain for WeightedGridPolicy<'_, T, M> {
+    fn width(&self) -> i32 {
+        self.map.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.map.height()
+    }
+}
+
+impl<T: Cost, M: CostModel> ExpansionPolicy<(i32, i32)> for WeightedGridPolicy<'_, T, M> {
+    fn expand(&mut self, node: &SearchNode<(i32, i32)>, edges: &mut Vec<Edge<(i32, i32)>>) {
+        self.map.get_neighborhood(node.id.0, node.id.1);
+        unsafe {
+            // SAFETY: bounds are checked by above call
+            self.expand_unchecked(node, edges)
+        }
+    }
+
+    unsafe fn expand_unchecked(
+        &mut self,
+        node: &SearchNode<(i32, i32)>,
+        edges: &mut Vec<Edge<(i32, i32)>>,
+    ) {
+        let (x, y) = node.id;
+        let nbs = self.map.get_neighborhood_unchecked(x, y);
+        let c = match nbs.c {
+            Some(c) => c.cost(),
+            None => return,
+        };
+        let n = nbs.n.map(Cost::cost);
+        let s = nbs.s.map(Cost::cost);
+        let w = nbs.w.map(Cost::cost);
+        let e = nbs.e.map(Cost::cost);
+
+        for &(cost, destination) in &[
+            (n, (x, y - 1)),
+            (s, (x, y + 1)),
+            (w, (x - 1, y)),
+            (e, (x + 1, y)),
+        ] {
+            if let Some(cost) = cost {
+                edges.push(Edge {
+                    destination,
+                    cost: M::orthogonal(c, cost),
+                });
+            }
+        }
+
+        if self.connectivity == Connectivity::Four {
+            return;
+        }
+
+        let nw = nbs.nw.map(Cost::cost);
+        let ne = nbs.ne.map(Cost::cost);
+        let sw = nbs.sw.map(Cost::cost);
+        let se = nbs.se.map(Cost::cost);
+        for &(a, b, d, destination) in &[
+            (n, w, nw, (x - 1, y - 1)),
+            (n, e, ne, (x + 1, y - 1)),
+            (s, w, sw, (x - 1, y + 1)),
+            (s, e, se, (x + 1, y + 1)),
+        ] {
+            if let (Some(a), Some(b), Some(d)) = (a, b, d) {
+                edges.push(Edge {
+                    destination,
+                    cost: M::diagonal(c, a, b, d),
+                });
+            }
+        }
+    }
+}
+
+/// Mostly uniform terrain with a few rectangular patches of other costs and some obstacles.
+#[cfg(test)]
+pub(crate) fn random_map(rng: &mut rand_pcg::Pcg64) -> WeightedGrid<u8> {
+    use rand::prelude::*;
+
+    let mut map = WeightedGrid::new(rng.gen_range(20..80), rng.gen_range(20..80));
+    for y in 0..map.height() {
+        for x in 0..map.width() {
+            *map.get_mut(x, y) = Some(3);
+        }
+    }
+    for _ in 0..rng.gen_range(0..6) {
+        let cost = [None, Some(1), Some(2), Some(7)]
+            .choose(rng)
+            .copied()
+            .unwrap();
+        let x = rng.gen_range(0..map.width());
+        let y = rng.gen_range(0..map.height());
+        let w = rng.gen_range(1..12).min(map.width() - x);
+        let h = rng.gen_range(1..12).min(map.height() - y);
+        for y in y..y + h {
+            for x in x..x + w {
+                *map.get_mut(x, y) = cost;
+            }
+        }
+    }
+    for _ in 0..rng.gen_range(0..20) {
+        let x = rng.gen_range(0..map.width());
+        let y = rng.gen_range(0..map.height());
+        *map.get_mut(x, y) = None;
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::avg_four::AverageOfFour;
+    use super::destination::DestinationCost;
+    use super::max::MaxCost;
+    use super::source::SourceCost;
+    use super::*;
+    use crate::node_pool::GridPool;
+    use crate::util::{assert_same_cost, grid_search, zero_heuristic};
+    use crate::Owner;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    fn check_optimal<E: ExpansionPolicy<(i32, i32)> + GridDomain>(
+        rng: &mut Pcg64,
+        map: &WeightedGrid<u8>,
+        mut policy: impl FnMut() -> E,
+        connectivity: Connectivity,
+    ) {
+        let mut pool = GridPool::new(map.width(), map.height());
+        let mut owner = Owner::new();
+        let min = min_cost(map);
+        for _ in 0..50 {
+            let from = (
+                rng.gen_range(0..map.width()),
+                rng.gen_range(0..map.height()),
+            );
+            let to = (
+                rng.gen_range(0..map.width()),
+                rng.gen_range(0..map.height()),
+            );
+            grid_search(
+                &mut pool,
+                &mut owner,
+                &mut policy(),
+                zero_heuristic(),
+                from,
+                to,
+            );
+            let expected = pool.get(to.0, to.1, &owner).map(|n| owner.ro(n).g);
+            let h = connectivity.heuristic(to, min);
+            if let Some(g) = expected {
+                assert!(h(from) <= g + 1e-9);
+            }
+            grid_search(&mut pool, &mut owner, &mut policy(), h, from, to);
+            let actual = pool.get(to.0, to.1, &owner).map(|n| owner.ro(n).g);
+            assert_same_cost(actual, expected);
+        }
+    }
+
+    #[test]
+    fn check_heuristics_optimal() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let map = random_map(&mut rng);
+        for &c in &[Connectivity::Four, Connectivity::Eight] {
+            check_optimal(
+                &mut rng,
+                &map,
+                || AverageOfFour::with_connectivity(&map, c),
+                c,
+            );
+            check_optimal(
+                &mut rng,
+                &map,
+                || DestinationCost::with_connectivity(&map, c),
+                c,
+            );
+            check_optimal(&mut rng, &map, || SourceCost::with_connectivity(&map, c), c);
+            check_optimal(&mut rng, &map, || MaxCost::with_connectivity(&map, c), c);
+        }
+    }
+
+    #[test]
+    fn check_edges() {
+        let mut map = WeightedGrid::new(3, 3);
+        for y in 0..3 {
+            for x in 0..3 {
+                *map.get_mut(x, y) = Some(1 + x as u8 + 3 * y as u8);
+            }
+        }
+        *map.get_mut(0, 0) = None;
+        let node = |id| SearchNode {
+            search_num: 0,
+            pqueue_location: 0,
+            expansions: 0,
+            id,
+            parent: None,
+            g: 0.0,
+            lb: 0.0,
+        };
+
+        let mut edges = vec![];
+        DestinationCost::new(&map).expand(&node((1, 1)), &mut edges);
+        assert_eq!(edges.len(), 7);
+        assert!(edges.contains(&Edge {
+            destination: (1, 0),
+            cost: 2.0
+        }));
+        assert!(edges.contains(&Edge {
+            destination: (2, 2),
+            cost: 9.0 * std::f64::consts::SQRT_2
+        }));
+
+        edges.clear();
+        MaxCost::with_connectivity(&map, Connectivity::Four).expand(&node((1, 1)), &mut edges);
+        assert_eq!(edges.len(), 4);
+        assert!(edges.contains(&Edge {
+            destination: (0, 1),
+            cost: 5.0
+        }));
+
+        edges.clear();
+        SourceCost::new(&map).expand(&node((0, 0)), &mut edges);
+        assert!(edges.is_empty());
+    }
+}
@@ -1,113 +1,20 @@
 use std::f64::consts::SQRT_2;
 
-use crate::domains::WeightedGrid;
-use crate::util::{Cost, GridDomain, Neighborhood};
-use crate::{Edge, ExpansionPolicy, SearchNode};
+use super::{CostModel, WeightedGridPolicy};
 
-pub struct AverageOfFour<'a, T>(&'a WeightedGrid<T>);
+/// Each move costs the average of the costs of the cells it touches, times its length. A diagonal
+/// move touches all four cells of the square it crosses.
+pub type AverageOfFour<'a, T> = WeightedGridPolicy<'a, T, AverageOfFourModel>;
 
-impl<T> AverageOfFour<'_, T> {
-    pub fn new(map: &WeightedGrid<T>) -> AverageOfFour<T> {
-        AverageOfFour(map)
-    }
-}
-
-unsafe impl<T> GridDomain for AverageOfFour<'_, T> {
-    fn width(&self) -> i32 {
-        self.0.width()
-    }
-
-    fn height(&self) -> i32 {
-        self.0.height()
-    }
-}
-
-impl<T: Cost> ExpansionPolicy<(i32, i32)> for AverageOfFour<'_, T> {
-    fn expand(&mut self, node: &SearchNode<(i32, i32)>, edges: &mut Vec<Edge<(i32, i32)>>) {
-        self.0.get_neighborhood(node.id.0, node.id.1);
-        unsafe {
-            // SAFETY: bounds are checked by above call
-            self.expand_unchecked(node, edges)
-        }
-    }
-
-    unsafe fn expand_unchecked(
-        &mut self,
-        node: &SearchNode<(i32, i32)>,
-        edges: &mut Vec<Edge<(i32, i32)>>,
-    ) {
-        let &mut AverageOfFour(map) = self;
-        let neighborhood = map.get_neighborhood_unchecked(node.id.0, node.id.1);
-        let c = neighborhood.c.map(Cost::cost).unwrap();
-        if let Some(cost) = neighborhood.n.map(Cost::cost) {
-            edges.push(Edge {
-                destination: (node.id.0, node.id.1 - 1),
-                cost: (cost + c) / 2.0,
-            });
-        }
-        if let Some(cost) = neighborhood.s.map(Cost::cost) {
-            edges.push(Edge {
-                destination: (node.id.0, node.id.1 + 1),
-                cost: (cost + c) / 2.0,
-            });
-        }
-        if let Some(cost) = neighborhood.w.map(Cost::cost) {
-            edges.push(Edge {
-                destination: (node.id.0 - 1, node.id.1),
-                cost: (cost + c) / 2.0,
-            });
-        }
-        if let Some(cost) = neighborhood.e.map(Cost::cost) {
-            edges.push(Edge {
-                destination: (node.id.0 + 1, node.id.1),
-                cost: (cost + c) / 2.0,
-            });
-        }
-        if let Some(cost) = neighborhood.nw_cost() {
-            edges.push(Edge {
-                destination: (node.id.0 - 1, node.id.1 - 1),
-                cost,
-            });
-        }
-        if let Some(cost) = neighborhood.ne_cost() {
-            edges.push(Edge {
-                destination: (node.id.0 + 1, node.id.1 - 1),
-                cost,
-            });
-        }
-        if let Some(cost) = neighborhood.sw_cost() {
-            edges.push(Edge {
-                destination: (node.id.0 - 1, node.id.1 + 1),
-                cost,
-            });
-        }
-        if let Some(cost) = neighborhood.se_cost() {
-            edges.push(Edge {
-                destination: (node.id.0 + 1, node.id.1 + 1),
-                cost,
-            });
-        }
-    }
-}
-
-impl<T: Cost> Neighborhood<Option<&T>> {
-    fn nw_cost(&self) -> Option<f64> {
-        let c = self.c?.cost();
-        let n = self.n?.cost();
-        let w = self.w?.cost();
-        let nw = self.nw?.cost();
-        Some((c + n + w + nw) * SQRT_2 / 4.0)
-    }
-
-    fn sw_cost(&self) -> Option<f64> {
-        self.rotate_cw().nw_cost()
-    }
+/// The cost model of `AverageOfFour`.
+pub enum AverageOfFourModel {}
 
-    fn se_cost(&self) -> Option<f64> {
-        self.rotate_cw().sw_cost()
+impl CostModel for AverageOfFourModel {
+    fn orthogonal(source: f64, destination: f64) -> f64 {
+        (source + destination) / 2.0
     }
 
-    fn ne_cost(&self) -> Option<f64> {
-        self.rotate_cw().se_cost()
+    fn diagonal(source: f64, a: f64, b: f64, destination: f64) -> f64 {
+        (source + a + b + destination) * SQRT_2 / 4.0
     }
 }
@@ -0,0 +1,19 @@
+use std::f64::consts::SQRT_2;
+
+use super::{CostModel, WeightedGridPolicy};
+
+/// Each move costs the cost of the cell it enters, times its length.
+pub type DestinationCost<'a, T> = WeightedGridPolicy<'a, T, DestinationCostModel>;
+
+/// The cost model of `DestinationCost`.
+pub enum DestinationCostModel {}
+
+impl CostModel for DestinationCostModel {
+    fn orthogonal(_: f64, destination: f64) -> f64 {
+        destination
+    }
+
+    fn diagonal(_: f64, _: f64, _: f64, destination: f64) -> f64 {
+        destination * SQRT_2
+    }
+}
@@ -0,0 +1,384 @@
+use std::f64::consts::SQRT_2;
+
+use crate::domains::{BitGrid, WeightedGrid};
+use crate::expansion_policy::bitgrid::jps::{canonical_successors, create_tmap, get_direction};
+use crate::node_pool::GridPool;
+use crate::util::{Cost, Direction, GridDomain};
+use crate::{astar_unchecked, Edge, ExpansionPolicy, Owner, SearchNode};
+
+/// Jump point search over the `AverageOfFour` cost model of a `WeightedGrid`.
+///
+/// Within regions of equal cost, the usual jump point pruning rules apply. Cells which are adjacent
+/// to a traversable cell of a different cost stop jumps and have all of their neighbors generated,
+/// so that paths are free to bend wherever the cost changes.
+pub struct JpswExpansionPolicy<'a, T> {
+    costs: &'a WeightedGrid<T>,
+    map: BitGrid,
+    tmap: BitGrid,
+    boundaries: BitGrid,
+    tboundaries: BitGrid,
+    goal: (i32, i32),
+}
+
+impl<'a, T: Cost> JpswExpansionPolicy<'a, T> {
+    /// note: this builds obstacle and cost boundary bitmaps of the entire map, so the policy should
+    ///       be reused between searches.
+    pub fn new(costs: &'a WeightedGrid<T>) -> Self {
+        let mut map = BitGrid::new(costs.width(), costs.height());
+        let mut boundaries = BitGrid::new(costs.width(), costs.height());
+        for y in 0..costs.height() {
+            for x in 0..costs.width() {
+                let c = match costs.get(x, y) {
+                    Some(c) => c.cost(),
+                    None => {
+                        map.set(x, y, true);
+                        continue;
+                    }
+                };
+                let nbs = costs.get_neighborhood(x, y);
+                let boundary = [nbs.nw, nbs.n, nbs.ne, nbs.w, nbs.e, nbs.sw, nbs.s, nbs.se]
+                    .iter()
+                    .any(|nb| matches!(nb, Some(nb) if nb.cost() != c));
+                boundaries.set(x, y, boundary);
+            }
+        }
+
+        JpswExpansionPolicy {
+            costs,
+            tmap: create_tmap(&map),
+            tboundaries: create_tmap(&boundaries),
+            map,
+            boundaries,
+            goal: (-1, -1),
+        }
+    }
+
+    pub fn set_goal(&mut self, new_goal: (i32, i32)) {
+        self.goal = new_goal;
+    }
+
+    pub fn search(
+        &mut self,
+        pool: &mut GridPool,
+        owner: &mut Owner,
+        h: impl FnMut((i32, i32)) -> f64,
+        source: (i32, i32),
+        goal: (i32, i32),
+    ) {
+        assert!(pool.width() >= self.map.width());
+        assert!(pool.height() >= self.map.height());
+        self.map.get_neighbors(source.0, source.1);
+        self.goal = goal;
+        unsafe {
+            // SAFETY: We check that the pool is large enough for our map.
+            //         Our implementation never produces edges to cells that are out-of-bounds.
+            //         We check that the source cell is in-bounds.
+            astar_unchecked(pool, owner, self, h, source, goal)
+        }
+    }
+
+    /// SAFETY: x and y must be in-bounds of the map. Exactly one of dx and dy must be nonzero, and
+    ///         it must be 1 or -1.
+    #[inline(always)]
+    unsafe fn jump_ortho_unchecked(&self, x: i32, y: i32, dx: i32, dy: i32) -> Result<i32, bool> {
+        let (goal_x, goal_y) = self.goal;
+        // SAFETY: The bitmaps and their transposes are constructed with matching dimensions.
+        match (dx, dy) {
+            (1, 0) => jump_plus_unchecked(&self.map, &self.boundaries, x, y, goal_x, goal_y),
+            (-1, 0) => jump_minus_unchecked(&self.map, &self.boundaries, x, y, goal_x, goal_y),
+            (0, 1) => jump_plus_unchecked(&self.tmap, &self.tboundaries, y, x, goal_y, goal_x),
+            (0, -1) => jump_minus_unchecked(&self.tmap, &self.tboundaries, y, x, goal_y, goal_x),
+            _ => unreachable!(),
+        }
+    }
+
+    /// SAFETY: x and y must be in-bounds of the map. dx and dy must each be 1 or -1, and the first
+    ///         diagonal step must not cut a corner.
+    #[inline(always)]
+    unsafe fn jump_diagonal_unchecked(&self, x: i32, y: i32, dx: i32, dy: i32) -> Option<i32> {
+        let mut distance = 0;
+        loop {
+            distance += 1;
+            let (nx, ny) = (x + dx * distance, y + dy * distance);
+
+            // SAFETY: Since x and y are in-bounds of the map and we stop when we get to an
+            //         obstruction (e.g. the padding 1s around the map), nx and ny will always be
+            //         in-bounds.
+            if self.map.get_unchecked(nx, ny) {
+                return None;
+            }
+            if self.boundaries.get_unchecked(nx, ny) || (nx, ny) == self.goal {
+                return Some(distance);
+            }
+
+            let mut done = false;
+            match self.jump_ortho_unchecked(nx, ny, dx, 0) {
+                Ok(_) => return Some(distance),
+                Err(d) => done |= d,
+            }
+            match self.jump_ortho_unchecked(nx, ny, 0, dy) {
+                Ok(_) => return Some(distance),
+                Err(d) => done |= d,
+            }
+            if done {
+                return None;
+            }
+        }
+    }
+}
+
+unsafe impl<T> GridDomain for JpswExpansionPolicy<'_, T> {
+    fn width(&self) -> i32 {
+        self.map.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.map.height()
+    }
+}
+
+impl<T: Cost> ExpansionPolicy<(i32, i32)> for JpswExpansionPolicy<'_, T> {
+    unsafe fn expand_unchecked(
+        &mut self,
+        node: &SearchNode<(i32, i32)>,
+        edges: &mut Vec<Edge<(i32, i32)>>,
+    ) {
+        let (x, y) = node.id;
+        // SAFETY: The caller is responsible for upholding the requirement that the node id is
+        //         in-bounds of the map.
+        let nbs = self.costs.get_neighborhood_unchecked(x, y);
+        let c = match nbs.c {
+            Some(c) => c.cost(),
+            None => return,
+        };
+
+        // jump point pruning is only valid where every traversable cell nearby costs the same
+        let dir = match self.boundaries.get_unchecked(x, y) {
+            true => None,
+            false => get_direction(node.id, node.parent),
+        };
+        let successors = canonical_successors(&self.map, node.id, dir);
+
+        // note: a jump longer than one step passes through a cell whose traversable neighbors all
+        //       cost the same, so every cell touched by the jump has the cost of the first cell.
+        let ortho = [
+            (Direction::North, 0, -1, nbs.n),
+            (Direction::South, 0, 1, nbs.s),
+            (Direction::West, -1, 0, nbs.w),
+            (Direction::East, 1, 0, nbs.e),
+        ];
+        for &(direction, dx, dy, nb) in &ortho {
+            if !successors.contains(direction) {
+                continue;
+            }
+            if let Ok(d) = self.jump_ortho_unchecked(x, y, dx, dy) {
+                let nb = nb.unwrap().cost();
+                edges.push(Edge {
+                    destination: (x + dx * d, y + dy * d),
+                    cost: if d == 1 {
+                        (c + nb) / 2.0
+                    } else {
+                        d as f64 * nb
+                    },
+                });
+            }
+        }
+
+        let diagonal = [
+            (Direction::NorthWest, -1, -1, nbs.n, nbs.w, nbs.nw),
+            (Direction::NorthEast, 1, -1, nbs.n, nbs.e, nbs.ne),
+            (Direction::SouthWest, -1, 1, nbs.s, nbs.w, nbs.sw),
+            (Direction::SouthEast, 1, 1, nbs.s, nbs.e, nbs.se),
+        ];
+        for &(direction, dx, dy, a, b, nb) in &diagonal {
+            if !successors.contains(direction) {
+                continue;
+            }
+            if let Some(d) = self.jump_diagonal_unchecked(x, y, dx, dy) {
+                let nb = nb.unwrap().cost();
+                let cost = if d == 1 {
+                    (c + a.unwrap().cost() + b.unwrap().cost() + nb) * SQRT_2 / 4.0
+                } else {
+                    d as f64 * nb * SQRT_2
+                };
+                edges.push(Edge {
+                    destination: (x + dx * d, y + dy * d),
+                    cost,
+                });
+            }
+        }
+    }
+
+    fn expand(&mut self, node: &SearchNode<(i32, i32)>, edges: &mut Vec<Edge<(i32, i32)>>) {
+        self.map.get_neighbors(node.id.0, node.id.1);
+        unsafe {
+            // SAFETY: The above get_neighbors call does the relevant bounds check for us.
+            self.expand_unchecked(node, edges)
+        }
+    }
+}
+
+/// SAFETY: x and y must be in-bounds of the map, and boundaries must have the same dimensions.
+#[inline(always)]
+unsafe fn jump_plus_unchecked(
+    map: &BitGrid,
+    boundaries: &BitGrid,
+    x: i32,
+    y: i32,
+    goal_x: i32,
+    goal_y: i32,
+) -> Result<i32, bool> {
+    let mut distance = 0;
+    loop {
+        // SAFETY: Since y is in-bounds of the map and get_row_unchecked has 1 cell padding, the
+        //         y parameter is in-bounds.
+        // SAFETY: Since we stop jumping when we see the first 1 bit and the map is padded with 1s,
+        //         x + distance will never go off the right side of the map and will be in-bounds.
+        let bits_above = map.get_row_unchecked(x + distance, y - 1);
+        let bits = map.get_row_unchecked(x + distance, y);
+        let bits_below = map.get_row_unchecked(x + distance, y + 1);
+        // the cell the jump starts from never stops it
+        let boundary = boundaries.get_row_unchecked(x + distance, y) & !1;
+
+        let forced_above = (bits_above << 1) & !bits_above;
+        let forced_below = (bits_below << 1) & !bits_below;
+        let stop = (forced_above | forced_below | bits | boundary) & !0 >> 7;
+
+        if stop != 0 {
+            let stop = stop.trailing_zeros();
+            distance += stop as i32;
+
+            // the goal can't be on the obstruction the jump stopped at
+            let blocked = bits & 1 << stop != 0;
+            let reach = if blocked { distance - 1 } else { distance };
+            if y == goal_y && x <= goal_x && goal_x <= x + reach {
+                return Ok(goal_x - x);
+            }
+
+            if blocked {
+                return Err(distance <= 1);
+            } else {
+                return Ok(distance);
+            }
+        }
+
+        distance += 56;
+    }
+}
+
+/// SAFETY: x and y must be in-bounds of the map, and boundaries must have the same dimensions.
+#[inline(always)]
+unsafe fn jump_minus_unchecked(
+    map: &BitGrid,
+    boundaries: &BitGrid,
+    x: i32,
+    y: i32,
+    goal_x: i32,
+    goal_y: i32,
+) -> Result<i32, bool> {
+    let mut distance = 0;
+    loop {
+        // SAFETY: Since y is in-bounds of the map and get_row_upper_unchecked has 1 cell padding,
+        //         the y parameter is in-bounds.
+        // SAFETY: Since we stop jumping when we see the first 1 bit and the map is padded with 1s,
+        //         x - distance will never go off the left side of the map and will be in-bounds.
+        let bits_above = map.get_row_upper_unchecked(x - distance, y - 1);
+        let bits = map.get_row_upper_unchecked(x - distance, y);
+        let bits_below = map.get_row_upper_unchecked(x - distance, y + 1);
+        // the cell the jump starts from never stops it
+        let boundary = boundaries.get_row_upper_unchecked(x - distance, y) & !(1 << 63);
+
+        let forced_above = (bits_above >> 1) & !bits_above;
+        let forced_below = (bits_below >> 1) & !bits_below;
+        let stop = (forced_above | forced_below | bits | boundary) & !0 << 7;
+
+        if stop != 0 {
+            let stop = stop.leading_zeros();
+            distance += stop as i32;
+
+            // the goal can't be on the obstruction the jump stopped at
+            let blocked = bits & (1 << 63) >> stop != 0;
+            let reach = if blocked { distance - 1 } else { distance };
+            if y == goal_y && x - reach <= goal_x && goal_x <= x {
+                return Ok(x - goal_x);
+            }
+
+            if blocked {
+                return Err(distance <= 1);
+            } else {
+                return Ok(distance);
+            }
+        }
+
+        distance += 56;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expansion_policy::weighted_grid::avg_four::AverageOfFour;
+    use crate::expansion_policy::weighted_grid::{min_cost, random_map};
+    use crate::util::{assert_same_cost, grid_search, octile_heuristic};
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    fn total_expansions(pool: &GridPool, owner: &Owner) -> usize {
+        let mut expansions = 0;
+        for y in 0..pool.height() {
+            for x in 0..pool.width() {
+                if let Some(n) = pool.get(x, y, owner) {
+                    expansions += owner.ro(n).expansions;
+                }
+            }
+        }
+        expansions
+    }
+
+    #[test]
+    fn check_matches_avg_four() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let mut owner = Owner::new();
+        let mut astar_expansions = 0;
+        let mut jpsw_expansions = 0;
+        for _ in 0..30 {
+            let map = random_map(&mut rng);
+            let min = min_cost(&map);
+            let mut pool = GridPool::new(map.width(), map.height());
+            let mut jpsw = JpswExpansionPolicy::new(&map);
+            for _ in 0..30 {
+                let from = (
+                    rng.gen_range(0..map.width()),
+                    rng.gen_range(0..map.height()),
+                );
+                let to = (
+                    rng.gen_range(0..map.width()),
+                    rng.gen_range(0..map.height()),
+                );
+                if map.get(from.0, from.1).is_none() {
+                    continue;
+                }
+
+                grid_search(
+                    &mut pool,
+                    &mut owner,
+                    &mut AverageOfFour::new(&map),
+                    octile_heuristic(to, min),
+                    from,
+                    to,
+                );
+                let expected = pool.get(to.0, to.1, &owner).map(|n| owner.ro(n).g);
+                astar_expansions += total_expansions(&pool, &owner);
+
+                jpsw.search(&mut pool, &mut owner, octile_heuristic(to, min), from, to);
+                let actual = pool.get(to.0, to.1, &owner).map(|n| owner.ro(n).g);
+                jpsw_expansions += total_expansions(&pool, &owner);
+
+                // unreachable cells may be generated without being reached
+                let reached = |g: Option<f64>| g.filter(|g| g.is_finite());
+                assert_same_cost(reached(actual), reached(expected));
+            }
+        }
+        assert!(jpsw_expansions * 2 < astar_expansions);
+    }
+}
@@ -0,0 +1,20 @@
+use std::f64::consts::SQRT_2;
+
+use super::{CostModel, WeightedGridPolicy};
+
+/// Each move costs the largest cost of the cells it touches, times its length. A diagonal move
+/// touches all four cells of the square it crosses.
+pub type MaxCost<'a, T> = WeightedGridPolicy<'a, T, MaxCostModel>;
+
+/// The cost model of `MaxCost`.
+pub enum MaxCostModel {}
+
+impl CostModel for MaxCostModel {
+    fn orthogonal(source: f64, destination: f64) -> f64 {
+        source.max(destination)
+    }
+
+    fn diagonal(source: f64, a: f64, b: f64, destination: f64) -> f64 {
+        source.max(a).max(b).max(destination) * SQRT_2
+    }
+}
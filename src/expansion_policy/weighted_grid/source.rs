@@ -0,0 +1,19 @@
+use std::f64::consts::SQRT_2;
+
+use super::{CostModel, WeightedGridPolicy};
+
+/// Each move costs the cost of the cell it leaves, times its length.
+pub type SourceCost<'a, T> = WeightedGridPolicy<'a, T, SourceCostModel>;
+
+/// The cost model of `SourceCost`.
+pub enum SourceCostModel {}
+
+impl CostModel for SourceCostModel {
+    fn orthogonal(source: f64, _: f64) -> f64 {
+        source
+    }
+
+    fn diagonal(source: f64, _: f64, _: f64, _: f64) -> f64 {
+        source * SQRT_2
+    }
+}
@@ -0,0 +1,418 @@
+//! Image export of grid maps and search state, as PPM, PGM or SVG.
+//!
+//! Images are built per cell and scaled up when written, so one cell becomes a `scale` by `scale`
+//! square of pixels.
+
+use std::io::{self, Write};
+
+use crate::domains::{BitGrid, WeightedGrid};
+use crate::node_pool::GridPool;
+use crate::util::GridDomain;
+use crate::{Edge, Owner, SearchNode};
+
+pub type Rgb = [u8; 3];
+
+pub const FREE: Rgb = [255, 255, 255];
+pub const OBSTRUCTED: Rgb = [32, 32, 32];
+pub const PATH: Rgb = [0, 160, 0];
+pub const OPEN: Rgb = [140, 190, 255];
+pub const CLOSED: Rgb = [255, 190, 120];
+
+/// A grid of cell colors, with an optional path drawn over it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapImage {
+    width: i32,
+    height: i32,
+    cells: Box<[Rgb]>,
+    path: Vec<(i32, i32)>,
+}
+
+impl MapImage {
+    /// Constructs an image with every cell set to `color`.
+    pub fn new(width: i32, height: i32, color: Rgb) -> Self {
+        assert!(width > 0 && height > 0, "width and height must be positive");
+        MapImage {
+            width,
+            height,
+            cells: vec![color; width as usize * height as usize].into_boxed_slice(),
+            path: vec![],
+        }
+    }
+
+    pub fn from_bitgrid(map: &BitGrid) -> Self {
+        let mut image = MapImage::new(map.width(), map.height(), FREE);
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                if map.get(x, y) {
+                    image.set(x, y, OBSTRUCTED);
+                }
+            }
+        }
+        image
+    }
+
+    /// Draws unobstructed cells in shades of gray, from white for the lowest weight to mid gray for
+    /// the highest weight.
+    pub fn from_weighted_grid<V: Copy + Into<f64>>(map: &WeightedGrid<V>) -> Self {
+        let weight = |x, y| map.get(x, y).map(|&w| w.into());
+        let (mut lo, mut hi) = (f64::INFINITY, f64::NEG_INFINITY);
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                if let Some(w) = weight(x, y) {
+                    lo = lo.min(w);
+                    hi = hi.max(w);
+                }
+            }
+        }
+        let mut image = MapImage::new(map.width(), map.height(), OBSTRUCTED);
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                if let Some(w) = weight(x, y) {
+                    image.set(x, y, lerp(FREE, [128; 3], normalize(w, lo, hi)));
+                }
+            }
+        }
+        image
+    }
+
+    #[inline(always)]
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    #[track_caller]
+    pub fn get(&self, x: i32, y: i32) -> Rgb {
+        self.cells[self.locate(x, y)]
+    }
+
+    #[track_caller]
+    pub fn set(&mut self, x: i32, y: i32, color: Rgb) {
+        let idx = self.locate(x, y);
+        self.cells[idx] = color;
+    }
+
+    /// Colors the nodes generated by the last search using the pool by their g-values, from blue
+    /// for the lowest to red for the highest.
+    pub fn overlay_g_values(&mut self, pool: &GridPool, owner: &Owner) {
+        self.overlay_nodes(
+            pool,
+            owner,
+            |n| Some(n.g).filter(|g| g.is_finite()),
+            [0, 0, 255],
+        );
+    }
+
+    /// Colors the nodes expanded by the last search using the pool by how many times they were
+    /// expanded, from yellow for once to red for the most.
+    pub fn overlay_expansions(&mut self, pool: &GridPool, owner: &Owner) {
+        self.overlay_nodes(
+            pool,
+            owner,
+            |n| Some(n.expansions as f64).filter(|&e| e > 0.0),
+            [255, 255, 0],
+        );
+    }
+
+    /// Draws the path over the image. Cells on the path are colored, and in SVG output the path is
+    /// also drawn as a line through the cell centers.
+    pub fn overlay_path(&mut self, path: &[(i32, i32)]) {
+        for &(x, y) in path {
+            self.set(x, y, PATH);
+        }
+        self.path = path.to_vec();
+    }
+
+    /// Writes the image as a binary PPM (P6).
+    pub fn write_ppm(&self, mut w: impl Write, scale: u32) -> io::Result<()> {
+        let (width, height) = self.scaled_size(scale);
+        write!(w, "P6\n{} {}\n255\n", width, height)?;
+        self.write_pixels(w, scale, |c| c)
+    }
+
+    /// Writes the image as a binary PGM (P5), converting colors to luma.
+    pub fn write_pgm(&self, mut w: impl Write, scale: u32) -> io::Result<()> {
+        let (width, height) = self.scaled_size(scale);
+        write!(w, "P5\n{} {}\n255\n", width, height)?;
+        self.write_pixels(w, scale, |[r, g, b]| {
+            let luma = 0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64;
+            [luma.round() as u8; 1]
+        })
+    }
+
+    /// Writes the image as SVG, with each cell `scale` units wide.
+    pub fn write_svg(&self, mut w: impl Write, scale: u32) -> io::Result<()> {
+        let (width, height) = self.scaled_size(scale);
+        writeln!(
+            w,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" shape-rendering="crispEdges">"#,
+            width, height
+        )?;
+        for y in 0..self.height {
+            // one rect per run of same-colored cells
+            let mut x = 0;
+            while x < self.width {
+                let color = self.get(x, y);
+                let mut end = x + 1;
+                while end < self.width && self.get(end, y) == color {
+                    end += 1;
+                }
+                writeln!(
+                    w,
+                    r##"<rect x="{}" y="{}" width="{}" height="{}" fill="#{:02x}{:02x}{:02x}"/>"##,
+                    x as u32 * scale,
+                    y as u32 * scale,
+                    (end - x) as u32 * scale,
+                    scale,
+                    color[0],
+                    color[1],
+                    color[2]
+                )?;
+                x = end;
+            }
+        }
+        if !self.path.is_empty() {
+            let center = |v: i32| v as f64 * scale as f64 + scale as f64 / 2.0;
+            let points: Vec<_> = self
+                .path
+                .iter()
+                .map(|&(x, y)| format!("{},{}", center(x), center(y)))
+                .collect();
+            writeln!(
+                w,
+                r##"<polyline points="{}" fill="none" stroke="#006000" stroke-width="{}" stroke-linejoin="round"/>"##,
+                points.join(" "),
+                (scale as f64 / 4.0).max(1.0)
+            )?;
+        }
+        writeln!(w, "</svg>")
+    }
+
+    fn overlay_nodes(
+        &mut self,
+        pool: &GridPool,
+        owner: &Owner,
+        value: impl Fn(&SearchNode<(i32, i32)>) -> Option<f64>,
+        low: Rgb,
+    ) {
+        let width = self.width.min(pool.width());
+        let height = self.height.min(pool.height());
+        let values: Vec<_> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter_map(|(x, y)| {
+                let node = owner.ro(pool.get(x, y, owner)?);
+                Some(((x, y), value(node)?))
+            })
+            .collect();
+        let lo = values.iter().map(|v| v.1).fold(f64::INFINITY, f64::min);
+        let hi = values.iter().map(|v| v.1).fold(f64::NEG_INFINITY, f64::max);
+        for ((x, y), v) in values {
+            self.set(x, y, lerp(low, [255, 0, 0], normalize(v, lo, hi)));
+        }
+    }
+
+    fn scaled_size(&self, scale: u32) -> (u32, u32) {
+        assert!(scale > 0, "scale must be positive");
+        (self.width as u32 * scale, self.height as u32 * scale)
+    }
+
+    fn write_pixels<const N: usize>(
+        &self,
+        mut w: impl Write,
+        scale: u32,
+        pixel: impl Fn(Rgb) -> [u8; N],
+    ) -> io::Result<()> {
+        let mut row = Vec::with_capacity(self.width as usize * scale as usize * N);
+        for y in 0..self.height {
+            row.clear();
+            for x in 0..self.width {
+                let p = pixel(self.get(x, y));
+                for _ in 0..scale {
+                    row.extend_from_slice(&p);
+                }
+            }
+            for _ in 0..scale {
+                w.write_all(&row)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    fn locate(&self, x: i32, y: i32) -> usize {
+        if !(0..self.width).contains(&x) || !(0..self.height).contains(&y) {
+            panic!("Grid cell ({}, {}) is out of bounds.", x, y);
+        }
+        x as usize + y as usize * self.width as usize
+    }
+}
+
+/// Records frames of a search as it runs, for use with `Observed`:
+///
+/// ```ignore
+/// let mut animation = Animation::new(MapImage::from_bitgrid(&map), 10);
+/// let mut ep = Observed::new(NoCornerCutting::new(&map), |n, e| animation.record(n, e));
+/// grid_search(&mut pool, &mut owner, &mut ep, h, start, goal);
+/// for (i, frame) in animation.finish().iter().enumerate() {
+///     frame.write_ppm(File::create(format!("frame{:04}.ppm", i))?, 4)?;
+/// }
+/// ```
+pub struct Animation {
+    current: MapImage,
+    frames: Vec<MapImage>,
+    interval: usize,
+    /// Expansions since the last frame was recorded.
+    pending: usize,
+}
+
+impl Animation {
+    /// Records a frame every `interval` expansions, drawing over `base`.
+    pub fn new(base: MapImage, interval: usize) -> Self {
+        assert!(interval > 0, "interval must be positive");
+        Animation {
+            frames: vec![base.clone()],
+            current: base,
+            interval,
+            pending: 0,
+        }
+    }
+
+    /// Marks the node as expanded and the destinations of the edges as generated, recording a frame
+    /// if it is due.
+    pub fn record(&mut self, node: &SearchNode<(i32, i32)>, edges: &[Edge<(i32, i32)>]) {
+        for edge in edges {
+            let (x, y) = edge.destination;
+            if self.current.get(x, y) != CLOSED {
+                self.current.set(x, y, OPEN);
+            }
+        }
+        self.current.set(node.id.0, node.id.1, CLOSED);
+        self.pending += 1;
+        if self.pending == self.interval {
+            self.pending = 0;
+            self.frames.push(self.current.clone());
+        }
+    }
+
+    pub fn frames(&self) -> &[MapImage] {
+        &self.frames
+    }
+
+    /// Returns the recorded frames, ending with the current state with `path` drawn over it.
+    pub fn finish(mut self, path: &[(i32, i32)]) -> Vec<MapImage> {
+        self.current.overlay_path(path);
+        self.frames.push(self.current);
+        self.frames
+    }
+}
+
+fn normalize(v: f64, lo: f64, hi: f64) -> f64 {
+    if hi > lo {
+        (v - lo) / (hi - lo)
+    } else {
+        0.0
+    }
+}
+
+fn lerp(a: Rgb, b: Rgb, t: f64) -> Rgb {
+    let channel = |i: usize| (a[i] as f64 + (b[i] as f64 - a[i] as f64) * t).round() as u8;
+    [channel(0), channel(1), channel(2)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ascii::{find_char, parse_bitgrid, AsciiStyle};
+    use crate::expansion_policy::bitgrid::no_corner_cutting::NoCornerCutting;
+    use crate::expansion_policy::observed::Observed;
+    use crate::util::{grid_search, octile_heuristic};
+
+    const MAP: &str = "
+        ##########
+        #S.......#
+        #.####...#
+        #....#..G#
+        ##########
+    ";
+
+    #[test]
+    fn check_formats() {
+        let map = parse_bitgrid(MAP, &AsciiStyle::default()).unwrap();
+        let mut image = MapImage::from_bitgrid(&map);
+        image.overlay_path(&[(1, 1), (2, 1), (3, 1)]);
+        assert_eq!(image.get(0, 0), OBSTRUCTED);
+        assert_eq!(image.get(2, 1), PATH);
+        assert_eq!(image.get(4, 1), FREE);
+
+        let mut ppm = vec![];
+        image.write_ppm(&mut ppm, 3).unwrap();
+        let header = b"P6\n30 15\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(ppm.len(), header.len() + 30 * 15 * 3);
+        // the pixel at (4, 4) is in cell (1, 1)
+        let at = |x: usize, y: usize| &ppm[header.len() + (y * 30 + x) * 3..][..3];
+        assert_eq!(at(4, 4), PATH);
+        assert_eq!(at(14, 5), FREE);
+
+        let mut pgm = vec![];
+        image.write_pgm(&mut pgm, 1).unwrap();
+        let header = b"P5\n10 5\n255\n";
+        assert_eq!(&pgm[..header.len()], header);
+        assert_eq!(pgm.len(), header.len() + 50);
+        assert_eq!(pgm[header.len()], 32);
+        assert_eq!(pgm[header.len() + 14], 255);
+
+        let mut svg = vec![];
+        image.write_svg(&mut svg, 10).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains(r#"points="15,15 25,15 35,15""#));
+        // runs of cells: 1 + 4 + 5 + 5 + 1 rects
+        assert_eq!(svg.matches("<rect").count(), 16);
+    }
+
+    #[test]
+    fn check_search_overlays() {
+        let map = parse_bitgrid(MAP, &AsciiStyle::default()).unwrap();
+        let (start, goal) = (find_char(MAP, 'S').unwrap(), find_char(MAP, 'G').unwrap());
+        let mut pool = GridPool::new(map.width(), map.height());
+        let mut owner = Owner::new();
+
+        let mut animation = Animation::new(MapImage::from_bitgrid(&map), 2);
+        let mut expanded = vec![];
+        let mut ep = Observed::new(NoCornerCutting::new(&map), |n: &SearchNode<_>, e: &[_]| {
+            expanded.push(n.id);
+            animation.record(n, e);
+        });
+        grid_search(
+            &mut pool,
+            &mut owner,
+            &mut ep,
+            octile_heuristic(goal, 1.0),
+            start,
+            goal,
+        );
+        let frames = animation.finish(&[start, goal]);
+        assert_eq!(frames.len(), 1 + expanded.len() / 2 + 1);
+        assert_eq!(frames[0], MapImage::from_bitgrid(&map));
+        let last = frames.last().unwrap();
+        for &(x, y) in &expanded {
+            assert!(last.get(x, y) == CLOSED || (x, y) == start);
+        }
+
+        let mut g = MapImage::from_bitgrid(&map);
+        g.overlay_g_values(&pool, &owner);
+        assert_eq!(g.get(start.0, start.1), [0, 0, 255]);
+        assert_eq!(g.get(0, 0), OBSTRUCTED);
+        let mut heat = MapImage::from_bitgrid(&map);
+        heat.overlay_expansions(&pool, &owner);
+        for &(x, y) in &expanded {
+            assert_ne!(heat.get(x, y), FREE);
+        }
+    }
+}
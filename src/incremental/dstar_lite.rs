@@ -0,0 +1,108 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+
+use crate::domains::BitGrid;
+use crate::expansion_policy::ExpansionPolicy;
+
+use super::Engine;
+
+/// D* Lite.
+///
+/// Searches backwards from the goal, so that the search state remains valid as the start moves
+/// towards the goal. Like `LpaStar`, only the part of the search affected by edge changes needs to
+/// be repaired.
+pub struct DStarLite<V, S = RandomState> {
+    engine: Engine<V, S>,
+    start: V,
+    last: V,
+}
+
+impl<V: Hash + Eq + Copy> DStarLite<V, RandomState> {
+    pub fn new(start: V, goal: V) -> Self {
+        Self::with_hasher(start, goal, Default::default())
+    }
+}
+
+impl<V: Hash + Eq + Copy, S: BuildHasher> DStarLite<V, S> {
+    pub fn with_hasher(start: V, goal: V, hash_builder: S) -> Self {
+        DStarLite {
+            engine: Engine::new(goal, hash_builder),
+            start,
+            last: start,
+        }
+    }
+
+    pub fn start(&self) -> V {
+        self.start
+    }
+
+    pub fn goal(&self) -> V {
+        self.engine.root
+    }
+
+    /// Moves the start, typically to the vertex returned by `next_move`.
+    pub fn move_start(&mut self, start: V) {
+        self.start = start;
+    }
+
+    /// Marks the outgoing edges of `id` as changed.
+    pub fn notify_vertex_changed(&mut self, id: V) {
+        self.engine.notify_vertex_changed(id);
+    }
+
+    /// Marks the edge from `from` to `to` as changed. This includes edges being added or removed.
+    pub fn notify_edge_changed(&mut self, from: V, _to: V) {
+        self.engine.notify_vertex_changed(from);
+    }
+
+    /// Brings the search state up to date, returning the cost of the shortest path from the start
+    /// to the goal, or `None` if the goal is unreachable.
+    ///
+    /// `successors` and `predecessors` must be consistent with each other, i.e. for every edge
+    /// produced by one, the other must produce the reverse edge with the same cost. `h(a, b)` must
+    /// be a consistent heuristic estimate of the distance between `a` and `b`.
+    pub fn compute_shortest_path(
+        &mut self,
+        successors: &mut impl ExpansionPolicy<V>,
+        predecessors: &mut impl ExpansionPolicy<V>,
+        mut h: impl FnMut(V, V) -> f64,
+    ) -> Option<f64> {
+        if self.last != self.start {
+            // rather than reordering the queue when the start moves, the keys of all new queue
+            // entries are offset by the distance moved so that existing keys remain lower bounds.
+            self.engine.km += h(self.last, self.start);
+            self.last = self.start;
+        }
+        let start = self.start;
+        self.engine
+            .compute_shortest_path(start, predecessors, successors, |v| h(start, v));
+        Some(self.g(start)).filter(|g| g.is_finite())
+    }
+
+    /// The current g-value (distance to the goal) of a vertex. Only meaningful for vertices on the
+    /// shortest path after calling `compute_shortest_path`.
+    pub fn g(&self, id: V) -> f64 {
+        self.engine.state(id).g
+    }
+
+    /// Returns the vertex to move to from the start along the shortest path.
+    pub fn next_move(&self, successors: &mut impl ExpansionPolicy<V>) -> Option<V> {
+        self.engine
+            .trace(self.start, successors)
+            .and_then(|path| path.get(1).copied())
+    }
+
+    /// Returns the shortest path from the start to the goal.
+    pub fn path(&self, successors: &mut impl ExpansionPolicy<V>) -> Option<Vec<V>> {
+        self.engine.trace(self.start, successors)
+    }
+}
+
+impl<S> DStarLite<(i32, i32), S> {
+    /// Marks a cell of the map as changed, for use with grid expansion policies such as
+    /// `NoCornerCutting` wrapped in a `SymmetricGrid`.
+    #[track_caller]
+    pub fn notify_cell_changed(&mut self, map: &BitGrid, cell: (i32, i32)) {
+        self.engine.notify_cell_changed(map, cell);
+    }
+}
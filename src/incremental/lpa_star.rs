@@ -0,0 +1,103 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+
+use crate::domains::BitGrid;
+use crate::expansion_policy::ExpansionPolicy;
+
+use super::Engine;
+
+/// Lifelong Planning A*.
+///
+/// Keeps its search state between calls to `compute_shortest_path`, so that after the costs of
+/// some edges change, only the part of the search affected by the change needs to be repaired.
+pub struct LpaStar<V, S = RandomState> {
+    engine: Engine<V, S>,
+    goal: V,
+    /// Whether the queue keys were computed with the heuristic towards a previous goal.
+    rekey: bool,
+}
+
+impl<V: Hash + Eq + Copy> LpaStar<V, RandomState> {
+    pub fn new(source: V, goal: V) -> Self {
+        Self::with_hasher(source, goal, Default::default())
+    }
+}
+
+impl<V: Hash + Eq + Copy, S: BuildHasher> LpaStar<V, S> {
+    pub fn with_hasher(source: V, goal: V, hash_builder: S) -> Self {
+        LpaStar {
+            engine: Engine::new(source, hash_builder),
+            goal,
+            rekey: false,
+        }
+    }
+
+    pub fn source(&self) -> V {
+        self.engine.root
+    }
+
+    pub fn goal(&self) -> V {
+        self.goal
+    }
+
+    /// Changes the goal without discarding the search state.
+    ///
+    /// note: the next call to `compute_shortest_path` recomputes the key of every queued vertex
+    ///       with the heuristic towards the new goal.
+    pub fn set_goal(&mut self, goal: V) {
+        if goal != self.goal {
+            self.goal = goal;
+            self.rekey = true;
+        }
+    }
+
+    /// Marks the incoming edges of `id` as changed.
+    pub fn notify_vertex_changed(&mut self, id: V) {
+        self.engine.notify_vertex_changed(id);
+    }
+
+    /// Marks the edge from `from` to `to` as changed. This includes edges being added or removed.
+    pub fn notify_edge_changed(&mut self, _from: V, to: V) {
+        self.engine.notify_vertex_changed(to);
+    }
+
+    /// Brings the search state up to date, returning the cost of the shortest path from the source
+    /// to the goal, or `None` if the goal is unreachable.
+    ///
+    /// `successors` and `predecessors` must be consistent with each other, i.e. for every edge
+    /// produced by one, the other must produce the reverse edge with the same cost. `h` must be
+    /// a consistent heuristic towards the goal.
+    pub fn compute_shortest_path(
+        &mut self,
+        successors: &mut impl ExpansionPolicy<V>,
+        predecessors: &mut impl ExpansionPolicy<V>,
+        mut h: impl FnMut(V) -> f64,
+    ) -> Option<f64> {
+        if std::mem::take(&mut self.rekey) {
+            self.engine.rebuild_queue(&mut h);
+        }
+        self.engine
+            .compute_shortest_path(self.goal, successors, predecessors, &mut h);
+        Some(self.g(self.goal)).filter(|g| g.is_finite())
+    }
+
+    /// The current g-value of a vertex. Only meaningful for vertices on the shortest path after
+    /// calling `compute_shortest_path`.
+    pub fn g(&self, id: V) -> f64 {
+        self.engine.state(id).g
+    }
+
+    /// Returns the shortest path from the goal back to the source.
+    pub fn path(&self, predecessors: &mut impl ExpansionPolicy<V>) -> Option<Vec<V>> {
+        self.engine.trace(self.goal, predecessors)
+    }
+}
+
+impl<S> LpaStar<(i32, i32), S> {
+    /// Marks a cell of the map as changed, for use with grid expansion policies such as
+    /// `NoCornerCutting` wrapped in a `SymmetricGrid`.
+    #[track_caller]
+    pub fn notify_cell_changed(&mut self, map: &BitGrid, cell: (i32, i32)) {
+        self.engine.notify_cell_changed(map, cell);
+    }
+}
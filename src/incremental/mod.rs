@@ -0,0 +1,490 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+use crate::domains::BitGrid;
+use crate::expansion_policy::ExpansionPolicy;
+use crate::util::GridDomain;
+use crate::{Edge, SearchNode};
+
+mod dstar_lite;
+pub use self::dstar_lite::DStarLite;
+mod lpa_star;
+pub use self::lpa_star::LpaStar;
+
+/// Wraps a grid expansion policy so that obstructed cells have no edges.
+///
+/// Grid policies such as `NoCornerCutting` only produce edges into unobstructed cells, but still
+/// produce edges out of obstructed ones, which have no reverse edge. LPA* and D* Lite need the
+/// predecessors to produce the reverse of every edge the successors produce, so the policies given
+/// to them should be wrapped. The grid policies with other notions of passability, such as
+/// `WeightedGrid` costs or terrain masks, already give impassable cells no edges for the same
+/// reason.
+pub struct SymmetricGrid<'a, E> {
+    map: &'a BitGrid,
+    inner: E,
+}
+
+impl<'a, E: GridDomain> SymmetricGrid<'a, E> {
+    /// `inner` must be a policy over `map`.
+    pub fn new(map: &'a BitGrid, inner: E) -> Self {
+        assert!(
+            map.width() == inner.width() && map.height() == inner.height(),
+            "The expansion policy must have the same size as the map."
+        );
+        SymmetricGrid { map, inner }
+    }
+}
+
+unsafe impl<E: GridDomain> GridDomain for SymmetricGrid<'_, E> {
+    fn width(&self) -> i32 {
+        self.inner.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.inner.height()
+    }
+}
+
+impl<E: ExpansionPolicy<(i32, i32)>> ExpansionPolicy<(i32, i32)> for SymmetricGrid<'_, E> {
+    fn expand(&mut self, node: &SearchNode<(i32, i32)>, edges: &mut Vec<Edge<(i32, i32)>>) {
+        if !self.map.get(node.id.0, node.id.1) {
+            self.inner.expand(node, edges);
+        }
+    }
+
+    unsafe fn expand_unchecked(
+        &mut self,
+        node: &SearchNode<(i32, i32)>,
+        edges: &mut Vec<Edge<(i32, i32)>>,
+    ) {
+        // SAFETY: the caller guarantees `node.id` is in-bounds of the inner policy, which has the
+        //         same size as the map.
+        if !self.map.get_unchecked(node.id.0, node.id.1) {
+            self.inner.expand_unchecked(node, edges);
+        }
+    }
+}
+
+/// The search state shared by LPA* and D* Lite.
+///
+/// The root is the vertex whose rhs-value is fixed at 0. Edges are relaxed from the root outwards,
+/// so `relax` must produce the edges leading away from the root and `support` must produce the
+/// edges leading towards it. For LPA* these are the successors and predecessors respectively; for
+/// D* Lite, which searches backwards from the goal, they are the other way around.
+struct Engine<V, S> {
+    root: V,
+    nodes: HashMap<V, State, S>,
+    queue: BinaryHeap<QueueEntry<V>>,
+    dirty: Vec<V>,
+    km: f64,
+    edges: Vec<Edge<V>>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct State {
+    g: f64,
+    rhs: f64,
+}
+
+const UNVISITED: State = State {
+    g: f64::INFINITY,
+    rhs: f64::INFINITY,
+};
+
+struct QueueEntry<V> {
+    key: (f64, f64),
+    id: V,
+}
+
+impl<V: Hash + Eq + Copy, S: BuildHasher> Engine<V, S> {
+    fn new(root: V, hasher: S) -> Self {
+        let mut this = Engine {
+            root,
+            nodes: HashMap::with_hasher(hasher),
+            queue: BinaryHeap::new(),
+            dirty: vec![],
+            km: 0.0,
+            edges: vec![],
+        };
+        this.nodes.insert(
+            root,
+            State {
+                g: f64::INFINITY,
+                rhs: 0.0,
+            },
+        );
+        this.queue.push(QueueEntry {
+            key: (0.0, 0.0),
+            id: root,
+        });
+        this
+    }
+
+    fn state(&self, id: V) -> State {
+        self.nodes.get(&id).copied().unwrap_or(UNVISITED)
+    }
+
+    fn key(&self, id: V, h: &mut impl FnMut(V) -> f64) -> (f64, f64) {
+        let s = self.state(id);
+        let m = s.g.min(s.rhs);
+        if m == f64::INFINITY {
+            return (m, m);
+        }
+        (m + h(id) + self.km, m)
+    }
+
+    fn push_if_inconsistent(&mut self, id: V, h: &mut impl FnMut(V) -> f64) {
+        let s = self.state(id);
+        if s.g != s.rhs {
+            let key = self.key(id, h);
+            self.queue.push(QueueEntry { key, id });
+        }
+    }
+
+    /// Recomputes the keys of all queued vertices, for when the heuristic has changed.
+    ///
+    /// Stale keys which are too low are repaired lazily, but keys which are too high would make
+    /// the search stop early.
+    fn rebuild_queue(&mut self, h: &mut impl FnMut(V) -> f64) {
+        let entries: Vec<_> = self
+            .nodes
+            .iter()
+            .filter(|(_, s)| s.g != s.rhs)
+            .map(|(&id, _)| QueueEntry {
+                key: self.key(id, h),
+                id,
+            })
+            .collect();
+        self.queue = entries.into();
+    }
+
+    /// Recomputes the rhs-value of `id` from its supporting edges.
+    fn update_vertex(
+        &mut self,
+        id: V,
+        support: &mut impl ExpansionPolicy<V>,
+        h: &mut impl FnMut(V) -> f64,
+    ) {
+        if id != self.root {
+            let mut edges = std::mem::take(&mut self.edges);
+            expand(support, id, &mut edges);
+            let rhs = edges
+                .drain(..)
+                .map(|e| self.state(e.destination).g + e.cost)
+                .fold(f64::INFINITY, f64::min);
+            self.edges = edges;
+
+            let s = self.nodes.entry(id).or_insert(UNVISITED);
+            s.rhs = rhs;
+            if s.g == f64::INFINITY && rhs == f64::INFINITY {
+                // there is no point remembering vertices that are in their initial state
+                self.nodes.remove(&id);
+            }
+        }
+        self.push_if_inconsistent(id, h);
+    }
+
+    fn compute_shortest_path(
+        &mut self,
+        target: V,
+        relax: &mut impl ExpansionPolicy<V>,
+        support: &mut impl ExpansionPolicy<V>,
+        mut h: impl FnMut(V) -> f64,
+    ) {
+        for id in std::mem::take(&mut self.dirty) {
+            self.update_vertex(id, support, &mut h);
+        }
+
+        let mut edges = vec![];
+        loop {
+            let target_key = self.key(target, &mut h);
+            let target_state = self.state(target);
+            match self.queue.peek() {
+                None => break,
+                Some(top)
+                    if !key_less(top.key, target_key) && target_state.g == target_state.rhs =>
+                {
+                    break
+                }
+                _ => {}
+            }
+
+            let QueueEntry { key, id } = self.queue.pop().unwrap();
+            let s = self.state(id);
+            if s.g == s.rhs {
+                // stale queue entry
+                continue;
+            }
+            let new_key = self.key(id, &mut h);
+            if key_less(key, new_key) {
+                self.queue.push(QueueEntry { key: new_key, id });
+                continue;
+            }
+
+            expand(relax, id, &mut edges);
+            if s.g > s.rhs {
+                self.nodes.get_mut(&id).unwrap().g = s.rhs;
+                for e in edges.drain(..) {
+                    if e.destination == self.root {
+                        continue;
+                    }
+                    let rhs = s.rhs + e.cost;
+                    let n = self.nodes.entry(e.destination).or_insert(UNVISITED);
+                    if rhs < n.rhs {
+                        n.rhs = rhs;
+                        self.push_if_inconsistent(e.destination, &mut h);
+                    }
+                }
+            } else {
+                self.nodes.get_mut(&id).unwrap().g = f64::INFINITY;
+                self.update_vertex(id, support, &mut h);
+                for e in edges.drain(..) {
+                    self.update_vertex(e.destination, support, &mut h);
+                }
+            }
+        }
+    }
+
+    /// Follows the edges produced by `support` greedily from `from` towards the root.
+    fn trace(&self, from: V, support: &mut impl ExpansionPolicy<V>) -> Option<Vec<V>> {
+        if self.state(from).g == f64::INFINITY {
+            return None;
+        }
+        let mut path = vec![from];
+        let mut edges = vec![];
+        let mut current = from;
+        while current != self.root {
+            // the g-values should strictly decrease along the path, so this is just a guard
+            // against being called while the search state is out of date.
+            if path.len() > self.nodes.len() {
+                return None;
+            }
+            expand(support, current, &mut edges);
+            current = edges
+                .drain(..)
+                .map(|e| (e.destination, self.state(e.destination).g + e.cost))
+                .filter(|&(_, g)| g < f64::INFINITY)
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))?
+                .0;
+            path.push(current);
+        }
+        Some(path)
+    }
+}
+
+impl<V, S> Engine<V, S> {
+    fn notify_vertex_changed(&mut self, id: V) {
+        self.dirty.push(id);
+    }
+}
+
+impl<S> Engine<(i32, i32), S> {
+    fn notify_cell_changed(&mut self, map: &BitGrid, (x, y): (i32, i32)) {
+        map.get_neighbors(x, y);
+        // changing a cell can affect every edge in its neighborhood via corner cutting rules
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (nx, ny) = (x + dx, y + dy);
+                if (0..map.width()).contains(&nx) && (0..map.height()).contains(&ny) {
+                    self.dirty.push((nx, ny));
+                }
+            }
+        }
+    }
+}
+
+fn expand<V>(policy: &mut impl ExpansionPolicy<V>, id: V, edges: &mut Vec<Edge<V>>) {
+    let node = SearchNode {
+        search_num: 0,
+        pqueue_location: 0,
+        expansions: 0,
+        id,
+        parent: None,
+        g: 0.0,
+        lb: 0.0,
+    };
+    policy.expand(&node, edges);
+}
+
+fn key_less(a: (f64, f64), b: (f64, f64)) -> bool {
+    a.0 < b.0 || a.0 == b.0 && a.1 < b.1
+}
+
+impl<V> PartialEq for QueueEntry<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<V> Eq for QueueEntry<V> {}
+
+impl<V> PartialOrd for QueueEntry<V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<V> Ord for QueueEntry<V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reversed, since BinaryHeap is a max-heap
+        let a = self.key;
+        let b = other.key;
+        b.0.partial_cmp(&a.0)
+            .unwrap_or(Ordering::Equal)
+            .then(b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::random_grid;
+    use crate::domains::DirectedGraph;
+    use crate::expansion_policy::bitgrid::no_corner_cutting::NoCornerCutting;
+    use crate::expansion_policy::graph::{IncomingEdges, OutgoingEdges};
+    use crate::node_pool::GridPool;
+    use crate::util::{assert_same_cost, grid_search, octile_heuristic};
+    use crate::Owner;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    fn symmetric(map: &BitGrid) -> SymmetricGrid<NoCornerCutting> {
+        SymmetricGrid::new(map, NoCornerCutting::new(map))
+    }
+
+    fn reference_cost(map: &BitGrid, from: (i32, i32), to: (i32, i32)) -> Option<f64> {
+        let mut pool = GridPool::new(map.width(), map.height());
+        let mut owner = Owner::new();
+        grid_search(
+            &mut pool,
+            &mut owner,
+            &mut symmetric(map),
+            octile_heuristic(to, 1.0),
+            from,
+            to,
+        );
+        let g = owner.ro(pool.get(to.0, to.1, &owner)?).g;
+        Some(g).filter(|g| g.is_finite())
+    }
+
+    #[test]
+    fn check_grid_updates() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let mut map = random_grid(&mut rng, 31, 27, 0.25);
+        let source = (0, 0);
+        let goal = (map.width() - 1, map.height() - 1);
+        map.set(source.0, source.1, false);
+        map.set(goal.0, goal.1, false);
+
+        let mut lpa = LpaStar::new(source, goal);
+        let mut dstar = DStarLite::new(source, goal);
+        for _ in 0..30 {
+            let (x, y) = (
+                rng.gen_range(0..map.width()),
+                rng.gen_range(0..map.height()),
+            );
+            if (x, y) != source && (x, y) != goal {
+                map.set(x, y, !map.get(x, y));
+                lpa.notify_cell_changed(&map, (x, y));
+                dstar.notify_cell_changed(&map, (x, y));
+            }
+
+            let ncc = &mut symmetric(&map);
+            let lpa_cost = lpa.compute_shortest_path(
+                &mut symmetric(&map),
+                &mut symmetric(&map),
+                octile_heuristic(goal, 1.0),
+            );
+            let expected = reference_cost(&map, source, goal);
+            assert_same_cost(lpa_cost, expected);
+            if expected.is_some() {
+                assert_eq!(lpa.path(ncc).unwrap().last(), Some(&source));
+            }
+
+            let dstar_cost =
+                dstar.compute_shortest_path(&mut symmetric(&map), &mut symmetric(&map), |a, b| {
+                    octile_heuristic(a, 1.0)(b)
+                });
+            let expected = reference_cost(&map, dstar.start(), goal);
+            assert_same_cost(dstar_cost, expected);
+            if expected.is_some() {
+                assert_eq!(dstar.path(ncc).unwrap().last(), Some(&goal));
+                if let Some(next) = dstar.next_move(ncc) {
+                    if next != goal {
+                        dstar.move_start(next);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn check_graph_updates() {
+        let mut graph = DirectedGraph::new();
+        for i in 0..4 {
+            graph.add_vertex(i);
+        }
+        graph.add_edge(0, 1, 1.0);
+        graph.add_edge(1, 3, 1.0);
+        graph.add_edge(0, 2, 2.0);
+        graph.add_edge(2, 3, 2.0);
+
+        let mut dstar = DStarLite::new(0, 3);
+        let cost = dstar.compute_shortest_path(
+            &mut OutgoingEdges::new(&graph),
+            &mut IncomingEdges::new(&graph),
+            |_, _| 0.0,
+        );
+        assert_eq!(cost, Some(2.0));
+        assert_eq!(dstar.next_move(&mut OutgoingEdges::new(&graph)), Some(1));
+
+        graph.add_edge(1, 3, 10.0);
+        dstar.notify_edge_changed(1, 3);
+        let cost = dstar.compute_shortest_path(
+            &mut OutgoingEdges::new(&graph),
+            &mut IncomingEdges::new(&graph),
+            |_, _| 0.0,
+        );
+        assert_eq!(cost, Some(4.0));
+        assert_eq!(
+            dstar.path(&mut OutgoingEdges::new(&graph)),
+            Some(vec![0, 2, 3])
+        );
+    }
+    #[test]
+    fn check_goal_change() {
+        let mut graph = DirectedGraph::new();
+        for i in 0..5 {
+            graph.add_vertex(i);
+        }
+        graph.add_edge(0, 1, 1.0);
+        graph.add_edge(0, 2, 1.0);
+        graph.add_edge(2, 3, 1.0);
+        graph.add_edge(0, 4, 1.0);
+        graph.add_edge(4, 3, 3.0);
+
+        // vertex 2 is queued with a key far above its key towards vertex 3
+        let mut lpa = LpaStar::new(0, 1);
+        let h = [1.0, 0.0, 10.0, 9.0, 0.5];
+        let cost = lpa.compute_shortest_path(
+            &mut OutgoingEdges::new(&graph),
+            &mut IncomingEdges::new(&graph),
+            |v| h[v],
+        );
+        assert_eq!(cost, Some(1.0));
+
+        lpa.set_goal(3);
+        let h = [2.0, 0.0, 1.0, 0.0, 3.0];
+        let cost = lpa.compute_shortest_path(
+            &mut OutgoingEdges::new(&graph),
+            &mut IncomingEdges::new(&graph),
+            |v| h[v],
+        );
+        assert_eq!(cost, Some(2.0));
+        assert_eq!(
+            lpa.path(&mut IncomingEdges::new(&graph)),
+            Some(vec![3, 2, 0])
+        );
+    }
+}
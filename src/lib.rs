@@ -3,8 +3,19 @@ use node_pool::NodePool;
 use pqueue::PriorityQueue;
 use qcell::{TLCell, TLCellOwner};
 
+pub mod ascii;
+pub mod cpd;
+pub mod dimacs;
 pub mod domains;
+pub mod image;
+pub mod incremental;
+pub mod mapf;
+pub mod movingai;
 pub mod pqueue;
+pub mod realtime;
+pub mod ros;
+#[cfg(feature = "tiled")]
+pub mod tiled;
 pub mod util;
 pub mod expansion_policy;
 pub mod node_pool;
@@ -0,0 +1,566 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::expansion_policy::ExpansionPolicy;
+use crate::node_pool::NodePool;
+use crate::Owner;
+
+use super::{
+    extract_path, find_conflicts, space_time_heuristic, space_time_search, Agent, Conflict,
+    ReservationTable, SpaceTime,
+};
+
+/// Tolerance used when comparing path costs, which are sums of floating point edge costs.
+const EPSILON: f64 = 1e-9;
+
+/// Conflict-Based Search.
+///
+/// The high level searches a tree of constraint sets, resolving one conflict between two agents'
+/// paths per branch. The low level plans single-agent paths subject to the constraints using a
+/// space-time A* search.
+///
+/// Two standard improvements are enabled by default:
+/// - Bypassing: if resolving a conflict yields an equal-cost path with fewer conflicts, the path is
+///   adopted without branching.
+/// - Prioritizing conflicts: cardinal conflicts (where resolving the conflict increases the cost
+///   for both agents) are split on first, then semi-cardinal conflicts, then the rest. Conflicts
+///   are classified by planning both children, so only the earliest few conflicts of each node
+///   are classified (see `set_max_classified_conflicts`).
+///
+/// With a suboptimality factor greater than 1, this is Enhanced CBS (ECBS): both levels use focal
+/// search to prefer nodes with fewer conflicts among those within the factor of the lower bound,
+/// and the solution is guaranteed to be within the factor of optimal.
+pub struct Cbs {
+    suboptimality: f64,
+    bypass: bool,
+    prioritize_conflicts: bool,
+    max_classified_conflicts: usize,
+    max_expansions: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Solution {
+    /// The cell each agent occupies at each timestep, ending at its goal.
+    pub paths: Vec<Vec<(i32, i32)>>,
+    /// The sum of the costs of the paths.
+    pub cost: f64,
+    /// A lower bound on the cost of an optimal solution.
+    pub lower_bound: f64,
+    /// The number of constraint tree nodes expanded.
+    pub expansions: usize,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Constraint {
+    /// The agent may not occupy the cell at the timestep.
+    Vertex((i32, i32), i32),
+    /// The agent may not move from the first cell at the timestep to the second cell at the next
+    /// timestep.
+    Move((i32, i32), (i32, i32), i32),
+}
+
+struct CtNode {
+    constraints: Vec<(usize, Constraint)>,
+    paths: Vec<Vec<(i32, i32)>>,
+    costs: Vec<f64>,
+    lower_bounds: Vec<f64>,
+    conflicts: usize,
+}
+
+struct Plan {
+    path: Vec<(i32, i32)>,
+    cost: f64,
+    lower_bound: f64,
+}
+
+struct Child {
+    agent: usize,
+    constraint: Constraint,
+    plan: Option<Plan>,
+}
+
+impl Cbs {
+    /// Creates an optimal CBS solver.
+    pub fn new() -> Self {
+        Cbs {
+            suboptimality: 1.0,
+            bypass: true,
+            prioritize_conflicts: true,
+            max_classified_conflicts: 4,
+            max_expansions: usize::MAX,
+        }
+    }
+
+    /// Creates an ECBS solver whose solutions cost at most `suboptimality` times the optimal cost.
+    pub fn ecbs(suboptimality: f64) -> Self {
+        assert!(suboptimality >= 1.0, "suboptimality must be at least 1");
+        Cbs {
+            suboptimality,
+            ..Cbs::new()
+        }
+    }
+
+    pub fn suboptimality(&self) -> f64 {
+        self.suboptimality
+    }
+
+    pub fn set_bypass(&mut self, bypass: bool) {
+        self.bypass = bypass;
+    }
+
+    pub fn set_prioritize_conflicts(&mut self, prioritize_conflicts: bool) {
+        self.prioritize_conflicts = prioritize_conflicts;
+    }
+
+    /// Limits the number of conflicts classified when prioritizing conflicts. Each classification
+    /// costs two low-level searches, except for the conflict which is split on, whose plans are
+    /// reused. Defaults to 4.
+    pub fn set_max_classified_conflicts(&mut self, max_classified_conflicts: usize) {
+        self.max_classified_conflicts = max_classified_conflicts;
+    }
+
+    /// Limits the number of constraint tree nodes expanded before giving up.
+    pub fn set_max_expansions(&mut self, max_expansions: usize) {
+        self.max_expansions = max_expansions;
+    }
+
+    /// Finds conflict-free paths for the agents.
+    ///
+    /// `h` is called with each agent's goal to create the heuristic for that agent.
+    ///
+    /// Returns `None` if there is no solution, or if the expansion limit is reached. Note that
+    /// since the search space is unbounded, this may not terminate on unsolvable instances without
+    /// an expansion limit.
+    pub fn solve<N, E, H>(
+        &self,
+        pool: &mut N,
+        owner: &mut Owner,
+        expansion_policy: &mut E,
+        mut h: impl FnMut((i32, i32)) -> H,
+        agents: &[Agent],
+    ) -> Option<Solution>
+    where
+        N: NodePool<(i32, i32, i32)>,
+        E: ExpansionPolicy<(i32, i32)>,
+        H: FnMut((i32, i32)) -> f64,
+    {
+        let mut low_level = LowLevel {
+            pool,
+            owner,
+            expansion_policy,
+            heuristics: agents.iter().map(|a| h(a.goal)).collect(),
+            agents,
+            suboptimality: self.suboptimality,
+        };
+
+        let mut root = CtNode {
+            constraints: vec![],
+            paths: Vec::with_capacity(agents.len()),
+            costs: Vec::with_capacity(agents.len()),
+            lower_bounds: Vec::with_capacity(agents.len()),
+            conflicts: 0,
+        };
+        for i in 0..agents.len() {
+            let plan = low_level.plan(i, &[], &root.paths)?;
+            root.paths.push(plan.path);
+            root.costs.push(plan.cost);
+            root.lower_bounds.push(plan.lower_bound);
+        }
+        root.conflicts = find_conflicts(&root.paths).len();
+
+        let mut nodes = vec![];
+        let mut open = BinaryHeap::new();
+        let mut focal = BinaryHeap::new();
+        let mut deferred = vec![];
+        let mut expansions = 0;
+        push_node(&mut nodes, &mut open, &mut focal, root);
+
+        loop {
+            let lower_bound = loop {
+                let &MinEntry(lb, i) = open.peek()?;
+                if nodes[i].is_some() {
+                    break lb;
+                }
+                open.pop();
+            };
+            let bound = lower_bound * self.suboptimality + EPSILON;
+            let index = loop {
+                let MinEntry((conflicts, cost), i) = focal.pop()?;
+                if nodes[i].is_none() {
+                    continue;
+                }
+                if cost > bound {
+                    deferred.push(MinEntry((conflicts, cost), i));
+                    continue;
+                }
+                break i;
+            };
+            focal.extend(deferred.drain(..));
+
+            if expansions >= self.max_expansions {
+                return None;
+            }
+            expansions += 1;
+            let mut node = nodes[index].take().unwrap();
+
+            loop {
+                let conflicts = find_conflicts(&node.paths);
+                if conflicts.is_empty() {
+                    return Some(Solution {
+                        cost: node.costs.iter().sum(),
+                        paths: node.paths,
+                        lower_bound,
+                        expansions,
+                    });
+                }
+                node.conflicts = conflicts.len();
+
+                let children = self.choose_conflict(&mut low_level, &node, &conflicts);
+
+                if self.bypass {
+                    if let Some((agent, plan)) = find_bypass(&node, &children) {
+                        node.paths[agent] = plan.path.clone();
+                        node.costs[agent] = plan.cost;
+                        continue;
+                    }
+                }
+
+                for child in children.iter() {
+                    if let Some(plan) = &child.plan {
+                        let mut constraints = node.constraints.clone();
+                        constraints.push((child.agent, child.constraint));
+                        let mut paths = node.paths.clone();
+                        paths[child.agent] = plan.path.clone();
+                        let mut costs = node.costs.clone();
+                        costs[child.agent] = plan.cost;
+                        let mut lower_bounds = node.lower_bounds.clone();
+                        lower_bounds[child.agent] = plan.lower_bound;
+                        let conflicts = find_conflicts(&paths).len();
+                        let child = CtNode {
+                            constraints,
+                            paths,
+                            costs,
+                            lower_bounds,
+                            conflicts,
+                        };
+                        push_node(&mut nodes, &mut open, &mut focal, child);
+                    }
+                }
+                break;
+            }
+        }
+    }
+
+    fn choose_conflict<N, E, H>(
+        &self,
+        low_level: &mut LowLevel<N, E, H>,
+        node: &CtNode,
+        conflicts: &[Conflict],
+    ) -> [Child; 2]
+    where
+        N: NodePool<(i32, i32, i32)>,
+        E: ExpansionPolicy<(i32, i32)>,
+        H: FnMut((i32, i32)) -> f64,
+    {
+        if !self.prioritize_conflicts || self.max_classified_conflicts == 0 {
+            return split(low_level, node, conflicts[0]);
+        }
+
+        // rather than building MDDs, we classify conflicts by planning the children directly, and
+        // reuse the plans for whichever conflict we end up splitting on. this is only done for the
+        // earliest conflicts, since each one costs two low-level searches.
+        let mut semi_cardinal = None;
+        let mut first = None;
+        for &conflict in conflicts.iter().take(self.max_classified_conflicts) {
+            let children = split(low_level, node, conflict);
+            let increases = children
+                .iter()
+                .filter(|c| match &c.plan {
+                    Some(plan) => plan.cost > node.costs[c.agent] + EPSILON,
+                    None => true,
+                })
+                .count();
+            match increases {
+                2 => return children,
+                1 if semi_cardinal.is_none() => semi_cardinal = Some(children),
+                _ if first.is_none() => first = Some(children),
+                _ => {}
+            }
+        }
+        semi_cardinal.or(first).unwrap()
+    }
+}
+
+impl Default for Cbs {
+    fn default() -> Self {
+        Cbs::new()
+    }
+}
+
+fn split<N, E, H>(
+    low_level: &mut LowLevel<N, E, H>,
+    node: &CtNode,
+    conflict: Conflict,
+) -> [Child; 2]
+where
+    N: NodePool<(i32, i32, i32)>,
+    E: ExpansionPolicy<(i32, i32)>,
+    H: FnMut((i32, i32)) -> f64,
+{
+    let ((a, a_constraint), (b, b_constraint)) = match conflict {
+        Conflict::Vertex {
+            agents: (a, b),
+            cell,
+            t,
+        } => (
+            (a, Constraint::Vertex(cell, t)),
+            (b, Constraint::Vertex(cell, t)),
+        ),
+        Conflict::Edge {
+            agents: (a, b),
+            from,
+            to,
+            t,
+        } => (
+            (a, Constraint::Move(from, to, t)),
+            (b, Constraint::Move(to, from, t)),
+        ),
+    };
+    let mut child = |agent, constraint| {
+        let mut constraints = node.constraints.clone();
+        constraints.push((agent, constraint));
+        Child {
+            agent,
+            constraint,
+            plan: low_level.plan(agent, &constraints, &node.paths),
+        }
+    };
+    [child(a, a_constraint), child(b, b_constraint)]
+}
+
+/// Finds a child whose new path is no more expensive than the old one and reduces the number of
+/// conflicts, so it can be adopted by the parent without adding a constraint.
+fn find_bypass<'a>(node: &CtNode, children: &'a [Child; 2]) -> Option<(usize, &'a Plan)> {
+    children.iter().find_map(|child| {
+        let plan = child.plan.as_ref()?;
+        if plan.cost > node.costs[child.agent] + EPSILON {
+            return None;
+        }
+        let mut paths = node.paths.clone();
+        paths[child.agent] = plan.path.clone();
+        if find_conflicts(&paths).len() < node.conflicts {
+            Some((child.agent, plan))
+        } else {
+            None
+        }
+    })
+}
+
+fn push_node(
+    nodes: &mut Vec<Option<CtNode>>,
+    open: &mut BinaryHeap<MinEntry<f64, usize>>,
+    focal: &mut BinaryHeap<MinEntry<(usize, f64), usize>>,
+    node: CtNode,
+) {
+    let index = nodes.len();
+    open.push(MinEntry(node.lower_bounds.iter().sum(), index));
+    focal.push(MinEntry((node.conflicts, node.costs.iter().sum()), index));
+    nodes.push(Some(node));
+}
+
+struct LowLevel<'a, N, E, H> {
+    pool: &'a mut N,
+    owner: &'a mut Owner,
+    expansion_policy: &'a mut E,
+    heuristics: Vec<H>,
+    agents: &'a [Agent],
+    suboptimality: f64,
+}
+
+impl<N, E, H> LowLevel<'_, N, E, H>
+where
+    N: NodePool<(i32, i32, i32)>,
+    E: ExpansionPolicy<(i32, i32)>,
+    H: FnMut((i32, i32)) -> f64,
+{
+    /// Plans a path for `agent` subject to its constraints. When running ECBS, the number of
+    /// conflicts with the other paths is minimized within the suboptimality bound.
+    fn plan(
+        &mut self,
+        agent: usize,
+        constraints: &[(usize, Constraint)],
+        paths: &[Vec<(i32, i32)>],
+    ) -> Option<Plan> {
+        let mut table = ReservationTable::new();
+        for &(a, constraint) in constraints {
+            if a != agent {
+                continue;
+            }
+            match constraint {
+                Constraint::Vertex(cell, t) => table.reserve_vertex(cell, t, a),
+                // a reservation of the reverse move is what prevents moving in this direction
+                Constraint::Move(from, to, t) => table.reserve_edge(to, from, t, a),
+            }
+        }
+
+        let Agent { start, goal } = self.agents[agent];
+        let h = &mut self.heuristics[agent];
+        if self.suboptimality == 1.0 {
+            let mut policy = SpaceTime::new(self.expansion_policy, &table, goal);
+            let (path, cost) = space_time_search(self.pool, self.owner, &mut policy, h, start, 0)?;
+            return Some(Plan {
+                path,
+                cost,
+                lower_bound: cost,
+            });
+        }
+
+        let mut others = ReservationTable::new();
+        for (i, path) in paths.iter().enumerate() {
+            if i != agent {
+                others.reserve_path(path, 0, i, true);
+            }
+        }
+        let mut policy = SpaceTime::new(self.expansion_policy, &table, goal);
+        policy.set_horizon(table.horizon().max(others.horizon()));
+        focal_search(
+            self.pool,
+            self.owner,
+            &mut policy,
+            h,
+            start,
+            self.suboptimality,
+            &others,
+        )
+    }
+}
+
+/// A* with a focal list: among the open vertices whose f-value is within the suboptimality factor
+/// of the smallest, the one whose path has the fewest conflicts with `others` is expanded.
+///
+/// note: this can't use `astar`, whose priority queue orders vertices by f-value alone. Focal
+///       search expands from a second list ordered by conflict count, and tracks the conflict
+///       count of each vertex alongside its g-value, neither of which an expansion policy can
+///       express.
+fn focal_search<N, E>(
+    pool: &mut N,
+    owner: &mut Owner,
+    expansion_policy: &mut SpaceTime<E>,
+    h: impl FnMut((i32, i32)) -> f64,
+    source: (i32, i32),
+    suboptimality: f64,
+    others: &ReservationTable,
+) -> Option<Plan>
+where
+    N: NodePool<(i32, i32, i32)>,
+    E: ExpansionPolicy<(i32, i32)>,
+{
+    let goal = expansion_policy.goal_vertex();
+    let mut h = space_time_heuristic(h);
+    pool.reset(owner);
+
+    let mut conflicts = HashMap::new();
+    let mut open = BinaryHeap::new();
+    let mut focal = BinaryHeap::new();
+    let mut deferred = vec![];
+    let mut edges = vec![];
+
+    let source = (source.0, source.1, 0);
+    let node = pool.generate(source, owner);
+    let n = owner.rw(node);
+    n.g = 0.0;
+    n.lb = h(source);
+    conflicts.insert(source, 0);
+    open.push(MinEntry(n.lb, source));
+    focal.push(MinEntry((0, n.lb), source));
+
+    loop {
+        let f_min = loop {
+            let &MinEntry(f, id) = open.peek()?;
+            let node = pool.generate(id, owner);
+            let n = owner.ro(node);
+            if n.expansions == 0 && n.lb == f {
+                break f;
+            }
+            open.pop();
+        };
+        let bound = f_min * suboptimality + EPSILON;
+        let id = loop {
+            let MinEntry((c, f), id) = focal.pop()?;
+            let node = pool.generate(id, owner);
+            let n = owner.ro(node);
+            if n.expansions != 0 || n.lb != f || conflicts[&id] != c {
+                continue;
+            }
+            if f > bound {
+                deferred.push(MinEntry((c, f), id));
+                continue;
+            }
+            break id;
+        };
+        focal.extend(deferred.drain(..));
+
+        let node = pool.generate(id, owner);
+        let n = owner.rw(node);
+        n.expansions += 1;
+        if id == goal {
+            let cost = n.g;
+            let path = extract_path(pool, owner, goal)?;
+            return Some(Plan {
+                path,
+                cost,
+                lower_bound: f_min,
+            });
+        }
+
+        expansion_policy.expand(n, &mut edges);
+        let parent_g = n.g;
+        let parent_conflicts = conflicts[&id];
+
+        for edge in edges.drain(..) {
+            let (x, y, t) = id;
+            let to = edge.destination;
+            let mut c = parent_conflicts;
+            if to != goal && others.is_move_blocked((x, y), (to.0, to.1), t) {
+                c += 1;
+            }
+            let g = parent_g + edge.cost;
+            let node = pool.generate(to, owner);
+            let n = owner.rw(node);
+            let old_c = conflicts.get(&to).copied().unwrap_or(usize::MAX);
+            if g < n.g || g == n.g && c < old_c {
+                n.g = g;
+                n.lb = g + h(to);
+                n.parent = Some(id);
+                // the focal list expands vertices out of f-order, so vertices may be reopened
+                n.expansions = 0;
+                conflicts.insert(to, c);
+                open.push(MinEntry(n.lb, to));
+                focal.push(MinEntry((c, n.lb), to));
+            }
+        }
+    }
+}
+
+/// Orders entries of a `BinaryHeap` smallest-key-first.
+struct MinEntry<K, V>(K, V);
+
+impl<K: PartialOrd, V> PartialEq for MinEntry<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: PartialOrd, V> Eq for MinEntry<K, V> {}
+
+impl<K: PartialOrd, V> PartialOrd for MinEntry<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: PartialOrd, V> Ord for MinEntry<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.partial_cmp(&self.0).unwrap_or(Ordering::Equal)
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::domains::BitGrid;
+use crate::expansion_policy::ExpansionPolicy;
+use crate::movingai::{load_map, parse_scen, MovingAiParseError};
+use crate::node_pool::{GridPool, NodePool};
+use crate::util::{grid_search, zero_heuristic, GridDomain};
+use crate::Owner;
+
+mod reservation;
+pub use self::reservation::ReservationTable;
+mod space_time;
+pub use self::space_time::{space_time_heuristic, space_time_search, SpaceTime};
+mod prioritized;
+pub use self::prioritized::prioritized_planning;
+mod whca;
+pub use self::whca::Whca;
+mod cbs;
+pub use self::cbs::{Cbs, Solution};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Agent {
+    pub start: (i32, i32),
+    pub goal: (i32, i32),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Conflict {
+    /// Both agents occupy `cell` at timestep `t`.
+    Vertex {
+        agents: (usize, usize),
+        cell: (i32, i32),
+        t: i32,
+    },
+    /// The first agent moves from `from` to `to` while the second agent moves from `to` to
+    /// `from`, starting at timestep `t`.
+    Edge {
+        agents: (usize, usize),
+        from: (i32, i32),
+        to: (i32, i32),
+        t: i32,
+    },
+}
+
+/// The cell an agent following `path` occupies at timestep `t`. Agents remain at the end of their
+/// path once they have reached it.
+pub fn position_at(path: &[(i32, i32)], t: i32) -> (i32, i32) {
+    path[(t.max(0) as usize).min(path.len() - 1)]
+}
+
+/// Finds the earliest conflict between a set of paths, where `paths[i][t]` is the cell occupied by
+/// agent `i` at timestep `t`.
+pub fn find_conflict(paths: &[Vec<(i32, i32)>]) -> Option<Conflict> {
+    let mut result = None;
+    visit_conflicts(paths, |c| {
+        result = Some(c);
+        false
+    });
+    result
+}
+
+/// Finds all conflicts between a set of paths, ordered by timestep.
+pub fn find_conflicts(paths: &[Vec<(i32, i32)>]) -> Vec<Conflict> {
+    let mut result = vec![];
+    visit_conflicts(paths, |c| {
+        result.push(c);
+        true
+    });
+    result
+}
+
+/// Calls `f` with each conflict in timestep order until it returns `false`.
+fn visit_conflicts(paths: &[Vec<(i32, i32)>], mut f: impl FnMut(Conflict) -> bool) {
+    let makespan = paths.iter().map(|p| p.len()).max().unwrap_or(0) as i32;
+    let mut occupied = HashMap::new();
+    let mut moves = HashMap::new();
+    for t in 0..makespan {
+        occupied.clear();
+        moves.clear();
+        for (i, path) in paths.iter().enumerate() {
+            let cell = position_at(path, t);
+            if let Some(j) = occupied.insert(cell, i) {
+                let c = Conflict::Vertex {
+                    agents: (j, i),
+                    cell,
+                    t,
+                };
+                if !f(c) {
+                    return;
+                }
+            }
+        }
+        for (i, path) in paths.iter().enumerate() {
+            let from = position_at(path, t);
+            let to = position_at(path, t + 1);
+            if from == to {
+                continue;
+            }
+            if let Some(&j) = moves.get(&(to, from)) {
+                let c = Conflict::Edge {
+                    agents: (j, i),
+                    from: to,
+                    to: from,
+                    t,
+                };
+                if !f(c) {
+                    return;
+                }
+            }
+            moves.insert((from, to), i);
+        }
+    }
+}
+
+/// Traces a space-time search tree back from `to`, returning the spatial path leading to it.
+fn extract_path(
+    pool: &impl NodePool<(i32, i32, i32)>,
+    owner: &mut Owner,
+    to: (i32, i32, i32),
+) -> Option<Vec<(i32, i32)>> {
+    let node = pool.generate(to, owner);
+    let mut current = owner.ro(node).parent?;
+    let mut path = vec![(current.0, current.1)];
+    loop {
+        let node = pool.generate(current, owner);
+        match owner.ro(node).parent {
+            Some(parent) => {
+                current = parent;
+                path.push((current.0, current.1));
+            }
+            None => break,
+        }
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// Loads a MAPF benchmark instance, using the first `agents` problems of the scenario file as the
+/// agents.
+pub fn load_instance(
+    map: &Path,
+    scen: &Path,
+    agents: usize,
+) -> Result<(BitGrid, Vec<Agent>), MovingAiParseError> {
+    let map = load_map(map)?;
+    let problems = parse_scen(BufReader::new(File::open(scen)?), &map)?;
+    if problems.len() < agents {
+        return Err(MovingAiParseError::InvalidData);
+    }
+    let agents = problems[..agents]
+        .iter()
+        .map(|p| Agent {
+            start: p.from,
+            goal: p.to,
+        })
+        .collect();
+    Ok((map, agents))
+}
+
+/// Creates a heuristic which gives the exact distance to `goal`, by running Dijkstra's algorithm
+/// outwards from the goal. This is the heuristic `Whca` works best with.
+///
+/// note: the expansion policy must be symmetric, i.e. for each edge it produces, it must also
+///       produce the reverse edge with the same cost. `NoCornerCutting` is symmetric.
+pub fn distance_heuristic<E>(
+    expansion_policy: &mut E,
+    goal: (i32, i32),
+) -> impl Fn((i32, i32)) -> f64
+where
+    E: ExpansionPolicy<(i32, i32)> + GridDomain,
+{
+    let width = expansion_policy.width();
+    let height = expansion_policy.height();
+    let mut pool = GridPool::new(width, height);
+    let mut owner = Owner::new();
+    grid_search(
+        &mut pool,
+        &mut owner,
+        expansion_policy,
+        zero_heuristic(),
+        goal,
+        (-1, -1),
+    );
+    let mut distances = vec![f64::INFINITY; width as usize * height as usize];
+    for y in 0..height {
+        for x in 0..width {
+            if let Some(node) = pool.get(x, y, &owner) {
+                distances[(x + y * width) as usize] = owner.ro(node).g;
+            }
+        }
+    }
+    move |(x, y)| distances[(x + y * width) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::random_grid;
+    use crate::expansion_policy::bitgrid::no_corner_cutting::NoCornerCutting;
+    use crate::node_pool::HashPool;
+    use crate::util::octile_heuristic;
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    fn random_instance(rng: &mut Pcg64, agents: usize) -> (BitGrid, Vec<Agent>) {
+        let map = random_grid(rng, 16, 16, 0.15);
+        let mut free = vec![];
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                if !map.get(x, y) {
+                    free.push((x, y));
+                }
+            }
+        }
+        free.shuffle(rng);
+        let starts = free[..agents].to_vec();
+        free.shuffle(rng);
+        let agents = starts
+            .into_iter()
+            .zip(&free[..agents])
+            .map(|(start, &goal)| Agent { start, goal })
+            .collect();
+        (map, agents)
+    }
+
+    fn check_solution(agents: &[Agent], paths: &[Vec<(i32, i32)>]) {
+        assert_eq!(find_conflict(paths), None);
+        for (agent, path) in agents.iter().zip(paths) {
+            assert_eq!(path.first(), Some(&agent.start));
+            assert_eq!(path.last(), Some(&agent.goal));
+        }
+    }
+
+    #[test]
+    fn check_corridor_swap() {
+        // two agents swap ends of a corridor, which requires one of them to duck into the alcove
+        let mut map = BitGrid::new(7, 2);
+        for x in [0, 1, 2, 3, 5, 6] {
+            map.set(x, 1, true);
+        }
+        let agents = [
+            Agent {
+                start: (0, 0),
+                goal: (6, 0),
+            },
+            Agent {
+                start: (6, 0),
+                goal: (0, 0),
+            },
+        ];
+        let mut pool = HashPool::new();
+        let mut owner = Owner::new();
+        let paths = prioritized_planning(
+            &mut pool,
+            &mut owner,
+            &mut NoCornerCutting::new(&map),
+            |goal| octile_heuristic(goal, 1.0),
+            &agents,
+        )
+        .unwrap();
+        check_solution(&agents, &paths);
+        assert!(paths[1].contains(&(4, 1)));
+    }
+
+    #[test]
+    fn check_random_instances() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let mut pool = HashPool::new();
+        let mut owner = Owner::new();
+        let mut solved = 0;
+        for _ in 0..20 {
+            let (map, agents) = random_instance(&mut rng, 8);
+            if let Some(paths) = prioritized_planning(
+                &mut pool,
+                &mut owner,
+                &mut NoCornerCutting::new(&map),
+                |goal| octile_heuristic(goal, 1.0),
+                &agents,
+            ) {
+                check_solution(&agents, &paths);
+                solved += 1;
+            }
+            if let Some(paths) = Whca::new(8, 500).solve(
+                &mut pool,
+                &mut owner,
+                &mut NoCornerCutting::new(&map),
+                |goal| distance_heuristic(&mut NoCornerCutting::new(&map), goal),
+                &agents,
+            ) {
+                check_solution(&agents, &paths);
+                solved += 1;
+            }
+        }
+        assert!(solved >= 30);
+    }
+
+    #[test]
+    fn check_cbs_corridor_swap() {
+        let map = crate::movingai::parse_map(
+            &b"type octile\nheight 2\nwidth 7\nmap\n.......\n@@@@.@@\n"[..],
+        )
+        .unwrap();
+        let problems = parse_scen(
+            &b"version 1\n0 x.map 7 2 0 0 6 0 6\n0 x.map 7 2 6 0 0 0 6\n"[..],
+            &map,
+        )
+        .unwrap();
+        let agents: Vec<_> = problems
+            .iter()
+            .map(|p| Agent {
+                start: p.from,
+                goal: p.to,
+            })
+            .collect();
+        let mut pool = HashPool::new();
+        let mut owner = Owner::new();
+        let solution = Cbs::new()
+            .solve(
+                &mut pool,
+                &mut owner,
+                &mut NoCornerCutting::new(&map),
+                |goal| octile_heuristic(goal, 1.0),
+                &agents,
+            )
+            .unwrap();
+        check_solution(&agents, &solution.paths);
+        assert!((solution.cost - solution.lower_bound).abs() < 1e-9);
+    }
+
+    #[test]
+    fn check_cbs_random_instances() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let mut pool = HashPool::new();
+        let mut owner = Owner::new();
+        let mut solved = 0;
+        for _ in 0..10 {
+            let (map, agents) = random_instance(&mut rng, 6);
+            let mut solve = |mut cbs: Cbs, bypass, prioritize| {
+                cbs.set_bypass(bypass);
+                cbs.set_prioritize_conflicts(prioritize);
+                cbs.set_max_expansions(2000);
+                cbs.solve(
+                    &mut pool,
+                    &mut owner,
+                    &mut NoCornerCutting::new(&map),
+                    |goal| distance_heuristic(&mut NoCornerCutting::new(&map), goal),
+                    &agents,
+                )
+            };
+            let optimal = match solve(Cbs::new(), true, true) {
+                Some(solution) => solution,
+                None => continue,
+            };
+            check_solution(&agents, &optimal.paths);
+            solved += 1;
+
+            for &(bypass, prioritize) in &[(false, false), (true, false), (false, true)] {
+                if let Some(solution) = solve(Cbs::new(), bypass, prioritize) {
+                    check_solution(&agents, &solution.paths);
+                    assert!((solution.cost - optimal.cost).abs() < 1e-9);
+                }
+            }
+            let mut cbs = Cbs::new();
+            cbs.set_max_classified_conflicts(usize::MAX);
+            if let Some(solution) = solve(cbs, true, true) {
+                check_solution(&agents, &solution.paths);
+                assert!((solution.cost - optimal.cost).abs() < 1e-9);
+            }
+
+            let solution = solve(Cbs::ecbs(1.5), true, true).unwrap();
+            check_solution(&agents, &solution.paths);
+            assert!(solution.cost >= optimal.cost - 1e-9);
+            assert!(solution.cost <= 1.5 * optimal.cost + 1e-9);
+            assert!(solution.lower_bound <= optimal.cost + 1e-9);
+        }
+        assert!(solved >= 5);
+    }
+}
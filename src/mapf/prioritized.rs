@@ -0,0 +1,37 @@
+use crate::expansion_policy::ExpansionPolicy;
+use crate::node_pool::NodePool;
+use crate::Owner;
+
+use super::{space_time_search, Agent, ReservationTable, SpaceTime};
+
+/// Plans paths for each agent in turn, with each agent avoiding the paths of the agents planned
+/// before it. Agents are prioritized in the order they are given.
+///
+/// `h` is called with each agent's goal to create the heuristic for that agent.
+///
+/// Returns `None` if some agent has no path which avoids the higher-priority agents. This can
+/// happen even if a conflict-free solution exists.
+pub fn prioritized_planning<N, E, H>(
+    pool: &mut N,
+    owner: &mut Owner,
+    expansion_policy: &mut E,
+    mut h: impl FnMut((i32, i32)) -> H,
+    agents: &[Agent],
+) -> Option<Vec<Vec<(i32, i32)>>>
+where
+    N: NodePool<(i32, i32, i32)>,
+    E: ExpansionPolicy<(i32, i32)>,
+    H: FnMut((i32, i32)) -> f64,
+{
+    let mut reservations = ReservationTable::new();
+    let mut paths = Vec::with_capacity(agents.len());
+    for (i, agent) in agents.iter().enumerate() {
+        let path = {
+            let mut policy = SpaceTime::new(expansion_policy, &reservations, agent.goal);
+            space_time_search(pool, owner, &mut policy, h(agent.goal), agent.start, 0)?.0
+        };
+        reservations.reserve_path(&path, 0, i, true);
+        paths.push(path);
+    }
+    Some(paths)
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+type Move = ((i32, i32), (i32, i32));
+
+/// Records which cells and moves are occupied by which agents at which times.
+///
+/// Transient reservations hold a cell at a single timestep, or a move between two cells from one
+/// timestep to the next. Permanent reservations hold a cell from some timestep onwards, which is
+/// how agents that have arrived at their goal are represented.
+#[derive(Clone, Debug, Default)]
+pub struct ReservationTable {
+    vertices: HashMap<((i32, i32), i32), usize>,
+    edges: HashMap<(Move, i32), usize>,
+    permanent: HashMap<(i32, i32), (i32, usize)>,
+    latest: HashMap<(i32, i32), i32>,
+    horizon: i32,
+}
+
+impl ReservationTable {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.edges.clear();
+        self.permanent.clear();
+        self.latest.clear();
+        self.horizon = 0;
+    }
+
+    /// The first timestep after all transient reservations and the start of all permanent
+    /// reservations. From this timestep onwards, the table no longer changes over time.
+    pub fn horizon(&self) -> i32 {
+        self.horizon
+    }
+
+    pub fn reserve_vertex(&mut self, cell: (i32, i32), t: i32, agent: usize) {
+        self.vertices.insert((cell, t), agent);
+        let latest = self.latest.entry(cell).or_insert(t);
+        *latest = (*latest).max(t);
+        self.horizon = self.horizon.max(t + 1);
+    }
+
+    /// Reserves the move from `from` at timestep `t` to `to` at timestep `t + 1`.
+    pub fn reserve_edge(&mut self, from: (i32, i32), to: (i32, i32), t: i32, agent: usize) {
+        self.edges.insert(((from, to), t), agent);
+        self.horizon = self.horizon.max(t + 1);
+    }
+
+    /// Reserves `cell` for all timesteps from `t` onwards.
+    pub fn reserve_permanently(&mut self, cell: (i32, i32), t: i32, agent: usize) {
+        let entry = self.permanent.entry(cell).or_insert((t, agent));
+        if t < entry.0 {
+            *entry = (t, agent);
+        }
+        self.horizon = self.horizon.max(t + 1);
+    }
+
+    /// Reserves the cells and moves of a path, where `path[i]` is the cell occupied at timestep
+    /// `start_time + i`. If `stay` is set, the last cell is reserved permanently.
+    pub fn reserve_path(&mut self, path: &[(i32, i32)], start_time: i32, agent: usize, stay: bool) {
+        for (i, &cell) in path.iter().enumerate() {
+            let t = start_time + i as i32;
+            self.reserve_vertex(cell, t, agent);
+            if let Some(&next) = path.get(i + 1) {
+                self.reserve_edge(cell, next, t, agent);
+            }
+        }
+        if let (true, Some(&last)) = (stay, path.last()) {
+            self.reserve_permanently(last, start_time + path.len() as i32 - 1, agent);
+        }
+    }
+
+    /// Returns the agent occupying `cell` at timestep `t`, if any.
+    pub fn vertex_reservation(&self, cell: (i32, i32), t: i32) -> Option<usize> {
+        match self.permanent.get(&cell) {
+            Some(&(from, agent)) if t >= from => Some(agent),
+            _ => self.vertices.get(&(cell, t)).copied(),
+        }
+    }
+
+    /// Returns the agent moving from `from` at timestep `t` to `to` at timestep `t + 1`, if any.
+    pub fn edge_reservation(&self, from: (i32, i32), to: (i32, i32), t: i32) -> Option<usize> {
+        self.edges.get(&((from, to), t)).copied()
+    }
+
+    /// Returns whether an agent which moves from `from` to `to` starting at timestep `t` would
+    /// collide with a reservation, either by arriving in an occupied cell or by swapping places
+    /// with another agent.
+    pub fn is_move_blocked(&self, from: (i32, i32), to: (i32, i32), t: i32) -> bool {
+        self.vertex_reservation(to, t + 1).is_some()
+            || from != to && self.edge_reservation(to, from, t).is_some()
+    }
+
+    /// Returns whether `cell` is unreserved at every timestep after `t`, i.e. whether an agent
+    /// could stay there indefinitely.
+    pub fn is_free_after(&self, cell: (i32, i32), t: i32) -> bool {
+        !self.permanent.contains_key(&cell) && !matches!(self.latest.get(&cell), Some(&l) if l > t)
+    }
+}
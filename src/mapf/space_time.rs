@@ -0,0 +1,181 @@
+use crate::expansion_policy::ExpansionPolicy;
+use crate::node_pool::NodePool;
+use crate::{astar, Edge, Owner, SearchNode};
+
+use super::{extract_path, ReservationTable};
+
+/// The timestep of the virtual vertex which marks arrival at the goal.
+const ARRIVED: i32 = -1;
+
+/// A time-expanded view of a grid expansion policy, where vertex IDs are `(x, y, t)`.
+///
+/// Each move of the underlying policy takes one timestep, and agents may wait in place for one
+/// timestep at a time. Moves which collide with the reservation table are not produced.
+///
+/// Once an agent is at its goal and the goal is free for the rest of time, an edge to the virtual
+/// vertex `(goal.0, goal.1, -1)` is produced, which is what the search should target.
+///
+/// From the horizon onwards, time no longer advances: all vertices at later timesteps are
+/// represented by the vertex at the horizon. Since the reservation table is unchanging after its
+/// own horizon, this is lossless when the horizon is at least that of the reservation table, and
+/// it ensures the search space is finite.
+pub struct SpaceTime<'a, E> {
+    inner: &'a mut E,
+    reservations: &'a ReservationTable,
+    goal: (i32, i32),
+    horizon: i32,
+    wait_cost: f64,
+    edges: Vec<Edge<(i32, i32)>>,
+}
+
+impl<'a, E> SpaceTime<'a, E> {
+    pub fn new(inner: &'a mut E, reservations: &'a ReservationTable, goal: (i32, i32)) -> Self {
+        SpaceTime {
+            inner,
+            reservations,
+            goal,
+            horizon: reservations.horizon(),
+            wait_cost: 1.0,
+            edges: vec![],
+        }
+    }
+
+    pub fn goal(&self) -> (i32, i32) {
+        self.goal
+    }
+
+    /// The vertex ID to search for.
+    pub fn goal_vertex(&self) -> (i32, i32, i32) {
+        (self.goal.0, self.goal.1, ARRIVED)
+    }
+
+    pub fn horizon(&self) -> i32 {
+        self.horizon
+    }
+
+    /// Sets the timestep after which time no longer advances. Reservations at or after the
+    /// horizon which are not permanent are ignored.
+    ///
+    /// note: defaults to the horizon of the reservation table. Setting it earlier than that, as
+    ///       windowed searches do, means conflicts past the horizon are not avoided.
+    pub fn set_horizon(&mut self, horizon: i32) {
+        self.horizon = horizon;
+    }
+
+    pub fn set_wait_cost(&mut self, wait_cost: f64) {
+        self.wait_cost = wait_cost;
+    }
+
+    fn blocked(&self, from: (i32, i32), to: (i32, i32), t: i32) -> bool {
+        if t < self.horizon {
+            self.reservations.is_move_blocked(from, to, t)
+        } else {
+            // transient reservations past the horizon are ignored, but we can query the
+            // permanent ones by asking about a timestep after everything else.
+            let t = self.horizon.max(self.reservations.horizon());
+            self.reservations.vertex_reservation(to, t).is_some()
+        }
+    }
+
+    fn free_after(&self, cell: (i32, i32), t: i32) -> bool {
+        if t < self.horizon {
+            self.reservations.is_free_after(cell, t)
+        } else {
+            let t = self.horizon.max(self.reservations.horizon());
+            self.reservations.vertex_reservation(cell, t).is_none()
+        }
+    }
+}
+
+impl<E: ExpansionPolicy<(i32, i32)>> ExpansionPolicy<(i32, i32, i32)> for SpaceTime<'_, E> {
+    fn expand(
+        &mut self,
+        node: &SearchNode<(i32, i32, i32)>,
+        edges: &mut Vec<Edge<(i32, i32, i32)>>,
+    ) {
+        let (x, y, t) = node.id;
+        if t == ARRIVED {
+            return;
+        }
+        let next_t = (t + 1).min(self.horizon.max(t));
+
+        let spatial = SearchNode {
+            search_num: 0,
+            pqueue_location: 0,
+            expansions: node.expansions,
+            id: (x, y),
+            parent: node.parent.map(|(x, y, _)| (x, y)),
+            g: node.g,
+            lb: node.lb,
+        };
+        let mut spatial_edges = std::mem::take(&mut self.edges);
+        self.inner.expand(&spatial, &mut spatial_edges);
+        for edge in spatial_edges.drain(..) {
+            if !self.blocked((x, y), edge.destination, t) {
+                edges.push(Edge {
+                    destination: (edge.destination.0, edge.destination.1, next_t),
+                    cost: edge.cost,
+                });
+            }
+        }
+        self.edges = spatial_edges;
+
+        // waiting is pointless once time has stopped advancing
+        if next_t != t && !self.blocked((x, y), (x, y), t) {
+            edges.push(Edge {
+                destination: (x, y, next_t),
+                cost: self.wait_cost,
+            });
+        }
+
+        if (x, y) == self.goal && self.free_after((x, y), t) {
+            edges.push(Edge {
+                destination: (x, y, ARRIVED),
+                cost: 0.0,
+            });
+        }
+    }
+}
+
+/// Lifts a spatial heuristic to space-time vertices.
+pub fn space_time_heuristic(
+    mut h: impl FnMut((i32, i32)) -> f64,
+) -> impl FnMut((i32, i32, i32)) -> f64 {
+    move |(x, y, t)| if t == ARRIVED { 0.0 } else { h((x, y)) }
+}
+
+/// Plans a path for an agent at `source` at timestep `start_time` to the goal of the space-time
+/// expansion policy.
+///
+/// The returned path contains the cell the agent occupies at each timestep, starting from
+/// `start_time`, and ends at the goal. It is returned along with its cost. Returns `None` if there
+/// is no such path.
+pub fn space_time_search<N, E>(
+    pool: &mut N,
+    owner: &mut Owner,
+    expansion_policy: &mut SpaceTime<E>,
+    h: impl FnMut((i32, i32)) -> f64,
+    source: (i32, i32),
+    start_time: i32,
+) -> Option<(Vec<(i32, i32)>, f64)>
+where
+    N: NodePool<(i32, i32, i32)>,
+    E: ExpansionPolicy<(i32, i32)>,
+{
+    assert!(start_time >= 0, "start time must not be negative");
+    let goal = expansion_policy.goal_vertex();
+    let source = (source.0, source.1, start_time);
+    astar(
+        pool,
+        owner,
+        expansion_policy,
+        space_time_heuristic(h),
+        source,
+        goal,
+    );
+
+    let path = extract_path(pool, owner, goal)?;
+    let node = pool.generate(goal, owner);
+    let cost = owner.ro(node).g;
+    Some((path, cost))
+}
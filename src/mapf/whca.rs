@@ -0,0 +1,99 @@
+use crate::expansion_policy::ExpansionPolicy;
+use crate::node_pool::NodePool;
+use crate::Owner;
+
+use super::{position_at, space_time_search, Agent, ReservationTable, SpaceTime};
+
+/// Windowed Hierarchical Cooperative A*.
+///
+/// Agents plan in priority order against a reservation table, but only avoid each other within a
+/// window of timesteps. After executing half of the window, all agents replan. This scales better
+/// than planning full paths with `prioritized_planning`, and lets agents step aside for
+/// higher-priority agents after they have reached their goals.
+///
+/// The heuristic given to `solve` should be close to the true distance, such as one computed by a
+/// backwards Dijkstra search, since beyond the window the search is purely spatial.
+pub struct Whca {
+    window: i32,
+    max_timesteps: i32,
+}
+
+impl Whca {
+    pub fn new(window: i32, max_timesteps: i32) -> Self {
+        assert!(window > 0, "window must be positive");
+        Whca {
+            window,
+            max_timesteps,
+        }
+    }
+
+    pub fn window(&self) -> i32 {
+        self.window
+    }
+
+    pub fn max_timesteps(&self) -> i32 {
+        self.max_timesteps
+    }
+
+    /// Routes the agents to their goals, returning the cell each agent occupies at each timestep.
+    ///
+    /// `h` is called with each agent's goal to create the heuristic for that agent.
+    ///
+    /// Returns `None` if some agent cannot find a path within a window, or if the agents have not
+    /// all reached their goals within the maximum number of timesteps.
+    pub fn solve<N, E, H>(
+        &self,
+        pool: &mut N,
+        owner: &mut Owner,
+        expansion_policy: &mut E,
+        mut h: impl FnMut((i32, i32)) -> H,
+        agents: &[Agent],
+    ) -> Option<Vec<Vec<(i32, i32)>>>
+    where
+        N: NodePool<(i32, i32, i32)>,
+        E: ExpansionPolicy<(i32, i32)>,
+        H: FnMut((i32, i32)) -> f64,
+    {
+        let mut trajectories: Vec<_> = agents.iter().map(|a| vec![a.start]).collect();
+        let mut reservations = ReservationTable::new();
+        let mut plans = Vec::with_capacity(agents.len());
+        let step = (self.window / 2).max(1);
+        let mut t = 0;
+
+        while agents
+            .iter()
+            .zip(&trajectories)
+            .any(|(a, p)| p.last() != Some(&a.goal))
+        {
+            if t >= self.max_timesteps {
+                return None;
+            }
+
+            reservations.clear();
+            plans.clear();
+            for (i, agent) in agents.iter().enumerate() {
+                let current = *trajectories[i].last().unwrap();
+                let path = {
+                    let mut policy = SpaceTime::new(expansion_policy, &reservations, agent.goal);
+                    policy.set_horizon(t + self.window);
+                    space_time_search(pool, owner, &mut policy, h(agent.goal), current, t)?.0
+                };
+                let windowed: Vec<_> = (0..=self.window).map(|i| position_at(&path, i)).collect();
+                reservations.reserve_path(&windowed, t, i, false);
+                plans.push(path);
+            }
+
+            for (trajectory, plan) in trajectories.iter_mut().zip(&plans) {
+                trajectory.extend((1..=step).map(|i| position_at(plan, i)));
+            }
+            t += step;
+        }
+
+        for trajectory in &mut trajectories {
+            while trajectory.len() > 1 && trajectory[trajectory.len() - 2] == *trajectory.last()? {
+                trajectory.pop();
+            }
+        }
+        Some(trajectories)
+    }
+}
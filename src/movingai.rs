@@ -2,22 +2,28 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
-use pathfinding::domains::BitGrid;
+use crate::domains::BitGrid;
 
+#[derive(Copy, Clone, Debug)]
 pub struct Problem {
     pub from: (i32, i32),
     pub to: (i32, i32),
 }
 
+/// Loads a scenario file along with its map, which is expected to be named like the scenario file
+/// without the `.scen` extension (e.g. `arena.map.scen` and `arena.map`).
 pub fn load_scenario(scen: &Path) -> Result<(BitGrid, Vec<Problem>), MovingAiParseError> {
-    let map = parse_map(&scen.with_extension("").with_extension("map"))?;
-    let problems = parse_scen(scen, &map)?;
+    let map = load_map(&scen.with_extension("").with_extension("map"))?;
+    let problems = parse_scen(BufReader::new(File::open(scen)?), &map)?;
 
     Ok((map, problems))
 }
 
-fn parse_scen(scen: &Path, map: &BitGrid) -> Result<Vec<Problem>, MovingAiParseError> {
-    let scen = BufReader::new(File::open(scen)?);
+pub fn load_map(map: &Path) -> Result<BitGrid, MovingAiParseError> {
+    parse_map(BufReader::new(File::open(map)?))
+}
+
+pub fn parse_scen(scen: impl BufRead, map: &BitGrid) -> Result<Vec<Problem>, MovingAiParseError> {
     let mut scen = scen.lines();
     let mut next = || match scen.next() {
         Some(v) => Ok(v?),
@@ -66,8 +72,7 @@ fn parse_scen(scen: &Path, map: &BitGrid) -> Result<Vec<Problem>, MovingAiParseE
     Ok(problems)
 }
 
-fn parse_map(map: &Path) -> Result<BitGrid, MovingAiParseError> {
-    let map = BufReader::new(File::open(map)?);
+pub fn parse_map(map: impl BufRead) -> Result<BitGrid, MovingAiParseError> {
     let mut map = map.lines();
     let mut next = || match map.next() {
         Some(v) => Ok(v?),
@@ -0,0 +1,128 @@
+use crate::util::{HexDomain, HexLayout};
+use crate::{Cell, Owner, SearchNode};
+
+use super::NodePool;
+
+/// A node pool for hexagonal grids, with vertices identified by axial coordinates.
+pub struct HexPool {
+    search_num: usize,
+    width: i32,
+    height: i32,
+    layout: HexLayout,
+    grid: Box<[Cell<SearchNode<(i32, i32)>>]>,
+}
+
+impl HexPool {
+    pub fn new(width: i32, height: i32, layout: HexLayout) -> Self {
+        assert!(width > 0 && height > 0, "width and height must be positive");
+        let mut grid = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..height {
+            for x in 0..width {
+                grid.push(Cell::new(SearchNode {
+                    search_num: 0,
+                    expansions: 0,
+                    pqueue_location: 0,
+                    id: layout.from_offset((x, y)),
+                    parent: None,
+                    g: 0.0,
+                    lb: 0.0,
+                }));
+            }
+        }
+        HexPool {
+            search_num: 0,
+            width,
+            height,
+            layout,
+            grid: grid.into_boxed_slice(),
+        }
+    }
+
+    #[track_caller]
+    pub fn get(&self, q: i32, r: i32, owner: &Owner) -> Option<&Cell<SearchNode<(i32, i32)>>> {
+        self.bounds_check(q, r);
+        let cell = unsafe {
+            // SAFETY: bounds checked above
+            self.grid.get_unchecked(self.locate(q, r))
+        };
+        if owner.ro(cell).search_num == self.search_num {
+            Some(cell)
+        } else {
+            None
+        }
+    }
+
+    #[inline(always)]
+    #[track_caller]
+    fn bounds_check(&self, q: i32, r: i32) {
+        let (x, y) = self.layout.to_offset((q, r));
+        if !(0..self.width).contains(&x) || !(0..self.height).contains(&y) {
+            panic!("Hex cell ({}, {}) is out of bounds.", q, r);
+        }
+    }
+
+    #[inline(always)]
+    fn locate(&self, q: i32, r: i32) -> usize {
+        #[cfg(debug_assertions)]
+        self.bounds_check(q, r);
+
+        let (x, y) = self.layout.to_offset((q, r));
+        x as usize + y as usize * self.width as usize
+    }
+}
+
+unsafe impl HexDomain for HexPool {
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn layout(&self) -> HexLayout {
+        self.layout
+    }
+}
+
+impl NodePool<(i32, i32)> for HexPool {
+    fn reset(&mut self, owner: &mut Owner) {
+        match self.search_num.checked_add(1) {
+            Some(ok) => self.search_num = ok,
+            None => {
+                // see GridPool::reset
+                self.search_num = 1;
+                for cell in self.grid.iter() {
+                    owner.rw(cell).search_num = 0;
+                }
+            }
+        }
+    }
+
+    fn generate(&self, (q, r): (i32, i32), owner: &mut Owner) -> &Cell<SearchNode<(i32, i32)>> {
+        self.bounds_check(q, r);
+        unsafe {
+            // SAFETY: Bounds checked above.
+            self.generate_unchecked((q, r), owner)
+        }
+    }
+
+    unsafe fn generate_unchecked(
+        &self,
+        (q, r): (i32, i32),
+        owner: &mut Owner,
+    ) -> &Cell<SearchNode<(i32, i32)>> {
+        let cell = self.grid.get_unchecked(self.locate(q, r));
+        if owner.ro(cell).search_num == self.search_num {
+            cell
+        } else {
+            let n = owner.rw(cell);
+            n.lb = f64::INFINITY;
+            n.g = f64::INFINITY;
+            n.expansions = 0;
+            n.search_num = self.search_num;
+            n.parent = None;
+            cell
+        }
+    }
+}
@@ -27,6 +27,16 @@ impl IndexPool {
             pool: pool.into_boxed_slice(),
         }
     }
+
+    #[track_caller]
+    pub fn get(&self, id: usize, owner: &Owner) -> Option<&Cell<SearchNode<usize>>> {
+        let cell = &self.pool[id];
+        if owner.ro(cell).search_num == self.search_num {
+            Some(cell)
+        } else {
+            None
+        }
+    }
 }
 
 impl NodePool<usize> for IndexPool {
@@ -2,10 +2,14 @@ use crate::{Cell, Owner, SearchNode};
 
 mod gridpool;
 pub use self::gridpool::GridPool;
+mod hexpool;
+pub use self::hexpool::HexPool;
 mod hashpool;
 pub use self::hashpool::HashPool;
 mod indexpool;
 pub use self::indexpool::IndexPool;
+mod voxelpool;
+pub use self::voxelpool::VoxelPool;
 
 pub trait NodePool<VertexId> {
     fn reset(&mut self, owner: &mut Owner);
@@ -0,0 +1,143 @@
+use crate::util::VoxelDomain;
+use crate::{Cell, Owner, SearchNode};
+
+use super::NodePool;
+
+pub struct VoxelPool {
+    search_num: usize,
+    width: i32,
+    height: i32,
+    depth: i32,
+    grid: Box<[Cell<SearchNode<(i32, i32, i32)>>]>,
+}
+
+impl VoxelPool {
+    pub fn new(width: i32, height: i32, depth: i32) -> Self {
+        assert!(
+            width > 0 && height > 0 && depth > 0,
+            "width, height and depth must be positive"
+        );
+        let mut grid = Vec::with_capacity(width as usize * height as usize * depth as usize);
+        for z in 0..depth {
+            for y in 0..height {
+                for x in 0..width {
+                    grid.push(Cell::new(SearchNode {
+                        search_num: 0,
+                        expansions: 0,
+                        pqueue_location: 0,
+                        id: (x, y, z),
+                        parent: None,
+                        g: 0.0,
+                        lb: 0.0,
+                    }));
+                }
+            }
+        }
+        VoxelPool {
+            search_num: 0,
+            width,
+            height,
+            depth,
+            grid: grid.into_boxed_slice(),
+        }
+    }
+
+    #[track_caller]
+    pub fn get(
+        &self,
+        x: i32,
+        y: i32,
+        z: i32,
+        owner: &Owner,
+    ) -> Option<&Cell<SearchNode<(i32, i32, i32)>>> {
+        self.bounds_check(x, y, z);
+        let cell = unsafe {
+            // SAFETY: bounds checked above
+            self.grid.get_unchecked(self.locate(x, y, z))
+        };
+        if owner.ro(cell).search_num == self.search_num {
+            Some(cell)
+        } else {
+            None
+        }
+    }
+
+    #[inline(always)]
+    #[track_caller]
+    fn bounds_check(&self, x: i32, y: i32, z: i32) {
+        if !(0..self.width).contains(&x)
+            || !(0..self.height).contains(&y)
+            || !(0..self.depth).contains(&z)
+        {
+            panic!("Voxel ({}, {}, {}) is out of bounds.", x, y, z);
+        }
+    }
+
+    #[inline(always)]
+    fn locate(&self, x: i32, y: i32, z: i32) -> usize {
+        #[cfg(debug_assertions)]
+        self.bounds_check(x, y, z);
+
+        x as usize + (y as usize + z as usize * self.height as usize) * self.width as usize
+    }
+}
+
+unsafe impl VoxelDomain for VoxelPool {
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn depth(&self) -> i32 {
+        self.depth
+    }
+}
+
+impl NodePool<(i32, i32, i32)> for VoxelPool {
+    fn reset(&mut self, owner: &mut Owner) {
+        match self.search_num.checked_add(1) {
+            Some(ok) => self.search_num = ok,
+            None => {
+                // see GridPool::reset
+                self.search_num = 1;
+                for cell in self.grid.iter() {
+                    owner.rw(cell).search_num = 0;
+                }
+            }
+        }
+    }
+
+    fn generate(
+        &self,
+        (x, y, z): (i32, i32, i32),
+        owner: &mut Owner,
+    ) -> &Cell<SearchNode<(i32, i32, i32)>> {
+        self.bounds_check(x, y, z);
+        unsafe {
+            // SAFETY: Bounds checked above.
+            self.generate_unchecked((x, y, z), owner)
+        }
+    }
+
+    unsafe fn generate_unchecked(
+        &self,
+        (x, y, z): (i32, i32, i32),
+        owner: &mut Owner,
+    ) -> &Cell<SearchNode<(i32, i32, i32)>> {
+        let cell = self.grid.get_unchecked(self.locate(x, y, z));
+        if owner.ro(cell).search_num == self.search_num {
+            cell
+        } else {
+            let n = owner.rw(cell);
+            n.lb = f64::INFINITY;
+            n.g = f64::INFINITY;
+            n.expansions = 0;
+            n.search_num = self.search_num;
+            n.parent = None;
+            cell
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::expansion_policy::ExpansionPolicy;
+use crate::node_pool::NodePool;
+use crate::util::GridDomain;
+use crate::Owner;
+
+use super::{check_bounds, extract_path, lookahead_unchecked, LearnedHeuristic};
+
+/// Learning Real-Time A* with a local search space (LSS-LRTA*).
+///
+/// Each step runs an A* search bounded by the lookahead, then updates the heuristic values of the
+/// expanded cells to the distance-to-frontier computed by a Dijkstra search over the expanded
+/// cells. With a lookahead of 1, this is classic LRTA*.
+pub struct LrtaStar {
+    lookahead: usize,
+    learned: LearnedHeuristic,
+}
+
+impl LrtaStar {
+    pub fn new(width: i32, height: i32, lookahead: usize) -> Self {
+        assert!(lookahead > 0, "lookahead must be positive");
+        LrtaStar {
+            lookahead,
+            learned: LearnedHeuristic::new(width, height),
+        }
+    }
+
+    pub fn lookahead(&self) -> usize {
+        self.lookahead
+    }
+
+    pub fn set_lookahead(&mut self, lookahead: usize) {
+        assert!(lookahead > 0, "lookahead must be positive");
+        self.lookahead = lookahead;
+    }
+
+    pub fn learned(&self) -> &LearnedHeuristic {
+        &self.learned
+    }
+
+    pub fn learned_mut(&mut self) -> &mut LearnedHeuristic {
+        &mut self.learned
+    }
+
+    /// Plans from `current` towards `goal`, updating the learned heuristic and returning the moves
+    /// the agent should commit to (excluding `current`). Returns `None` if the goal is
+    /// unreachable.
+    ///
+    /// `h` is the base heuristic, used for cells that have not been learned yet.
+    pub fn step<N, E>(
+        &mut self,
+        pool: &mut N,
+        owner: &mut Owner,
+        expansion_policy: &mut E,
+        mut h: impl FnMut((i32, i32)) -> f64,
+        current: (i32, i32),
+        goal: (i32, i32),
+    ) -> Option<Vec<(i32, i32)>>
+    where
+        N: NodePool<(i32, i32)> + GridDomain,
+        E: ExpansionPolicy<(i32, i32)> + GridDomain,
+    {
+        check_bounds(pool, expansion_policy, &self.learned, current);
+        unsafe {
+            // SAFETY: We check that the pool and learned heuristic table are large enough for the
+            //         expansion policy. The expansion policy guarantees that it never produces
+            //         edges leading out-of-bounds. We check that the current vertex is in-bounds.
+            let learned = &self.learned;
+            let search = lookahead_unchecked(
+                pool,
+                owner,
+                expansion_policy,
+                |id| learned.h_unchecked(id, &mut h),
+                current,
+                goal,
+                self.lookahead,
+            );
+            let &target = search.frontier.first()?;
+
+            // Dijkstra backwards from the frontier over the expanded cells.
+            let index: HashMap<_, _> = search
+                .closed
+                .iter()
+                .enumerate()
+                .map(|(i, &id)| (id, i))
+                .collect();
+            let mut distance = vec![f64::INFINITY; search.closed.len()];
+            let mut predecessors = vec![vec![]; search.closed.len()];
+            let mut edges = vec![];
+            for (i, &id) in search.closed.iter().enumerate() {
+                let node = pool.generate_unchecked(id, owner);
+                let node = *owner.ro(node);
+                expansion_policy.expand_unchecked(&node, &mut edges);
+                for edge in edges.drain(..) {
+                    match index.get(&edge.destination) {
+                        Some(&j) => predecessors[j].push((i, edge.cost)),
+                        None => {
+                            let (x, y) = edge.destination;
+                            let h = self
+                                .learned
+                                .get_unchecked(x, y)
+                                .unwrap_or_else(|| h((x, y)));
+                            distance[i] = distance[i].min(edge.cost + h);
+                        }
+                    }
+                }
+            }
+
+            let mut queue: BinaryHeap<_> = distance
+                .iter()
+                .enumerate()
+                .map(|(i, &d)| Entry(d, i))
+                .collect();
+            while let Some(Entry(d, i)) = queue.pop() {
+                if d > distance[i] {
+                    continue;
+                }
+                for &(p, cost) in &predecessors[i] {
+                    if d + cost < distance[p] {
+                        distance[p] = d + cost;
+                        queue.push(Entry(d + cost, p));
+                    }
+                }
+            }
+
+            for (&(x, y), &d) in search.closed.iter().zip(&distance) {
+                let old = self
+                    .learned
+                    .get_unchecked(x, y)
+                    .unwrap_or_else(|| h((x, y)));
+                self.learned.set_unchecked(x, y, old.max(d));
+            }
+
+            Some(extract_path(pool, owner, target))
+        }
+    }
+}
+
+struct Entry(f64, usize);
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reversed, since BinaryHeap is a max-heap
+        other.0.partial_cmp(&self.0).unwrap_or(Ordering::Equal)
+    }
+}
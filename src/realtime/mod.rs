@@ -0,0 +1,299 @@
+use crate::expansion_policy::ExpansionPolicy;
+use crate::node_pool::NodePool;
+use crate::pqueue::PriorityQueue;
+use crate::util::GridDomain;
+use crate::Owner;
+
+mod lrta_star;
+pub use self::lrta_star::LrtaStar;
+mod rtaa_star;
+pub use self::rtaa_star::RtaaStar;
+
+/// A table of heuristic values learned by real-time search agents, falling back to a base
+/// heuristic for cells which have not been learned yet.
+///
+/// Learned values are only meaningful for the goal they were learned towards, so the table should
+/// be cleared when the goal changes.
+pub struct LearnedHeuristic {
+    width: i32,
+    height: i32,
+    values: Box<[f64]>,
+}
+
+impl LearnedHeuristic {
+    pub fn new(width: i32, height: i32) -> Self {
+        assert!(width > 0 && height > 0, "width and height must be positive");
+        LearnedHeuristic {
+            width,
+            height,
+            values: vec![f64::NAN; width as usize * height as usize].into_boxed_slice(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    #[track_caller]
+    pub fn get(&self, x: i32, y: i32) -> Option<f64> {
+        self.bounds_check(x, y);
+        unsafe { self.get_unchecked(x, y) }
+    }
+
+    #[track_caller]
+    pub fn set(&mut self, x: i32, y: i32, h: f64) {
+        self.bounds_check(x, y);
+        unsafe { self.set_unchecked(x, y, h) }
+    }
+
+    /// SAFETY: `x` must be in `0..width`, `y` must be in `0..height`.
+    #[inline(always)]
+    unsafe fn h_unchecked(&self, (x, y): (i32, i32), base: impl FnOnce((i32, i32)) -> f64) -> f64 {
+        self.get_unchecked(x, y).unwrap_or_else(|| base((x, y)))
+    }
+
+    /// Forgets all learned values.
+    pub fn clear(&mut self) {
+        self.values.fill(f64::NAN);
+    }
+
+    /// SAFETY: `x` must be in `0..width`, `y` must be in `0..height`.
+    #[inline(always)]
+    pub unsafe fn get_unchecked(&self, x: i32, y: i32) -> Option<f64> {
+        let h = *self.values.get_unchecked(self.locate(x, y));
+        if h.is_nan() {
+            None
+        } else {
+            Some(h)
+        }
+    }
+
+    /// SAFETY: `x` must be in `0..width`, `y` must be in `0..height`.
+    #[inline(always)]
+    pub unsafe fn set_unchecked(&mut self, x: i32, y: i32, h: f64) {
+        *self.values.get_unchecked_mut(self.locate(x, y)) = h;
+    }
+
+    #[inline(always)]
+    fn locate(&self, x: i32, y: i32) -> usize {
+        #[cfg(debug_assertions)]
+        self.bounds_check(x, y);
+
+        x as usize + y as usize * self.width as usize
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    fn bounds_check(&self, x: i32, y: i32) {
+        if !(0..self.width).contains(&x) || !(0..self.height).contains(&y) {
+            panic!("Grid cell ({}, {}) is out of bounds.", x, y);
+        }
+    }
+}
+
+/// The result of an expansion-bounded A* search.
+struct Lookahead {
+    /// The expanded vertices, deduplicated.
+    closed: Vec<(i32, i32)>,
+    /// The generated but unexpanded vertices in order of priority, so the first is the one the
+    /// agent should move towards. Empty if the goal is unreachable.
+    frontier: Vec<(i32, i32)>,
+}
+
+#[track_caller]
+fn check_bounds<N, E>(
+    pool: &N,
+    expansion_policy: &E,
+    learned: &LearnedHeuristic,
+    source: (i32, i32),
+) where
+    N: GridDomain,
+    E: GridDomain,
+{
+    assert!(pool.width() >= expansion_policy.width());
+    assert!(pool.height() >= expansion_policy.height());
+    assert!(learned.width() >= expansion_policy.width());
+    assert!(learned.height() >= expansion_policy.height());
+    assert!(source.0 >= 0 && source.0 < expansion_policy.width());
+    assert!(source.1 >= 0 && source.1 < expansion_policy.height());
+}
+
+/// Runs A* from `source`, stopping once `lookahead` vertices have been expanded or the goal is
+/// about to be expanded.
+///
+/// SAFETY: The caller must ensure the invariants checked by `check_bounds` hold.
+unsafe fn lookahead_unchecked<N, E>(
+    pool: &mut N,
+    owner: &mut Owner,
+    expansion_policy: &mut E,
+    mut h: impl FnMut((i32, i32)) -> f64,
+    source: (i32, i32),
+    goal: (i32, i32),
+    lookahead: usize,
+) -> Lookahead
+where
+    N: NodePool<(i32, i32)>,
+    E: ExpansionPolicy<(i32, i32)>,
+{
+    assert!(lookahead > 0, "lookahead must be positive");
+
+    pool.reset(owner);
+    let pool = &*pool;
+    let mut queue = PriorityQueue::new();
+    let mut edges = vec![];
+    let mut closed = vec![];
+
+    let source = pool.generate_unchecked(source, owner);
+    owner.rw(source).g = 0.0;
+    owner.rw(source).lb = h(owner.ro(source).id);
+    queue.decrease_key(source, owner);
+
+    while let Some(node) = queue.pop(owner) {
+        let n = owner.rw(node);
+        if n.id == goal || closed.len() >= lookahead {
+            queue.decrease_key(node, owner);
+            break;
+        }
+        n.expansions += 1;
+        closed.push(n.id);
+
+        expansion_policy.expand_unchecked(n, &mut edges);
+
+        let parent_g = n.g;
+        let parent_id = n.id;
+
+        for edge in edges.drain(..) {
+            let g = parent_g + edge.cost;
+            let node = pool.generate_unchecked(edge.destination, owner);
+            let n = owner.rw(node);
+            if g < n.g {
+                n.g = g;
+                n.lb = g + h(n.id);
+                n.parent = Some(parent_id);
+                queue.decrease_key(node, owner);
+            }
+        }
+    }
+
+    let mut frontier = vec![];
+    while let Some(node) = queue.pop(owner) {
+        frontier.push(owner.ro(node).id);
+    }
+    closed.sort_unstable();
+    closed.dedup();
+
+    Lookahead { closed, frontier }
+}
+
+/// Traces the search tree of the last lookahead search back from `to`, excluding the root.
+fn extract_path(
+    pool: &impl NodePool<(i32, i32)>,
+    owner: &mut Owner,
+    to: (i32, i32),
+) -> Vec<(i32, i32)> {
+    let mut path = vec![];
+    let mut current = to;
+    loop {
+        let node = pool.generate(current, owner);
+        match owner.ro(node).parent {
+            Some(parent) => {
+                path.push(current);
+                current = parent;
+            }
+            None => break,
+        }
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::random_grid;
+    use crate::expansion_policy::bitgrid::no_corner_cutting::NoCornerCutting;
+    use crate::node_pool::GridPool;
+    use crate::util::{grid_search, octile_heuristic};
+    use rand_pcg::Pcg64;
+
+    fn trial(
+        mut step: impl FnMut(&mut GridPool, &mut Owner, (i32, i32)) -> Option<Vec<(i32, i32)>>,
+        pool: &mut GridPool,
+        owner: &mut Owner,
+        source: (i32, i32),
+        goal: (i32, i32),
+    ) -> f64 {
+        let mut current = source;
+        let mut cost = 0.0;
+        while current != goal {
+            for next in step(pool, owner, current).unwrap() {
+                let diagonal = next.0 != current.0 && next.1 != current.1;
+                cost += if diagonal {
+                    std::f64::consts::SQRT_2
+                } else {
+                    1.0
+                };
+                current = next;
+            }
+        }
+        cost
+    }
+
+    #[test]
+    fn check_convergence() {
+        let mut rng = Pcg64::new(0xcafef00dd15ea5e5, 0xa02bdbf7bb3c0a7ac28fa16a64abf96);
+        let mut map = random_grid(&mut rng, 24, 24, 0.2);
+        let source = (0, 0);
+        let goal = (23, 23);
+        map.set(source.0, source.1, false);
+        map.set(goal.0, goal.1, false);
+
+        let mut pool = GridPool::new(map.width(), map.height());
+        let mut owner = Owner::new();
+        grid_search(
+            &mut pool,
+            &mut owner,
+            &mut NoCornerCutting::new(&map),
+            octile_heuristic(goal, 1.0),
+            source,
+            goal,
+        );
+        let optimal = owner.ro(pool.get(goal.0, goal.1, &owner).unwrap()).g;
+        assert!(optimal.is_finite());
+
+        let mut lrta = LrtaStar::new(map.width(), map.height(), 4);
+        let mut rtaa = RtaaStar::new(map.width(), map.height(), 4);
+        let mut lrta_cost = f64::INFINITY;
+        let mut rtaa_cost = f64::INFINITY;
+        for _ in 0..200 {
+            lrta_cost = trial(
+                |pool, owner, current| {
+                    let ep = &mut NoCornerCutting::new(&map);
+                    lrta.step(pool, owner, ep, octile_heuristic(goal, 1.0), current, goal)
+                },
+                &mut pool,
+                &mut owner,
+                source,
+                goal,
+            );
+            rtaa_cost = trial(
+                |pool, owner, current| {
+                    let ep = &mut NoCornerCutting::new(&map);
+                    rtaa.step(pool, owner, ep, octile_heuristic(goal, 1.0), current, goal)
+                },
+                &mut pool,
+                &mut owner,
+                source,
+                goal,
+            );
+        }
+        assert!((lrta_cost - optimal).abs() < 1e-9);
+        assert!((rtaa_cost - optimal).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::expansion_policy::ExpansionPolicy;
+use crate::node_pool::NodePool;
+use crate::util::GridDomain;
+use crate::Owner;
+
+use super::{check_bounds, extract_path, lookahead_unchecked, LearnedHeuristic};
+
+/// Real-Time Adaptive A*.
+///
+/// Each step runs an A* search bounded by the lookahead, then sets the heuristic value of every
+/// expanded cell to `f - g`, where `f` is the f-value of the best frontier cell. This learns less
+/// per step than `LrtaStar`, but the update is much cheaper.
+pub struct RtaaStar {
+    lookahead: usize,
+    learned: LearnedHeuristic,
+}
+
+impl RtaaStar {
+    pub fn new(width: i32, height: i32, lookahead: usize) -> Self {
+        assert!(lookahead > 0, "lookahead must be positive");
+        RtaaStar {
+            lookahead,
+            learned: LearnedHeuristic::new(width, height),
+        }
+    }
+
+    pub fn lookahead(&self) -> usize {
+        self.lookahead
+    }
+
+    pub fn set_lookahead(&mut self, lookahead: usize) {
+        assert!(lookahead > 0, "lookahead must be positive");
+        self.lookahead = lookahead;
+    }
+
+    pub fn learned(&self) -> &LearnedHeuristic {
+        &self.learned
+    }
+
+    pub fn learned_mut(&mut self) -> &mut LearnedHeuristic {
+        &mut self.learned
+    }
+
+    /// Plans from `current` towards `goal`, updating the learned heuristic and returning the moves
+    /// the agent should commit to (excluding `current`). Returns `None` if the goal is
+    /// unreachable.
+    ///
+    /// `h` is the base heuristic, used for cells that have not been learned yet.
+    pub fn step<N, E>(
+        &mut self,
+        pool: &mut N,
+        owner: &mut Owner,
+        expansion_policy: &mut E,
+        mut h: impl FnMut((i32, i32)) -> f64,
+        current: (i32, i32),
+        goal: (i32, i32),
+    ) -> Option<Vec<(i32, i32)>>
+    where
+        N: NodePool<(i32, i32)> + GridDomain,
+        E: ExpansionPolicy<(i32, i32)> + GridDomain,
+    {
+        check_bounds(pool, expansion_policy, &self.learned, current);
+        unsafe {
+            // SAFETY: We check that the pool and learned heuristic table are large enough for the
+            //         expansion policy. The expansion policy guarantees that it never produces
+            //         edges leading out-of-bounds. We check that the current vertex is in-bounds.
+            let learned = &self.learned;
+            let search = lookahead_unchecked(
+                pool,
+                owner,
+                expansion_policy,
+                |id| learned.h_unchecked(id, &mut h),
+                current,
+                goal,
+                self.lookahead,
+            );
+            let &target = search.frontier.first()?;
+
+            let target_node = pool.generate_unchecked(target, owner);
+            let f = owner.ro(target_node).lb;
+            for &(x, y) in &search.closed {
+                let node = pool.generate_unchecked((x, y), owner);
+                let g = owner.ro(node).g;
+                let old = self
+                    .learned
+                    .get_unchecked(x, y)
+                    .unwrap_or_else(|| h((x, y)));
+                self.learned.set_unchecked(x, y, old.max(f - g));
+            }
+
+            Some(extract_path(pool, owner, target))
+        }
+    }
+}
@@ -0,0 +1,518 @@
+//! Loading of ROS `map_server` occupancy grids: a YAML metadata file next to a netpbm image.
+//!
+//! ```text
+//! image: map.pgm
+//! resolution: 0.05
+//! origin: [-10.0, -10.0, 0.0]
+//! occupied_thresh: 0.65
+//! free_thresh: 0.196
+//! negate: 0
+//! ```
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use crate::domains::{BitGrid, WeightedGrid};
+
+/// A grayscale netpbm image (P1, P2, P4 or P5), with pixels scaled to `0` for black to `255` for
+/// white.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Netpbm {
+    pub width: i32,
+    pub height: i32,
+    /// Pixels in row-major order, starting at the top-left.
+    pub pixels: Vec<u8>,
+}
+
+pub fn load_netpbm(path: &Path) -> Result<Netpbm, RosMapError> {
+    parse_netpbm(BufReader::new(File::open(path)?))
+}
+
+pub fn parse_netpbm(mut input: impl Read) -> Result<Netpbm, RosMapError> {
+    let mut data = vec![];
+    input.read_to_end(&mut data)?;
+    let mut pos = 0;
+
+    let magic = token(&data, &mut pos)?;
+    let (binary, bitmap) = match magic {
+        b"P1" => (false, true),
+        b"P2" => (false, false),
+        b"P4" => (true, true),
+        b"P5" => (true, false),
+        _ => return Err(RosMapError::InvalidHeader),
+    };
+    let width: i32 = number(&data, &mut pos)?;
+    let height: i32 = number(&data, &mut pos)?;
+    if width <= 0 || height <= 0 {
+        return Err(RosMapError::InvalidData);
+    }
+    let max: u32 = if bitmap { 1 } else { number(&data, &mut pos)? };
+    if max == 0 || max > u16::MAX as u32 {
+        return Err(RosMapError::InvalidHeader);
+    }
+    // binary data starts after a single whitespace character
+    pos += 1;
+
+    let len = width as usize * height as usize;
+    // every pixel takes at least one bit, so don't trust the header further than the data goes
+    let remaining = data.len().saturating_sub(pos);
+    let mut pixels = Vec::with_capacity(len.min(remaining.saturating_mul(8)));
+    let scale = |v: u32| -> Result<u8, RosMapError> {
+        if v > max {
+            return Err(RosMapError::InvalidData);
+        }
+        // bitmaps use 1 for black
+        let v = if bitmap { max - v } else { v };
+        Ok((v * 255 / max) as u8)
+    };
+    match (binary, bitmap) {
+        (false, _) => {
+            for _ in 0..len {
+                if bitmap {
+                    // bitmap digits need not be separated by whitespace
+                    skip_whitespace(&data, &mut pos);
+                    let d = *data.get(pos).ok_or(RosMapError::UnexpectedEof)?;
+                    pos += 1;
+                    match d {
+                        b'0' | b'1' => pixels.push(scale((d - b'0') as u32)?),
+                        _ => return Err(RosMapError::InvalidData),
+                    }
+                } else {
+                    pixels.push(scale(number(&data, &mut pos)?)?);
+                }
+            }
+        }
+        (true, true) => {
+            let row_bytes = (width as usize - 1) / 8 + 1;
+            let rows = data.get(pos..pos + row_bytes * height as usize);
+            let rows = rows.ok_or(RosMapError::UnexpectedEof)?;
+            for row in rows.chunks(row_bytes) {
+                for x in 0..width as usize {
+                    pixels.push(scale((row[x / 8] >> (7 - x % 8) & 1) as u32)?);
+                }
+            }
+        }
+        (true, false) => {
+            let bytes = if max > 255 { 2 } else { 1 };
+            let raw = data.get(pos..pos + len * bytes);
+            let raw = raw.ok_or(RosMapError::UnexpectedEof)?;
+            for p in raw.chunks(bytes) {
+                // 16 bit samples are big-endian
+                let v = p.iter().fold(0, |acc, &b| acc << 8 | b as u32);
+                pixels.push(scale(v)?);
+            }
+        }
+    }
+
+    Ok(Netpbm {
+        width,
+        height,
+        pixels,
+    })
+}
+
+fn skip_whitespace(data: &[u8], pos: &mut usize) {
+    while let Some(&c) = data.get(*pos) {
+        if c == b'#' {
+            while matches!(data.get(*pos), Some(&c) if c != b'\n') {
+                *pos += 1;
+            }
+        } else if c.is_ascii_whitespace() {
+            *pos += 1;
+        } else {
+            break;
+        }
+    }
+}
+
+fn token<'a>(data: &'a [u8], pos: &mut usize) -> Result<&'a [u8], RosMapError> {
+    skip_whitespace(data, pos);
+    let start = *pos;
+    while matches!(data.get(*pos), Some(c) if !c.is_ascii_whitespace()) {
+        *pos += 1;
+    }
+    if start == *pos {
+        return Err(RosMapError::UnexpectedEof);
+    }
+    Ok(&data[start..*pos])
+}
+
+fn number<T: std::str::FromStr>(data: &[u8], pos: &mut usize) -> Result<T, RosMapError> {
+    let t = token(data, pos)?;
+    std::str::from_utf8(t)
+        .ok()
+        .and_then(|t| t.parse().ok())
+        .ok_or(RosMapError::InvalidData)
+}
+
+/// The contents of a `map_server` YAML file. The `mode` key is ignored, since occupancy
+/// probabilities are kept as they are.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapMetadata {
+    pub image: PathBuf,
+    /// The size of a cell in metres.
+    pub resolution: f64,
+    /// The pose `[x, y, yaw]` of the bottom-left cell in the world.
+    pub origin: [f64; 3],
+    pub negate: bool,
+    pub occupied_thresh: f64,
+    pub free_thresh: f64,
+}
+
+/// Parses the subset of YAML used by `map_server` files: one `key: value` pair per line.
+pub fn parse_map_metadata(yaml: &str) -> Result<MapMetadata, RosMapError> {
+    let (mut image, mut resolution, mut origin) = (None, None, None);
+    let (mut negate, mut occupied_thresh, mut free_thresh) = (false, 0.65, 0.196);
+    for line in yaml.lines() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (key, value) = line.split_once(':').ok_or(RosMapError::InvalidData)?;
+        let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+        match key.trim() {
+            "image" => image = Some(PathBuf::from(value)),
+            "resolution" => resolution = Some(value.parse()?),
+            "origin" => {
+                let list = value.strip_prefix('[').and_then(|v| v.strip_suffix(']'));
+                let list = list.ok_or(RosMapError::InvalidData)?;
+                let mut o = [0.0; 3];
+                let mut items = list.split(',');
+                for v in &mut o {
+                    *v = items
+                        .next()
+                        .ok_or(RosMapError::InvalidData)?
+                        .trim()
+                        .parse()?;
+                }
+                if items.next().is_some() {
+                    return Err(RosMapError::InvalidData);
+                }
+                origin = Some(o);
+            }
+            "negate" => {
+                negate = match value {
+                    "0" | "false" => false,
+                    "1" | "true" => true,
+                    _ => return Err(RosMapError::InvalidData),
+                }
+            }
+            "occupied_thresh" => occupied_thresh = value.parse()?,
+            "free_thresh" => free_thresh = value.parse()?,
+            _ => {}
+        }
+    }
+    Ok(MapMetadata {
+        image: image.ok_or(RosMapError::MissingKey("image"))?,
+        resolution: resolution.ok_or(RosMapError::MissingKey("resolution"))?,
+        origin: origin.ok_or(RosMapError::MissingKey("origin"))?,
+        negate,
+        occupied_thresh,
+        free_thresh,
+    })
+}
+
+/// Converts between grid cells and world coordinates in metres. Cell `(0, 0)` is the top-left
+/// pixel of the image, while the origin is the pose of the bottom-left pixel.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MapTransform {
+    pub resolution: f64,
+    pub origin: [f64; 3],
+    pub height: i32,
+}
+
+impl MapTransform {
+    /// Returns the world position of the center of the cell.
+    pub fn cell_to_world(&self, (x, y): (i32, i32)) -> (f64, f64) {
+        let lx = (x as f64 + 0.5) * self.resolution;
+        let ly = ((self.height - 1 - y) as f64 + 0.5) * self.resolution;
+        let (sin, cos) = self.origin[2].sin_cos();
+        (
+            self.origin[0] + lx * cos - ly * sin,
+            self.origin[1] + lx * sin + ly * cos,
+        )
+    }
+
+    /// Returns the cell containing the world position, which may be out of bounds.
+    pub fn world_to_cell(&self, (wx, wy): (f64, f64)) -> (i32, i32) {
+        let (dx, dy) = (wx - self.origin[0], wy - self.origin[1]);
+        let (sin, cos) = self.origin[2].sin_cos();
+        let lx = (dx * cos + dy * sin) / self.resolution;
+        let ly = (-dx * sin + dy * cos) / self.resolution;
+        (lx.floor() as i32, self.height - 1 - ly.floor() as i32)
+    }
+
+    pub fn path_to_world(&self, path: &[(i32, i32)]) -> Vec<(f64, f64)> {
+        path.iter().map(|&c| self.cell_to_world(c)).collect()
+    }
+}
+
+/// An occupancy grid, storing the probability that each cell is occupied.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RosMap {
+    width: i32,
+    height: i32,
+    occupancy: Box<[f64]>,
+    occupied_thresh: f64,
+    free_thresh: f64,
+    transform: MapTransform,
+}
+
+/// Loads a `map_server` YAML file and the image it refers to, which is relative to the YAML file.
+pub fn load_ros_map(yaml: &Path) -> Result<RosMap, RosMapError> {
+    let mut text = String::new();
+    File::open(yaml)?.read_to_string(&mut text)?;
+    let metadata = parse_map_metadata(&text)?;
+    let image = load_netpbm(&yaml.with_file_name(&metadata.image))?;
+    Ok(RosMap::new(&image, &metadata))
+}
+
+impl RosMap {
+    pub fn new(image: &Netpbm, metadata: &MapMetadata) -> Self {
+        let occupancy = image
+            .pixels
+            .iter()
+            .map(|&p| {
+                let p = p as f64 / 255.0;
+                // dark pixels are occupied unless negated
+                if metadata.negate {
+                    p
+                } else {
+                    1.0 - p
+                }
+            })
+            .collect();
+        RosMap {
+            width: image.width,
+            height: image.height,
+            occupancy,
+            occupied_thresh: metadata.occupied_thresh,
+            free_thresh: metadata.free_thresh,
+            transform: MapTransform {
+                resolution: metadata.resolution,
+                origin: metadata.origin,
+                height: image.height,
+            },
+        }
+    }
+
+    #[inline(always)]
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn transform(&self) -> &MapTransform {
+        &self.transform
+    }
+
+    /// Returns the probability that the cell is occupied.
+    #[track_caller]
+    pub fn occupancy(&self, x: i32, y: i32) -> f64 {
+        if !(0..self.width).contains(&x) || !(0..self.height).contains(&y) {
+            panic!("Grid cell ({}, {}) is out of bounds.", x, y);
+        }
+        self.occupancy[x as usize + y as usize * self.width as usize]
+    }
+
+    /// Returns whether the cell is known to be occupied.
+    pub fn is_occupied(&self, x: i32, y: i32) -> bool {
+        self.occupancy(x, y) > self.occupied_thresh
+    }
+
+    /// Returns whether the cell is known to be free.
+    pub fn is_free(&self, x: i32, y: i32) -> bool {
+        self.occupancy(x, y) < self.free_thresh
+    }
+
+    /// Builds a `BitGrid` in which occupied cells are obstructed. Cells which are neither known to
+    /// be free nor occupied are obstructed if `unknown_obstructed` is set.
+    pub fn to_bitgrid(&self, unknown_obstructed: bool) -> BitGrid {
+        let mut map = BitGrid::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let blocked = if unknown_obstructed {
+                    !self.is_free(x, y)
+                } else {
+                    self.is_occupied(x, y)
+                };
+                map.set(x, y, blocked);
+            }
+        }
+        map
+    }
+
+    /// Builds a `WeightedGrid` of occupancy probabilities, in which occupied cells are obstructed.
+    pub fn to_weighted_grid(&self) -> WeightedGrid<f64> {
+        let mut map = WeightedGrid::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !self.is_occupied(x, y) {
+                    *map.get_mut(x, y) = Some(self.occupancy(x, y));
+                }
+            }
+        }
+        map
+    }
+}
+
+#[derive(Debug)]
+pub enum RosMapError {
+    Stdio(std::io::Error),
+    ParseError(std::num::ParseFloatError),
+    InvalidHeader,
+    InvalidData,
+    UnexpectedEof,
+    MissingKey(&'static str),
+}
+
+impl From<std::io::Error> for RosMapError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Stdio(e)
+    }
+}
+
+impl From<std::num::ParseFloatError> for RosMapError {
+    fn from(e: std::num::ParseFloatError) -> Self {
+        Self::ParseError(e)
+    }
+}
+
+impl std::fmt::Display for RosMapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stdio(e) => write!(f, "{}", e),
+            Self::ParseError(e) => write!(f, "{}", e),
+            Self::InvalidHeader => write!(f, "Invalid file header"),
+            Self::UnexpectedEof => write!(f, "Expected more data, but got EOF"),
+            Self::InvalidData => write!(f, "Invalid data provided"),
+            Self::MissingKey(k) => write!(f, "Missing key {:?}", k),
+        }
+    }
+}
+
+impl std::error::Error for RosMapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Stdio(e) => Some(e),
+            Self::ParseError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::MapImage;
+
+    #[test]
+    fn check_formats() {
+        // the same 3x2 image in each format
+        let expected = vec![255, 0, 255, 0, 0, 255];
+        let p1 = b"P1\n# comment\n3 2\n010\n110\n";
+        let p2 = b"P2 3 2 4\n4 0 4 # comment\n0 0 4\n";
+        let p4 = b"P4\n3 2\n\x40\xc0";
+        let p5 = b"P5\n3 2\n255\n\xff\x00\xff\x00\x00\xff";
+        let p5_16 = b"P5\n3 2\n1000\n\x03\xe8\x00\x00\x03\xe8\x00\x00\x00\x00\x03\xe8";
+        for data in [&p1[..], &p2[..], &p4[..], &p5[..], &p5_16[..]] {
+            let image = parse_netpbm(data).unwrap();
+            assert_eq!((image.width, image.height), (3, 2));
+            assert_eq!(image.pixels, expected);
+        }
+        assert!(matches!(
+            parse_netpbm(&b"P6\n3 2\n255\n"[..]),
+            Err(RosMapError::InvalidHeader)
+        ));
+        assert!(matches!(
+            parse_netpbm(&b"P5\n3 2\n255\n\x00"[..]),
+            Err(RosMapError::UnexpectedEof)
+        ));
+        for data in [
+            &b"P2 2000000000 2000000000 255\n0"[..],
+            &b"P4 2000000000 2000000000\n\x00"[..],
+        ] {
+            assert!(matches!(
+                parse_netpbm(data),
+                Err(RosMapError::UnexpectedEof)
+            ));
+        }
+    }
+
+    #[test]
+    fn check_ros_map() {
+        let yaml = "
+            image: \"map.pgm\"
+            resolution: 0.5
+            origin: [-1.0, 2.0, 0.0]  # bottom left
+            occupied_thresh: 0.65
+            free_thresh: 0.196
+            negate: 0
+        ";
+        let metadata = parse_map_metadata(yaml).unwrap();
+        assert_eq!(metadata.image, PathBuf::from("map.pgm"));
+        assert_eq!(metadata.origin, [-1.0, 2.0, 0.0]);
+        assert!(matches!(
+            parse_map_metadata("resolution: 1\norigin: [0, 0, 0]"),
+            Err(RosMapError::MissingKey("image"))
+        ));
+
+        // free, unknown and occupied cells
+        let image = Netpbm {
+            width: 3,
+            height: 2,
+            pixels: vec![254, 205, 0, 254, 254, 254],
+        };
+        let map = RosMap::new(&image, &metadata);
+        assert!(map.is_free(0, 0) && !map.is_free(1, 0) && !map.is_occupied(1, 0));
+        assert!(map.is_occupied(2, 0));
+        let bits = map.to_bitgrid(true);
+        assert!(!bits.get(0, 0) && bits.get(1, 0) && bits.get(2, 0));
+        let bits = map.to_bitgrid(false);
+        assert!(!bits.get(0, 0) && !bits.get(1, 0) && bits.get(2, 0));
+        let weights = map.to_weighted_grid();
+        assert_eq!(weights.get(2, 0), None);
+        assert!((weights.get(1, 0).unwrap() - 50.0 / 255.0).abs() < 1e-9);
+
+        // the bottom-left cell is at the origin
+        let t = map.transform();
+        assert_eq!(t.cell_to_world((0, 1)), (-0.75, 2.25));
+        assert_eq!(t.cell_to_world((2, 0)), (0.25, 2.75));
+        for &cell in &[(0, 0), (2, 1), (-3, 7)] {
+            assert_eq!(t.world_to_cell(t.cell_to_world(cell)), cell);
+        }
+        let rotated = MapTransform {
+            origin: [1.0, 1.0, 0.7],
+            ..*t
+        };
+        for &cell in &[(0, 0), (2, 1), (-3, 7)] {
+            assert_eq!(rotated.world_to_cell(rotated.cell_to_world(cell)), cell);
+        }
+    }
+
+    #[test]
+    fn check_load() {
+        let dir = std::env::temp_dir().join(format!("pathfinding-ros-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut map = BitGrid::new(13, 7);
+        map.fill_rect(2, 1, 5, 3, true);
+        let mut pgm = File::create(dir.join("map.pgm")).unwrap();
+        MapImage::from_bitgrid(&map).write_pgm(&mut pgm, 1).unwrap();
+        std::fs::write(
+            dir.join("map.yaml"),
+            "image: map.pgm\nresolution: 0.05\norigin: [0.0, 0.0, 0.0]\n",
+        )
+        .unwrap();
+
+        let loaded = load_ros_map(&dir.join("map.yaml")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let loaded = loaded.to_bitgrid(true);
+        assert_eq!(loaded.to_string(), map.to_string());
+    }
+}
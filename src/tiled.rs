@@ -0,0 +1,743 @@
+//! Importing of [Tiled](https://www.mapeditor.org/) tilemaps, in JSON (`.tmj`/`.json`) or TMX
+//! format.
+//!
+//! Each cell of the resulting grid is decided by a rule which can look at the tiles of any layer at
+//! that cell and their custom properties:
+//!
+//! ```ignore
+//! let map = load_tiled(Path::new("level.tmj"))?;
+//! // walls are obstructed, and bridges make water walkable
+//! let grid = map.to_bitgrid(|cell| {
+//!     Ok(cell.gid("walls")? != 0
+//!         || cell.property("terrain", "water")? == Some(&Property::Bool(true))
+//!             && cell.gid("bridges")? == 0)
+//! })?;
+//! ```
+//!
+//! Only finite maps with embedded tilesets are supported, and layer data must not be compressed.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::domains::{BitGrid, WeightedGrid};
+
+/// The bits of a global tile ID which store flipping and rotation.
+const FLAGS: u32 = 0xF000_0000;
+
+/// A custom property value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Property {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    /// Strings, colors and files, as well as properties of any other type (such as object
+    /// references and classes) in their raw form.
+    String(String),
+}
+
+impl Property {
+    /// Returns the value as a number, if it is one.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Property::Int(v) => Some(v as f64),
+            Property::Float(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// A tile layer, storing the global tile ID of each cell in row-major order, with flip flags
+/// cleared. Empty cells have an ID of 0.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileLayer {
+    pub name: String,
+    pub data: Vec<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledMap {
+    width: i32,
+    height: i32,
+    layers: Vec<TileLayer>,
+    /// The custom properties of each tile with any, by global tile ID.
+    properties: HashMap<u32, HashMap<String, Property>>,
+}
+
+pub fn load_tiled(path: &Path) -> Result<TiledMap, TiledError> {
+    let mut text = String::new();
+    File::open(path)?.read_to_string(&mut text)?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("tmx") => parse_tmx(&text),
+        _ => parse_tiled_json(&text),
+    }
+}
+
+impl TiledMap {
+    #[inline(always)]
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn layers(&self) -> &[TileLayer] {
+        &self.layers
+    }
+
+    pub fn layer(&self, name: &str) -> Result<&TileLayer, TiledError> {
+        self.layers
+            .iter()
+            .find(|l| l.name == name)
+            .ok_or_else(|| TiledError::MissingLayer(name.to_owned()))
+    }
+
+    /// Returns a custom property of the tile, or `None` if the tile doesn't have it.
+    pub fn tile_property(&self, gid: u32, name: &str) -> Option<&Property> {
+        self.properties.get(&gid)?.get(name)
+    }
+
+    /// Returns a view of the tiles of every layer at the cell.
+    #[track_caller]
+    pub fn cell(&self, x: i32, y: i32) -> TileCell<'_> {
+        if !(0..self.width).contains(&x) || !(0..self.height).contains(&y) {
+            panic!("Grid cell ({}, {}) is out of bounds.", x, y);
+        }
+        TileCell { map: self, x, y }
+    }
+
+    /// Builds a `BitGrid` in which cells are obstructed where `rule` returns `true`.
+    pub fn to_bitgrid(
+        &self,
+        mut rule: impl FnMut(TileCell) -> Result<bool, TiledError>,
+    ) -> Result<BitGrid, TiledError> {
+        let mut map = BitGrid::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                map.set(x, y, rule(self.cell(x, y))?);
+            }
+        }
+        Ok(map)
+    }
+
+    /// Builds a `WeightedGrid` with the weights returned by `rule`.
+    pub fn to_weighted_grid<T>(
+        &self,
+        mut rule: impl FnMut(TileCell) -> Result<Option<T>, TiledError>,
+    ) -> Result<WeightedGrid<T>, TiledError> {
+        let mut map = WeightedGrid::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                *map.get_mut(x, y) = rule(self.cell(x, y))?;
+            }
+        }
+        Ok(map)
+    }
+
+    /// Builds a `BitGrid` in which the cells of the layer are obstructed unless their tile has a
+    /// `walkable` property set to `true`. Empty cells are obstructed.
+    pub fn walkable_bitgrid(&self, layer: &str) -> Result<BitGrid, TiledError> {
+        self.layer(layer)?;
+        self.to_bitgrid(|cell| Ok(!cell.walkable(layer)?))
+    }
+
+    /// Builds a `WeightedGrid` from the `cost` property of the tiles of the layer. Cells whose tile
+    /// is not walkable, as for `walkable_bitgrid`, are obstructed.
+    pub fn cost_grid(&self, layer: &str) -> Result<WeightedGrid<f64>, TiledError> {
+        self.layer(layer)?;
+        self.to_weighted_grid(|cell| {
+            if !cell.walkable(layer)? {
+                return Ok(None);
+            }
+            match cell.property(layer, "cost")? {
+                Some(p) => p
+                    .as_f64()
+                    .map(Some)
+                    .ok_or_else(|| cell.invalid(layer, "cost")),
+                None => Err(cell.missing(layer, "cost")),
+            }
+        })
+    }
+}
+
+/// The tiles at one cell of a `TiledMap`.
+#[derive(Copy, Clone)]
+pub struct TileCell<'a> {
+    map: &'a TiledMap,
+    pub x: i32,
+    pub y: i32,
+}
+
+impl<'a> TileCell<'a> {
+    /// Returns the global tile ID in the layer, which is 0 if the cell is empty.
+    pub fn gid(&self, layer: &str) -> Result<u32, TiledError> {
+        let layer = self.map.layer(layer)?;
+        Ok(layer.data[(self.x + self.y * self.map.width) as usize])
+    }
+
+    /// Returns a custom property of the tile in the layer, or `None` if the cell is empty or the
+    /// tile doesn't have it.
+    pub fn property(&self, layer: &str, name: &str) -> Result<Option<&'a Property>, TiledError> {
+        let gid = self.gid(layer)?;
+        Ok(self.map.tile_property(gid, name))
+    }
+
+    /// Returns the `walkable` property of the tile in the layer, which must be a boolean. Empty
+    /// cells are not walkable.
+    pub fn walkable(&self, layer: &str) -> Result<bool, TiledError> {
+        if self.gid(layer)? == 0 {
+            return Ok(false);
+        }
+        match self.property(layer, "walkable")? {
+            Some(Property::Bool(b)) => Ok(*b),
+            Some(_) => Err(self.invalid(layer, "walkable")),
+            None => Err(self.missing(layer, "walkable")),
+        }
+    }
+
+    pub fn missing(&self, layer: &str, name: &str) -> TiledError {
+        TiledError::MissingProperty {
+            x: self.x,
+            y: self.y,
+            gid: self.gid(layer).unwrap_or(0),
+            name: name.to_owned(),
+        }
+    }
+
+    pub fn invalid(&self, layer: &str, name: &str) -> TiledError {
+        TiledError::InvalidProperty {
+            x: self.x,
+            y: self.y,
+            gid: self.gid(layer).unwrap_or(0),
+            name: name.to_owned(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonMap {
+    width: i32,
+    height: i32,
+    #[serde(default)]
+    infinite: bool,
+    layers: Vec<JsonLayer>,
+    #[serde(default)]
+    tilesets: Vec<JsonTileset>,
+}
+
+#[derive(Deserialize)]
+struct JsonLayer {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    name: String,
+    data: Option<serde_json::Value>,
+    encoding: Option<String>,
+    compression: Option<String>,
+    #[serde(default)]
+    layers: Vec<JsonLayer>,
+}
+
+#[derive(Deserialize)]
+struct JsonTileset {
+    firstgid: u32,
+    source: Option<String>,
+    #[serde(default)]
+    tiles: Vec<JsonTile>,
+}
+
+#[derive(Deserialize)]
+struct JsonTile {
+    id: u32,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+}
+
+#[derive(Deserialize)]
+struct JsonProperty {
+    name: String,
+    #[serde(rename = "type", default)]
+    kind: String,
+    value: serde_json::Value,
+}
+
+pub fn parse_tiled_json(json: &str) -> Result<TiledMap, TiledError> {
+    let map: JsonMap = serde_json::from_str(json)?;
+    if map.infinite {
+        return Err(TiledError::Unsupported("infinite maps"));
+    }
+    let mut result = TiledMap::new(map.width, map.height)?;
+
+    fn add_layers(result: &mut TiledMap, layers: Vec<JsonLayer>) -> Result<(), TiledError> {
+        for layer in layers {
+            match &*layer.kind {
+                "tilelayer" => {
+                    if !matches!(layer.compression.as_deref(), None | Some("")) {
+                        return Err(TiledError::Unsupported("compressed layer data"));
+                    }
+                    let data = match (layer.encoding.as_deref(), layer.data) {
+                        (None, Some(data)) | (Some("csv"), Some(data)) => {
+                            serde_json::from_value::<Vec<u32>>(data)?
+                        }
+                        (Some("base64"), Some(serde_json::Value::String(s))) => base64_gids(&s)?,
+                        (_, None) => return Err(TiledError::Unsupported("infinite maps")),
+                        _ => return Err(TiledError::InvalidData("layer encoding")),
+                    };
+                    result.add_layer(layer.name, data)?;
+                }
+                "group" => add_layers(result, layer.layers)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+    add_layers(&mut result, map.layers)?;
+
+    for tileset in map.tilesets {
+        if tileset.source.is_some() {
+            return Err(TiledError::Unsupported("external tilesets"));
+        }
+        for tile in tileset.tiles {
+            let gid = tile_gid(tileset.firstgid, tile.id)?;
+            for p in tile.properties {
+                let value = match (&*p.kind, p.value) {
+                    ("bool", serde_json::Value::Bool(b)) => Property::Bool(b),
+                    ("int", v) => Property::Int(v.as_i64().ok_or(bad_property(&p.name))?),
+                    ("float", v) => Property::Float(v.as_f64().ok_or(bad_property(&p.name))?),
+                    ("bool", _) => return Err(bad_property(&p.name)),
+                    (_, serde_json::Value::String(s)) => Property::String(s),
+                    // other types, such as object references and classes, are kept as raw JSON
+                    (_, v) => Property::String(v.to_string()),
+                };
+                result.set_property(gid, p.name, value);
+            }
+        }
+    }
+    Ok(result)
+}
+
+pub fn parse_tmx(xml: &str) -> Result<TiledMap, TiledError> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let root = doc.root_element();
+    if !root.has_tag_name("map") {
+        return Err(TiledError::InvalidData("root element"));
+    }
+    if root.attribute("infinite") == Some("1") {
+        return Err(TiledError::Unsupported("infinite maps"));
+    }
+    let mut result = TiledMap::new(number(root, "width")?, number(root, "height")?)?;
+
+    for node in root.descendants().filter(|n| n.is_element()) {
+        match node.tag_name().name() {
+            "layer" => {
+                let data = node
+                    .children()
+                    .find(|n| n.has_tag_name("data"))
+                    .ok_or(TiledError::InvalidData("layer data"))?;
+                if data.attribute("compression").is_some() {
+                    return Err(TiledError::Unsupported("compressed layer data"));
+                }
+                let text = data.text().unwrap_or("");
+                let gids = match data.attribute("encoding") {
+                    Some("csv") => text
+                        .split(',')
+                        .map(|t| t.trim().parse())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| TiledError::InvalidData("layer data"))?,
+                    Some("base64") => base64_gids(text)?,
+                    None => data
+                        .children()
+                        .filter(|n| n.has_tag_name("tile"))
+                        .map(|n| Ok(n.attribute("gid").map_or(Ok(0), str::parse)?))
+                        .collect::<Result<_, TiledError>>()?,
+                    Some(_) => return Err(TiledError::InvalidData("layer encoding")),
+                };
+                let name = node.attribute("name").unwrap_or("").to_owned();
+                result.add_layer(name, gids)?;
+            }
+            "tileset" => {
+                if node.attribute("source").is_some() {
+                    return Err(TiledError::Unsupported("external tilesets"));
+                }
+                let firstgid: u32 = number(node, "firstgid")?;
+                for tile in node.children().filter(|n| n.has_tag_name("tile")) {
+                    let gid = tile_gid(firstgid, number(tile, "id")?)?;
+                    // members of class properties are nested further down, and are not tile
+                    // properties themselves
+                    let properties = tile
+                        .children()
+                        .filter(|n| n.has_tag_name("properties"))
+                        .flat_map(|n| n.children())
+                        .filter(|n| n.has_tag_name("property"));
+                    for p in properties {
+                        let name = p.attribute("name").unwrap_or("");
+                        let value = p.attribute("value").unwrap_or("");
+                        let value = match p.attribute("type").unwrap_or("string") {
+                            "bool" => match value {
+                                "true" => Property::Bool(true),
+                                "false" => Property::Bool(false),
+                                _ => return Err(bad_property(name)),
+                            },
+                            "int" => Property::Int(value.parse().map_err(|_| bad_property(name))?),
+                            "float" => {
+                                Property::Float(value.parse().map_err(|_| bad_property(name))?)
+                            }
+                            _ => Property::String(value.to_owned()),
+                        };
+                        result.set_property(gid, name.to_owned(), value);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(result)
+}
+
+impl TiledMap {
+    fn new(width: i32, height: i32) -> Result<Self, TiledError> {
+        if width <= 0 || height <= 0 {
+            return Err(TiledError::InvalidData("map size"));
+        }
+        Ok(TiledMap {
+            width,
+            height,
+            layers: vec![],
+            properties: HashMap::new(),
+        })
+    }
+
+    fn add_layer(&mut self, name: String, mut data: Vec<u32>) -> Result<(), TiledError> {
+        if data.len() != self.width as usize * self.height as usize {
+            return Err(TiledError::InvalidData("layer size"));
+        }
+        for gid in &mut data {
+            *gid &= !FLAGS;
+        }
+        self.layers.push(TileLayer { name, data });
+        Ok(())
+    }
+
+    fn set_property(&mut self, gid: u32, name: String, value: Property) {
+        self.properties.entry(gid).or_default().insert(name, value);
+    }
+}
+
+fn number<T: std::str::FromStr>(node: roxmltree::Node, name: &str) -> Result<T, TiledError> {
+    node.attribute(name)
+        .and_then(|v| v.parse().ok())
+        .ok_or(TiledError::InvalidData("attribute"))
+}
+
+/// Computes the global ID of a tile from its tileset's first global ID and its local ID.
+fn tile_gid(firstgid: u32, id: u32) -> Result<u32, TiledError> {
+    firstgid.checked_add(id).ok_or_else(|| {
+        TiledError::InvalidTileset(format!("tile {} is out of the global ID range", id))
+    })
+}
+
+fn bad_property(name: &str) -> TiledError {
+    TiledError::InvalidTileset(format!("property {:?} has an invalid value", name))
+}
+
+/// Decodes uncompressed base64 layer data into little-endian global tile IDs.
+fn base64_gids(s: &str) -> Result<Vec<u32>, TiledError> {
+    let mut bytes = vec![];
+    let (mut acc, mut bits) = (0u32, 0);
+    for c in s.bytes().filter(|c| !c.is_ascii_whitespace() && *c != b'=') {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err(TiledError::InvalidData("base64 layer data")),
+        };
+        acc = acc << 6 | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+        }
+    }
+    if bytes.len() % 4 != 0 {
+        return Err(TiledError::InvalidData("base64 layer data"));
+    }
+    Ok(bytes
+        .chunks(4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
+
+#[derive(Debug)]
+pub enum TiledError {
+    Stdio(std::io::Error),
+    Json(serde_json::Error),
+    Xml(roxmltree::Error),
+    ParseError(std::num::ParseIntError),
+    InvalidData(&'static str),
+    InvalidTileset(String),
+    Unsupported(&'static str),
+    MissingLayer(String),
+    MissingProperty {
+        x: i32,
+        y: i32,
+        gid: u32,
+        name: String,
+    },
+    InvalidProperty {
+        x: i32,
+        y: i32,
+        gid: u32,
+        name: String,
+    },
+}
+
+impl From<std::io::Error> for TiledError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Stdio(e)
+    }
+}
+
+impl From<serde_json::Error> for TiledError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl From<roxmltree::Error> for TiledError {
+    fn from(e: roxmltree::Error) -> Self {
+        Self::Xml(e)
+    }
+}
+
+impl From<std::num::ParseIntError> for TiledError {
+    fn from(e: std::num::ParseIntError) -> Self {
+        Self::ParseError(e)
+    }
+}
+
+impl std::fmt::Display for TiledError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stdio(e) => write!(f, "{}", e),
+            Self::Json(e) => write!(f, "{}", e),
+            Self::Xml(e) => write!(f, "{}", e),
+            Self::ParseError(e) => write!(f, "{}", e),
+            Self::InvalidData(what) => write!(f, "Invalid {}", what),
+            Self::InvalidTileset(e) => write!(f, "Invalid tileset: {}", e),
+            Self::Unsupported(what) => write!(f, "Unsupported feature: {}", what),
+            Self::MissingLayer(name) => write!(f, "Missing layer {:?}", name),
+            Self::MissingProperty { x, y, gid, name } => write!(
+                f,
+                "Tile {} at ({}, {}) is missing property {:?}",
+                gid, x, y, name
+            ),
+            Self::InvalidProperty { x, y, gid, name } => write!(
+                f,
+                "Tile {} at ({}, {}) has an invalid value for property {:?}",
+                gid, x, y, name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TiledError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Stdio(e) => Some(e),
+            Self::Json(e) => Some(e),
+            Self::Xml(e) => Some(e),
+            Self::ParseError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a 4x3 map with a ground layer of grass (1), water (2) and rock (3), and a bridge layer. the
+    // bridge tile is flipped horizontally.
+    const JSON: &str = r#"{
+        "width": 4, "height": 3, "infinite": false, "orientation": "orthogonal",
+        "layers": [
+            {"type": "tilelayer", "name": "ground", "width": 4, "height": 3,
+             "data": [1, 1, 2, 1, 1, 3, 2, 1, 1, 1, 2, 1]},
+            {"type": "group", "name": "decor", "layers": [
+                {"type": "tilelayer", "name": "bridges", "width": 4, "height": 3,
+                 "encoding": "base64", "data": "AAAAAAAAAAAEAACAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA"}
+            ]},
+            {"type": "objectgroup", "name": "spawns", "objects": []}
+        ],
+        "tilesets": [{
+            "firstgid": 1, "name": "terrain",
+            "tiles": [
+                {"id": 0, "properties": [
+                    {"name": "walkable", "type": "bool", "value": true},
+                    {"name": "cost", "type": "float", "value": 1.5}]},
+                {"id": 1, "properties": [
+                    {"name": "walkable", "type": "bool", "value": false},
+                    {"name": "water", "type": "bool", "value": true}]},
+                {"id": 2, "properties": [{"name": "walkable", "type": "bool", "value": false}]},
+                {"id": 3, "properties": [{"name": "cost", "type": "int", "value": 2}]}
+            ]
+        }]
+    }"#;
+
+    const TMX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <map version="1.10" orientation="orthogonal" width="4" height="3" infinite="0">
+          <tileset firstgid="1" name="terrain">
+            <tile id="0"><properties>
+              <property name="walkable" type="bool" value="true"/>
+              <property name="cost" type="float" value="1.5"/>
+            </properties></tile>
+            <tile id="1"><properties>
+              <property name="walkable" type="bool" value="false"/>
+              <property name="water" type="bool" value="true"/>
+            </properties></tile>
+            <tile id="2"><properties>
+              <property name="walkable" type="bool" value="false"/>
+            </properties></tile>
+            <tile id="3"><properties>
+              <property name="cost" type="int" value="2"/>
+            </properties></tile>
+          </tileset>
+          <layer id="1" name="ground" width="4" height="3">
+            <data encoding="csv">
+        1,1,2,1,
+        1,3,2,1,
+        1,1,2,1
+        </data>
+          </layer>
+          <group name="decor">
+            <layer id="2" name="bridges" width="4" height="3">
+              <data>
+                <tile/><tile/><tile gid="2147483652"/><tile/>
+                <tile/><tile/><tile/><tile/>
+                <tile/><tile/><tile/><tile/>
+              </data>
+            </layer>
+          </group>
+        </map>"#;
+
+    #[test]
+    fn check_formats_agree() {
+        let json = parse_tiled_json(JSON).unwrap();
+        let tmx = parse_tmx(TMX).unwrap();
+        assert_eq!(json, tmx);
+        assert_eq!(json.layers().len(), 2);
+        assert_eq!(json.layer("bridges").unwrap().data[2], 4);
+        assert_eq!(json.tile_property(4, "cost"), Some(&Property::Int(2)));
+    }
+
+    #[test]
+    fn check_rules() {
+        let map = parse_tiled_json(JSON).unwrap();
+        let walkable = map.walkable_bitgrid("ground").unwrap();
+        assert_eq!(walkable.to_string(), "..#.\n.##.\n..#.\n");
+
+        let costs = map.cost_grid("ground").unwrap();
+        assert_eq!(costs.get(0, 0), Some(&1.5));
+        assert_eq!(costs.get(2, 0), None);
+
+        // water is walkable where there is a bridge
+        let combined = map
+            .to_bitgrid(|cell| {
+                let water = cell.property("ground", "water")? == Some(&Property::Bool(true));
+                let bridge = cell.gid("bridges")? != 0;
+                Ok(!(cell.walkable("ground")? || water && bridge))
+            })
+            .unwrap();
+        assert_eq!(combined.to_string(), "....\n.##.\n..#.\n");
+
+        // the bridge tile has no walkable property
+        match map.walkable_bitgrid("bridges") {
+            Err(TiledError::MissingProperty { x, y, gid, name }) => {
+                assert_eq!((x, y, gid, &*name), (2, 0, 4, "walkable"));
+            }
+            r => panic!("expected a missing property error, got {:?}", r.map(|_| ())),
+        }
+        assert!(matches!(
+            map.walkable_bitgrid("roofs"),
+            Err(TiledError::MissingLayer(_))
+        ));
+    }
+
+    #[test]
+    fn check_other_property_types() {
+        let json = r##"{"width": 1, "height": 1, "layers": [], "tilesets": [{"firstgid": 1,
+            "tiles": [{"id": 0, "properties": [
+                {"name": "spawn", "type": "object", "value": 7},
+                {"name": "tint", "type": "color", "value": "#ff00ff00"},
+                {"name": "door", "type": "class", "propertytype": "Door",
+                 "value": {"locked": true}}]}]}]}"##;
+        let tmx = r##"<map width="1" height="1">
+            <tileset firstgid="1"><tile id="0"><properties>
+              <property name="spawn" type="object" value="7"/>
+              <property name="tint" type="color" value="#ff00ff00"/>
+              <property name="door" type="class" propertytype="Door"><properties>
+                <property name="locked" type="bool" value="true"/>
+              </properties></property>
+            </properties></tile></tileset>
+        </map>"##;
+        for map in [parse_tiled_json(json).unwrap(), parse_tmx(tmx).unwrap()] {
+            let spawn = Some(&Property::String("7".to_owned()));
+            assert_eq!(map.tile_property(1, "spawn"), spawn);
+            let tint = Some(&Property::String("#ff00ff00".to_owned()));
+            assert_eq!(map.tile_property(1, "tint"), tint);
+            assert!(map.tile_property(1, "door").is_some());
+            assert_eq!(map.tile_property(1, "locked"), None);
+        }
+    }
+
+    #[test]
+    fn check_errors() {
+        let external = r#"{"width": 1, "height": 1, "layers": [],
+            "tilesets": [{"firstgid": 1, "source": "terrain.tsj"}]}"#;
+        assert!(matches!(
+            parse_tiled_json(external),
+            Err(TiledError::Unsupported(_))
+        ));
+        let short = r#"{"width": 2, "height": 1,
+            "layers": [{"type": "tilelayer", "name": "a", "data": [1]}]}"#;
+        assert!(matches!(
+            parse_tiled_json(short),
+            Err(TiledError::InvalidData("layer size"))
+        ));
+        let bad_bool = r#"<map width="1" height="1">
+            <tileset firstgid="1"><tile id="0"><properties>
+              <property name="walkable" type="bool" value="yes"/>
+            </properties></tile></tileset>
+        </map>"#;
+        assert!(matches!(
+            parse_tmx(bad_bool),
+            Err(TiledError::InvalidTileset(_))
+        ));
+        let overflow = r#"{"width": 1, "height": 1, "layers": [], "tilesets": [{
+            "firstgid": 4294967295, "tiles": [{"id": 1, "properties": [
+                {"name": "walkable", "type": "bool", "value": true}]}]}]}"#;
+        assert!(matches!(
+            parse_tiled_json(overflow),
+            Err(TiledError::InvalidTileset(_))
+        ));
+        let overflow = r#"<map width="1" height="1">
+            <tileset firstgid="4294967295"><tile id="1"><properties>
+              <property name="walkable" type="bool" value="true"/>
+            </properties></tile></tileset>
+        </map>"#;
+        assert!(matches!(
+            parse_tmx(overflow),
+            Err(TiledError::InvalidTileset(_))
+        ));
+        assert!(matches!(parse_tiled_json("{"), Err(TiledError::Json(_))));
+        assert!(matches!(parse_tmx("<map"), Err(TiledError::Xml(_))));
+    }
+}
@@ -2,6 +2,7 @@ use std::f64::consts::SQRT_2;
 
 use enumset::EnumSetType;
 
+use crate::domains::{ComponentMap, DirectedGraph};
 use crate::expansion_policy::ExpansionPolicy;
 use crate::node_pool::NodePool;
 use crate::{astar_unchecked, Owner};
@@ -42,6 +43,39 @@ pub fn grid_search<N, E>(
     }
 }
 
+/// Like `grid_search`, but returns immediately if `components` shows that the goal is unreachable
+/// from the source, rather than exploring the whole component of the source. Returns whether the
+/// goal is reachable.
+///
+/// note: `components` must describe the map the expansion policy searches, and the expansion policy
+/// must not allow corner cutting.
+pub fn reachable_grid_search<N, E>(
+    pool: &mut N,
+    owner: &mut Owner,
+    expansion_policy: &mut E,
+    components: &ComponentMap,
+    h: impl FnMut((i32, i32)) -> f64,
+    source: (i32, i32),
+    goal: (i32, i32),
+) -> bool
+where
+    N: NodePool<(i32, i32)> + GridDomain,
+    E: ExpansionPolicy<(i32, i32)> + GridDomain,
+{
+    assert!(components.width() == expansion_policy.width());
+    assert!(components.height() == expansion_policy.height());
+    let in_bounds = |(x, y): (i32, i32)| {
+        (0..components.width()).contains(&x) && (0..components.height()).contains(&y)
+    };
+    if !in_bounds(goal) || !components.same_component(source, goal) {
+        // leave the pool empty so that the goal is not found in it
+        pool.reset(owner);
+        return false;
+    }
+    grid_search(pool, owner, expansion_policy, h, source, goal);
+    true
+}
+
 /// Indicates that the implementing type guarantees the following invariants:
 ///
 /// If `Self` is a `NodePool<usize>`:
@@ -75,6 +109,129 @@ pub fn index_search<N, E>(
     }
 }
 
+/// The storage layout of a hexagonal grid of pointy-topped hexes.
+///
+/// Hex cells are always identified by their axial coordinates `(q, r)`, where the `r` axis runs
+/// along the rows. The layout determines which cells are part of a `width` by `height` map, and
+/// where they are stored.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HexLayout {
+    /// The map is a rhombus; `(q, r)` is stored at `(q, r)`.
+    Axial,
+    /// The map is a rectangle in which the odd rows are shoved half a cell to the right.
+    OddRows,
+    /// The map is a rectangle in which the even rows are shoved half a cell to the right.
+    EvenRows,
+}
+
+impl HexLayout {
+    /// Converts axial coordinates to the storage (offset) coordinates of this layout.
+    #[inline(always)]
+    pub fn to_offset(self, (q, r): (i32, i32)) -> (i32, i32) {
+        match self {
+            HexLayout::Axial => (q, r),
+            HexLayout::OddRows => (q + (r >> 1), r),
+            HexLayout::EvenRows => (q + ((r + 1) >> 1), r),
+        }
+    }
+
+    /// Converts storage (offset) coordinates of this layout to axial coordinates.
+    #[inline(always)]
+    pub fn from_offset(self, (x, y): (i32, i32)) -> (i32, i32) {
+        match self {
+            HexLayout::Axial => (x, y),
+            HexLayout::OddRows => (x - (y >> 1), y),
+            HexLayout::EvenRows => (x - ((y + 1) >> 1), y),
+        }
+    }
+}
+
+/// The axial offsets of the six neighbors of a hex cell, starting east and going clockwise.
+pub const HEX_NEIGHBORS: [(i32, i32); 6] = [(1, 0), (0, 1), (-1, 1), (-1, 0), (0, -1), (1, -1)];
+
+/// Indicates that the implementing type guarantees the following invariants:
+///
+/// If `Self` is a `NodePool<(i32, i32)>`:
+/// - All axial ids whose `self.layout()` offset coordinates are from `(0, 0)` inclusive to
+///   `(self.width(), self.height())` exclusive are in-bounds.
+///
+/// If `Self` is an `ExpansionPolicy<(i32, i32)>`:
+/// - All axial ids whose `self.layout()` offset coordinates are from `(0, 0)` inclusive to
+///   `(self.width(), self.height())` exclusive are in-bounds.
+/// - The ids of the destinations of all edges produced by `expand_unchecked` are in-bounds.
+pub unsafe trait HexDomain {
+    fn width(&self) -> i32;
+    fn height(&self) -> i32;
+    fn layout(&self) -> HexLayout;
+}
+
+pub fn hex_search<N, E>(
+    pool: &mut N,
+    owner: &mut Owner,
+    expansion_policy: &mut E,
+    h: impl FnMut((i32, i32)) -> f64,
+    source: (i32, i32),
+    goal: (i32, i32),
+) where
+    N: NodePool<(i32, i32)> + HexDomain,
+    E: ExpansionPolicy<(i32, i32)> + HexDomain,
+{
+    assert!(pool.layout() == expansion_policy.layout());
+    assert!(pool.width() >= expansion_policy.width());
+    assert!(pool.height() >= expansion_policy.height());
+    let (x, y) = expansion_policy.layout().to_offset(source);
+    assert!(x >= 0 && x < expansion_policy.width());
+    assert!(y >= 0 && y < expansion_policy.height());
+    unsafe {
+        // SAFETY: We check that the pool uses the same layout and is large enough for the
+        //         expansion policy. The expansion policy guarantees that it never produces edges
+        //         leading out-of-bounds. We check that the source vertex is in-bounds.
+        astar_unchecked(pool, owner, expansion_policy, h, source, goal)
+    }
+}
+
+/// Indicates that the implementing type guarantees the following invariants:
+///
+/// If `Self` is a `NodePool<(i32, i32, i32)>`:
+/// - All ids from `(0, 0, 0)` inclusive to `(self.width(), self.height(), self.depth())` exclusive
+///   are in-bounds.
+///
+/// If `Self` is an `ExpansionPolicy<(i32, i32, i32)>`:
+/// - All ids from `(0, 0, 0)` inclusive to `(self.width(), self.height(), self.depth())` exclusive
+///   are in-bounds.
+/// - The ids of the destinations of all edges produced by `expand_unchecked` are in-bounds.
+pub unsafe trait VoxelDomain {
+    fn width(&self) -> i32;
+    fn height(&self) -> i32;
+    fn depth(&self) -> i32;
+}
+
+pub fn voxel_search<N, E>(
+    pool: &mut N,
+    owner: &mut Owner,
+    expansion_policy: &mut E,
+    h: impl FnMut((i32, i32, i32)) -> f64,
+    source: (i32, i32, i32),
+    goal: (i32, i32, i32),
+) where
+    N: NodePool<(i32, i32, i32)> + VoxelDomain,
+    E: ExpansionPolicy<(i32, i32, i32)> + VoxelDomain,
+{
+    assert!(pool.width() >= expansion_policy.width());
+    assert!(pool.height() >= expansion_policy.height());
+    assert!(pool.depth() >= expansion_policy.depth());
+    assert!(source.0 >= 0 && source.0 < expansion_policy.width());
+    assert!(source.1 >= 0 && source.1 < expansion_policy.height());
+    assert!(source.2 >= 0 && source.2 < expansion_policy.depth());
+    unsafe {
+        // SAFETY: We check that the pool is large enough for the expansion policy. The expansion
+        //         policy guarantees that it never produces edges leading out-of-bounds. We check
+        //         that the source vertex is in-bounds.
+        astar_unchecked(pool, owner, expansion_policy, h, source, goal)
+    }
+}
+
 #[derive(Debug, EnumSetType)]
 pub enum Direction {
     NorthWest,
@@ -211,6 +368,113 @@ pub fn manhattan_heuristic((tx, ty): (i32, i32), scale: f64) -> impl Fn((i32, i3
     }
 }
 
+/// The cost of the shortest 26-connected path through open space.
+pub fn octile_heuristic_3d(
+    (tx, ty, tz): (i32, i32, i32),
+    scale: f64,
+) -> impl Fn((i32, i32, i32)) -> f64 {
+    const SQRT_3: f64 = 1.7320508075688772;
+    move |(x, y, z)| {
+        let mut d = [(tx - x).abs(), (ty - y).abs(), (tz - z).abs()];
+        d.sort_unstable();
+        let [small, medium, large] = d;
+        let diagonal_3d = small;
+        let diagonal_2d = medium - small;
+        let ortho = large - medium;
+        (ortho as f64 + SQRT_2 * diagonal_2d as f64 + SQRT_3 * diagonal_3d as f64) * scale
+    }
+}
+
+pub fn manhattan_heuristic_3d(
+    (tx, ty, tz): (i32, i32, i32),
+    scale: f64,
+) -> impl Fn((i32, i32, i32)) -> f64 {
+    move |(x, y, z)| {
+        let d = (tx - x).abs() + (ty - y).abs() + (tz - z).abs();
+        d as f64 * scale
+    }
+}
+
+pub fn euclidean_heuristic_3d(
+    (tx, ty, tz): (i32, i32, i32),
+    scale: f64,
+) -> impl Fn((i32, i32, i32)) -> f64 {
+    move |(x, y, z)| {
+        let (dx, dy, dz) = ((tx - x) as f64, (ty - y) as f64, (tz - z) as f64);
+        (dx * dx + dy * dy + dz * dz).sqrt() * scale
+    }
+}
+
+/// Euclidean distance between the positions stored as vertex data.
+pub fn euclidean_graph_heuristic(
+    graph: &DirectedGraph<(f64, f64)>,
+    goal: usize,
+    scale: f64,
+) -> impl Fn(usize) -> f64 + '_ {
+    let (tx, ty) = *graph.vertex_data(goal);
+    move |v| distance(*graph.vertex_data(v), (tx, ty)) * scale
+}
+
+/// Great-circle distance in metres between the `(longitude, latitude)` positions stored as vertex
+/// data. `unit` is the size of one coordinate unit in degrees, e.g. `1e-6` for DIMACS road
+/// networks.
+///
+/// note: distances are measured on a sphere with the smallest radius of curvature of the earth's
+///       ellipsoid (the meridional radius at the equator), which keeps them below distances
+///       measured on the ellipsoid.
+pub fn great_circle_graph_heuristic(
+    graph: &DirectedGraph<(f64, f64)>,
+    goal: usize,
+    unit: f64,
+    scale: f64,
+) -> impl Fn(usize) -> f64 + '_ {
+    const RADIUS: f64 = 6_335_439.0;
+    let radians = unit.to_radians();
+    let (tx, ty) = *graph.vertex_data(goal);
+    let (tlon, tlat) = (tx * radians, ty * radians);
+    let tcos = tlat.cos();
+    move |v| {
+        let &(x, y) = graph.vertex_data(v);
+        let (lon, lat) = (x * radians, y * radians);
+        // haversine formula
+        let a = ((lat - tlat) / 2.0).sin().powi(2)
+            + lat.cos() * tcos * ((lon - tlon) / 2.0).sin().powi(2);
+        2.0 * RADIUS * a.sqrt().min(1.0).asin() * scale
+    }
+}
+
+/// Twice the signed area of the triangle `abc`; positive when `c` is to the left of `a -> b`.
+#[inline(always)]
+pub(crate) fn cross(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> f64 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+#[inline(always)]
+pub(crate) fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
 pub fn zero_heuristic<VertexId>() -> impl Fn(VertexId) -> f64 {
     |_| 0.0
 }
+
+/// The number of steps between two hex cells given in axial coordinates.
+pub fn hex_distance((q1, r1): (i32, i32), (q2, r2): (i32, i32)) -> i32 {
+    let dq = q1 - q2;
+    let dr = r1 - r2;
+    (dq.abs() + dr.abs() + (dq + dr).abs()) / 2
+}
+
+pub fn hex_heuristic(goal: (i32, i32), scale: f64) -> impl Fn((i32, i32)) -> f64 {
+    move |v| hex_distance(v, goal) as f64 * scale
+}
+
+/// Asserts that two path costs are equal up to rounding error, where `None` means there is no path.
+#[cfg(test)]
+#[track_caller]
+pub(crate) fn assert_same_cost(actual: Option<f64>, expected: Option<f64>) {
+    match (actual, expected) {
+        (Some(a), Some(e)) => assert!((a - e).abs() < 1e-9, "{} != {}", a, e),
+        _ => assert_eq!(actual, expected),
+    }
+}